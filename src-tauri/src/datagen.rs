@@ -0,0 +1,828 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+
+use crate::jobs::{JobHandle, JobSummary};
+use crate::sql_admin::{num_field, text_field};
+use crate::sql_conn::SqlConnection;
+use crate::sql_dialect;
+use crate::{ColumnDef, ConnectionConfig};
+
+const MAX_PARAMS: usize = 65535;
+/// Parent rows sampled per foreign key
+const MAX_PARENT_ROWS: usize = 10000;
+/// Attempts to generate a row that doesn't collide with a unique index
+const MAX_ATTEMPTS: usize = 100;
+/// Share of NULLs in nullable columns when the rule doesn't say
+const DEFAULT_NULL_RATIO: f64 = 0.1;
+
+pub(crate) const FIRST_NAMES: &[&str] = &[
+    "James", "Mary", "John", "Linda", "Robert", "Emma", "Michael", "Olivia", "David", "Sophia",
+    "Wei", "Fang", "Lei", "Min", "Jing", "Yan", "Hao", "Xin", "Kenji", "Yuki",
+];
+pub(crate) const LAST_NAMES: &[&str] = &[
+    "Smith", "Johnson", "Brown", "Garcia", "Miller", "Davis", "Wilson", "Taylor", "Clark", "Lee",
+    "Wang", "Li", "Zhang", "Liu", "Chen", "Yang", "Huang", "Zhao", "Tanaka", "Sato",
+];
+pub(crate) const WORDS: &[&str] = &[
+    "alpha", "bravo", "cloud", "delta", "ember", "forest", "garden", "harbor", "island", "jungle",
+    "kernel", "lemon", "meadow", "nebula", "orbit", "pepper", "quartz", "river", "summit",
+    "thunder", "umber", "valley", "willow", "xenon", "yellow", "zephyr",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GenerateRule {
+    pub column: String,
+    // "skip", "null", "constant", "sequence", "int", "float", "bool", "date", "datetime",
+    // "time", "uuid", "email", "name", "first_name", "last_name", "phone", "word",
+    // "text", "enum", "json"
+    pub generator: String,
+    pub min: Option<f64>, // Numbers and sequence start; years for dates
+    pub max: Option<f64>,
+    pub values: Option<Vec<String>>, // Choices for "enum", the value for "constant"
+    pub null_ratio: Option<f64>,     // Share of NULLs, nullable columns only
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GenerateOptions {
+    pub seed: Option<u64>,         // Same seed, same rows; default random
+    pub batch_size: Option<usize>, // Rows per INSERT / transaction, default 500
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct GeneratedColumn {
+    pub column: String,
+    pub generator: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct GenerateSummary {
+    pub inserted: u64,
+    pub seed: u64, // Pass back in to repeat the run
+    pub columns: Vec<GeneratedColumn>,
+}
+
+impl From<GenerateSummary> for JobSummary {
+    fn from(summary: GenerateSummary) -> Self {
+        JobSummary {
+            processed: summary.inserted,
+            details: serde_json::to_value(&summary).ok(),
+        }
+    }
+}
+
+/// SplitMix64, small and stable across platforms so a seed always gives the same data
+pub(crate) struct Rng(pub(crate) u64);
+
+impl Rng {
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    fn float(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in [min, max]
+    pub(crate) fn range(&mut self, min: i64, max: i64) -> i64 {
+        if max <= min {
+            return min;
+        }
+        let span = (max as i128 - min as i128 + 1) as u128;
+        (min as i128 + (self.next_u64() as u128 % span) as i128) as i64
+    }
+
+    pub(crate) fn pick<'a>(&mut self, items: &'a [&'a str]) -> &'a str {
+        items[self.range(0, items.len() as i64 - 1) as usize]
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Generator {
+    Skip, // Left to the column default / auto increment
+    Null,
+    Constant(String),
+    Sequence(i64), // Next value
+    Int(i64, i64),
+    Float(f64, f64, usize), // min, max, decimal places
+    Bool,
+    Bit,            // 0 / 1 for BIT(1) columns, which take neither `true` nor a character
+    Date(i32, i32), // Year range
+    DateTime(i32, i32),
+    Time,
+    Uuid,
+    Email,
+    Name,
+    FirstName,
+    LastName,
+    Phone,
+    Word,
+    Text,
+    Choice(Vec<String>),
+    Json,
+}
+
+impl Generator {
+    fn label(&self) -> &'static str {
+        match self {
+            Generator::Skip => "skip",
+            Generator::Null => "null",
+            Generator::Constant(_) => "constant",
+            Generator::Sequence(_) => "sequence",
+            Generator::Int(..) => "int",
+            Generator::Float(..) => "float",
+            Generator::Bool | Generator::Bit => "bool",
+            Generator::Date(..) => "date",
+            Generator::DateTime(..) => "datetime",
+            Generator::Time => "time",
+            Generator::Uuid => "uuid",
+            Generator::Email => "email",
+            Generator::Name => "name",
+            Generator::FirstName => "first_name",
+            Generator::LastName => "last_name",
+            Generator::Phone => "phone",
+            Generator::Word => "word",
+            Generator::Text => "text",
+            Generator::Choice(_) => "enum",
+            Generator::Json => "json",
+        }
+    }
+
+    /// A value for one row. `serial` is unique per generated row and used as a suffix
+    /// for text columns in a unique index.
+    fn generate(&mut self, rng: &mut Rng, db_type: &str, serial: Option<u64>) -> Option<String> {
+        let suffix = |base: String| match serial {
+            Some(n) => format!("{}_{}", base, n),
+            None => base,
+        };
+        Some(match self {
+            Generator::Skip | Generator::Null => return None,
+            Generator::Constant(v) => v.clone(),
+            Generator::Sequence(next) => {
+                *next += 1;
+                (*next - 1).to_string()
+            }
+            Generator::Int(min, max) => rng.range(*min, *max).to_string(),
+            Generator::Float(min, max, scale) => {
+                format!("{:.*}", *scale, *min + rng.float() * (*max - *min))
+            }
+            Generator::Bool => {
+                let b = rng.range(0, 1) == 1;
+                match db_type {
+                    "mysql" => if b { "1" } else { "0" }.to_string(),
+                    _ => b.to_string(),
+                }
+            }
+            Generator::Bit => rng.range(0, 1).to_string(),
+            Generator::Date(from, to) => random_date(rng, *from, *to).to_string(),
+            Generator::DateTime(from, to) => {
+                let date = random_date(rng, *from, *to);
+                let secs = rng.range(0, 86399) as u32;
+                date.and_hms_opt(secs / 3600, secs / 60 % 60, secs % 60)
+                    .unwrap_or_default()
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            }
+            Generator::Time => {
+                let secs = rng.range(0, 86399);
+                format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+            }
+            Generator::Uuid => {
+                let mut bytes = [0u8; 16];
+                bytes[..8].copy_from_slice(&rng.next_u64().to_le_bytes());
+                bytes[8..].copy_from_slice(&rng.next_u64().to_le_bytes());
+                uuid::Builder::from_random_bytes(bytes)
+                    .into_uuid()
+                    .to_string()
+            }
+            Generator::Email => {
+                let local = format!(
+                    "{}.{}",
+                    rng.pick(FIRST_NAMES).to_lowercase(),
+                    rng.pick(LAST_NAMES).to_lowercase()
+                );
+                let local = match serial {
+                    Some(n) => format!("{}.{}", local, n),
+                    None => format!("{}{}", local, rng.range(1, 999)),
+                };
+                format!("{}@example.com", local)
+            }
+            Generator::Name => suffix(format!(
+                "{} {}",
+                rng.pick(FIRST_NAMES),
+                rng.pick(LAST_NAMES)
+            )),
+            Generator::FirstName => suffix(rng.pick(FIRST_NAMES).to_string()),
+            Generator::LastName => suffix(rng.pick(LAST_NAMES).to_string()),
+            Generator::Phone => match serial {
+                // Keep the 11 digits of a mobile number while staying unique
+                Some(n) => format!("1{}{:09}", rng.pick(&["3", "5", "8"]), n % 1_000_000_000),
+                None => format!(
+                    "1{}{:09}",
+                    rng.pick(&["3", "5", "8"]),
+                    rng.range(0, 999_999_999)
+                ),
+            },
+            Generator::Word => suffix(rng.pick(WORDS).to_string()),
+            Generator::Text => {
+                let count = rng.range(3, 12);
+                let words: Vec<&str> = (0..count).map(|_| rng.pick(WORDS)).collect();
+                suffix(words.join(" "))
+            }
+            Generator::Choice(values) => {
+                values[rng.range(0, values.len() as i64 - 1) as usize].clone()
+            }
+            Generator::Json => json!({
+                "id": rng.range(1, 100_000),
+                "tag": rng.pick(WORDS),
+                "active": rng.range(0, 1) == 1,
+            })
+            .to_string(),
+        })
+    }
+}
+
+fn random_date(rng: &mut Rng, from_year: i32, to_year: i32) -> chrono::NaiveDate {
+    let start = chrono::NaiveDate::from_ymd_opt(from_year, 1, 1).unwrap_or_default();
+    let end = chrono::NaiveDate::from_ymd_opt(to_year, 12, 31).unwrap_or(start);
+    let days = (end - start).num_days().max(0);
+    start + chrono::Duration::days(rng.range(0, days))
+}
+
+/// Length in `varchar(50)` style types
+fn type_length(type_name: &str) -> Option<usize> {
+    let inner = type_name.split_once('(')?.1.split_once(')')?.0;
+    inner.split(',').next()?.trim().parse().ok()
+}
+
+/// Quoted values of a MySQL `enum('a','b')` type
+fn enum_values(type_name: &str) -> Vec<String> {
+    let Some(inner) = type_name
+        .split_once('(')
+        .and_then(|(_, rest)| rest.rsplit_once(')'))
+        .map(|(inner, _)| inner)
+    else {
+        return Vec::new();
+    };
+    let mut values = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' if quoted && chars.peek() == Some(&'\'') => {
+                current.push('\'');
+                chars.next();
+            }
+            '\'' if quoted => {
+                values.push(std::mem::take(&mut current));
+                quoted = false;
+            }
+            '\'' => quoted = true,
+            _ if quoted => current.push(c),
+            _ => {}
+        }
+    }
+    values
+}
+
+/// Generator for a column from its exact type and, for text, its name
+fn infer(name: &str, type_name: &str, enum_labels: Option<&Vec<String>>) -> Option<Generator> {
+    let t = type_name.to_lowercase();
+    let n = name.to_lowercase();
+    let unsigned = t.contains("unsigned");
+    if let Some(labels) = enum_labels.filter(|l| !l.is_empty()) {
+        return Some(Generator::Choice(labels.clone()));
+    }
+    let base = t.split(['(', ' ']).next().unwrap_or("");
+    Some(match base {
+        "enum" | "set" => Generator::Choice(enum_values(type_name)),
+        "bool" | "boolean" => Generator::Bool,
+        "tinyint" if t.starts_with("tinyint(1)") => Generator::Bool,
+        "bit" if type_length(&t).unwrap_or(1) == 1 => Generator::Bit,
+        "tinyint" => Generator::Int(0, if unsigned { 255 } else { 127 }),
+        "smallint" | "int2" => Generator::Int(0, if unsigned { 65535 } else { 32767 }),
+        "mediumint" | "int" | "integer" | "int4" | "bigint" | "int8" => {
+            Generator::Int(1, 1_000_000)
+        }
+        "year" => Generator::Int(1970, 2030),
+        "decimal" | "numeric" => {
+            let scale: usize = t
+                .split_once(',')
+                .and_then(|(_, s)| s.trim_end_matches(')').trim().parse().ok())
+                .unwrap_or(2);
+            let precision = type_length(&t).unwrap_or(10);
+            let digits = precision.saturating_sub(scale).min(6) as i32;
+            Generator::Float(0.0, 10f64.powi(digits) - 1.0, scale)
+        }
+        "float" | "double" | "real" | "float4" | "float8" => Generator::Float(0.0, 10000.0, 2),
+        "date" => Generator::Date(2020, 2025),
+        "datetime" | "timestamp" => Generator::DateTime(2020, 2025),
+        "time" => Generator::Time,
+        "uuid" => Generator::Uuid,
+        "json" | "jsonb" => Generator::Json,
+        "char" | "varchar" | "character" | "text" | "tinytext" | "mediumtext" | "longtext"
+        | "nvarchar" | "nchar" => {
+            if n.contains("email") || n.contains("mail") {
+                Generator::Email
+            } else if n.contains("phone") || n.contains("mobile") || n.contains("tel") {
+                Generator::Phone
+            } else if n.contains("uuid") || n.contains("guid") {
+                Generator::Uuid
+            } else if n.contains("first_name") || n == "firstname" {
+                Generator::FirstName
+            } else if n.contains("last_name") || n == "lastname" || n.contains("surname") {
+                Generator::LastName
+            } else if n.contains("name") {
+                Generator::Name
+            } else if base.contains("text") {
+                Generator::Text
+            } else if type_length(&t).is_some_and(|len| len < 16) {
+                Generator::Word
+            } else {
+                Generator::Text
+            }
+        }
+        _ => return None,
+    })
+}
+
+fn from_rule(rule: &GenerateRule) -> Result<Generator, String> {
+    let min = rule.min;
+    let max = rule.max;
+    let year = |v: Option<f64>, default: i32| v.map(|y| y as i32).unwrap_or(default);
+    let values = rule.values.clone().unwrap_or_default();
+    Ok(match rule.generator.as_str() {
+        "skip" => Generator::Skip,
+        "null" => Generator::Null,
+        "constant" => Generator::Constant(
+            values
+                .into_iter()
+                .next()
+                .ok_or_else(|| format!("列 {} 的 constant 规则缺少值", rule.column))?,
+        ),
+        "sequence" => Generator::Sequence(min.unwrap_or(1.0) as i64),
+        "int" => Generator::Int(min.unwrap_or(0.0) as i64, max.unwrap_or(1_000_000.0) as i64),
+        "float" => Generator::Float(min.unwrap_or(0.0), max.unwrap_or(10000.0), 2),
+        "bool" => Generator::Bool,
+        "date" => Generator::Date(year(min, 2020), year(max, 2025)),
+        "datetime" => Generator::DateTime(year(min, 2020), year(max, 2025)),
+        "time" => Generator::Time,
+        "uuid" => Generator::Uuid,
+        "email" => Generator::Email,
+        "name" => Generator::Name,
+        "first_name" => Generator::FirstName,
+        "last_name" => Generator::LastName,
+        "phone" => Generator::Phone,
+        "word" => Generator::Word,
+        "text" => Generator::Text,
+        "enum" if !values.is_empty() => Generator::Choice(values),
+        "enum" => return Err(format!("列 {} 的 enum 规则缺少候选值", rule.column)),
+        "json" => Generator::Json,
+        other => return Err(format!("Unsupported generator: {}", other)),
+    })
+}
+
+fn to_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// `expr` cast to text on the server, so every key type reads back exactly
+fn text_cast(db_type: &str, expr: &str) -> String {
+    if db_type == "mysql" {
+        format!("CAST({} AS CHAR)", expr)
+    } else {
+        format!("({})::text", expr)
+    }
+}
+
+/// Foreign keys of `table` as (constraint, columns, parent table, parent columns)
+async fn foreign_keys(
+    conn: &mut SqlConnection,
+    table: &str,
+) -> Result<Vec<(String, Vec<String>, String, Vec<String>)>, String> {
+    let sql = match conn {
+        SqlConnection::MySql(_) => {
+            "SELECT CONSTRAINT_NAME AS name, COLUMN_NAME AS col,
+                    REFERENCED_TABLE_NAME AS ref_table, REFERENCED_COLUMN_NAME AS ref_col
+             FROM information_schema.KEY_COLUMN_USAGE
+             WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND REFERENCED_TABLE_NAME IS NOT NULL
+             ORDER BY CONSTRAINT_NAME, ORDINAL_POSITION"
+        }
+        SqlConnection::Postgres(_) => {
+            "SELECT c.conname::text AS name, a.attname::text AS col,
+                    cf.relname::text AS ref_table, af.attname::text AS ref_col
+             FROM pg_constraint c
+             CROSS JOIN LATERAL unnest(c.conkey, c.confkey) WITH ORDINALITY AS k(col, ref, pos)
+             JOIN pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = k.col
+             JOIN pg_class cf ON cf.oid = c.confrelid
+             JOIN pg_attribute af ON af.attrelid = c.confrelid AND af.attnum = k.ref
+             WHERE c.contype = 'f' AND c.conrelid = quote_ident($1)::regclass
+             ORDER BY c.conname, k.pos"
+        }
+    };
+    let rows = conn
+        .fetch_all_params(sql, &[Value::String(table.to_string())])
+        .await?;
+    let mut keys: Vec<(String, Vec<String>, String, Vec<String>)> = Vec::new();
+    for row in &rows {
+        let name = text_field(row, "name").unwrap_or_default();
+        let col = text_field(row, "col").unwrap_or_default();
+        let ref_col = text_field(row, "ref_col").unwrap_or_default();
+        match keys.last_mut() {
+            Some(last) if last.0 == name => {
+                last.1.push(col);
+                last.3.push(ref_col);
+            }
+            _ => keys.push((
+                name,
+                vec![col],
+                text_field(row, "ref_table").unwrap_or_default(),
+                vec![ref_col],
+            )),
+        }
+    }
+    Ok(keys)
+}
+
+/// Labels of PostgreSQL enum columns, keyed by column name
+async fn pg_enum_labels(
+    conn: &mut SqlConnection,
+    table: &str,
+) -> Result<HashMap<String, Vec<String>>, String> {
+    let mut labels: HashMap<String, Vec<String>> = HashMap::new();
+    if conn.db_type() != "postgresql" {
+        return Ok(labels);
+    }
+    let rows = conn
+        .fetch_all_params(
+            "SELECT a.attname::text AS col, e.enumlabel::text AS label
+             FROM pg_attribute a
+             JOIN pg_enum e ON e.enumtypid = a.atttypid
+             WHERE a.attrelid = quote_ident($1)::regclass AND a.attnum > 0 AND NOT a.attisdropped
+             ORDER BY a.attnum, e.enumsortorder",
+            &[Value::String(table.to_string())],
+        )
+        .await?;
+    for row in &rows {
+        if let (Some(col), Some(label)) = (text_field(row, "col"), text_field(row, "label")) {
+            labels.entry(col).or_default().push(label);
+        }
+    }
+    Ok(labels)
+}
+
+/// Where a column's values come from
+enum Source {
+    Generated(Generator),
+    Parent { key: usize, position: usize }, // Column of a sampled parent row
+}
+
+struct ColumnPlan {
+    name: String,
+    source: Source,
+    null_ratio: f64,
+    max_len: Option<usize>,
+    unique_text: bool, // Needs the per-row serial to stay unique
+    bit: bool,
+}
+
+/// Insert `row_count` generated rows into `table`, one transaction per batch.
+/// Columns come from `get_columns`; `rules` override the inferred generator per column.
+/// Foreign key columns take values from existing parent rows, and unique indexes are
+/// kept unique among the generated rows (integer ones continue after the current max).
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    job: JobHandle,
+    mut conn: SqlConnection,
+    config: ConnectionConfig,
+    database: Option<String>,
+    table: String,
+    row_count: u64,
+    rules: Vec<GenerateRule>,
+    options: GenerateOptions,
+) -> Result<GenerateSummary, String> {
+    let db_type = conn.db_type();
+    // get_indexes reads the database from the config
+    let config = ConnectionConfig {
+        database: database.clone().or(config.database.clone()),
+        ..config
+    };
+    let columns: Vec<ColumnDef> =
+        crate::get_columns(config.clone(), table.clone(), database.clone()).await?;
+    if columns.is_empty() {
+        return Err(format!("表 {} 不存在或没有列", table));
+    }
+    let indexes = crate::get_indexes(config, table.clone()).await?;
+    let pg_types = conn.pg_column_types(&table).await?;
+    let enum_labels = pg_enum_labels(&mut conn, &table).await?;
+    for rule in &rules {
+        if !columns.iter().any(|c| c.name == rule.column) {
+            return Err(format!("列 {} 不存在", rule.column));
+        }
+    }
+
+    // Unique indexes over plain columns; expression indexes can't be checked here
+    let unique_sets: Vec<Vec<String>> = indexes
+        .iter()
+        .filter(|i| i.is_unique || i.is_pk)
+        .map(|i| i.columns.clone())
+        .filter(|cols| {
+            cols.iter()
+                .all(|c| columns.iter().any(|col| col.name == *c))
+        })
+        .collect();
+
+    // Sample parent rows for each foreign key
+    let mut parents: Vec<Vec<Vec<Option<String>>>> = Vec::new();
+    let mut fk_columns: HashMap<String, (usize, usize)> = HashMap::new();
+    for (name, cols, ref_table, ref_cols) in foreign_keys(&mut conn, &table).await? {
+        let quoted: Vec<String> = ref_cols
+            .iter()
+            .map(|c| sql_dialect::quote_ident(db_type, c))
+            .collect();
+        let select: Vec<String> = quoted
+            .iter()
+            .enumerate()
+            .map(|(i, c)| format!("{} AS k{}", text_cast(db_type, c), i))
+            .collect();
+        let not_null: Vec<String> = quoted
+            .iter()
+            .map(|c| format!("{} IS NOT NULL", c))
+            .collect();
+        let sql = format!(
+            "SELECT DISTINCT {} FROM {} WHERE {} LIMIT {}",
+            select.join(", "),
+            sql_dialect::quote_ident(db_type, &ref_table),
+            not_null.join(" AND "),
+            MAX_PARENT_ROWS
+        );
+        let rows: Vec<Vec<Option<String>>> = conn
+            .fetch_all(&sql)
+            .await?
+            .iter()
+            .map(|row| row.iter().map(|(_, v)| to_text(v)).collect())
+            .collect();
+        let optional = cols.iter().all(|c| {
+            columns
+                .iter()
+                .any(|col| col.name == *c && col.is_nullable.unwrap_or(true))
+        });
+        if rows.is_empty() && !optional {
+            return Err(format!(
+                "外键 {} 引用的表 {} 没有数据，请先为父表生成数据",
+                name, ref_table
+            ));
+        }
+        for (position, col) in cols.iter().enumerate() {
+            // A column in several foreign keys follows the first one
+            fk_columns
+                .entry(col.clone())
+                .or_insert((parents.len(), position));
+        }
+        parents.push(rows);
+    }
+
+    let mut plans = Vec::new();
+    for column in &columns {
+        let rule = rules.iter().find(|r| r.column == column.name);
+        let nullable = column.is_nullable.unwrap_or(true);
+        let exact_type = pg_types
+            .iter()
+            .find(|(name, _)| *name == column.name)
+            .map(|(_, t)| t.as_str())
+            .unwrap_or(&column.type_name);
+        let in_unique = unique_sets.iter().any(|set| set.contains(&column.name));
+        let single_unique = unique_sets
+            .iter()
+            .any(|set| set.len() == 1 && set[0] == column.name);
+
+        let source = match (rule, fk_columns.get(&column.name)) {
+            (Some(rule), _) => Source::Generated(from_rule(rule)?),
+            (None, _) if column.auto_increment.unwrap_or(false) => {
+                Source::Generated(Generator::Skip)
+            }
+            (None, Some(&(key, position))) => Source::Parent { key, position },
+            (None, None) => match infer(&column.name, exact_type, enum_labels.get(&column.name)) {
+                Some(Generator::Int(..)) if single_unique => {
+                    // Continue after the rows already in the table
+                    let max = format!("MAX({})", sql_dialect::quote_ident(db_type, &column.name));
+                    let sql = format!(
+                        "SELECT {} AS max_value FROM {}",
+                        text_cast(db_type, &max),
+                        sql_dialect::quote_ident(db_type, &table)
+                    );
+                    let max = conn
+                        .fetch_all(&sql)
+                        .await?
+                        .first()
+                        .and_then(|row| text_field(row, "max_value"))
+                        .and_then(|v| v.parse::<i64>().ok())
+                        .unwrap_or(0);
+                    Source::Generated(Generator::Sequence(max + 1))
+                }
+                Some(generator) => Source::Generated(generator),
+                None if nullable => Source::Generated(Generator::Null),
+                None if column.default_value.is_some() => Source::Generated(Generator::Skip),
+                None => {
+                    return Err(format!(
+                        "无法为列 {} ({}) 推断生成规则，请指定规则",
+                        column.name, column.type_name
+                    ))
+                }
+            },
+        };
+        let bit = exact_type.to_lowercase().starts_with("bit");
+        let source = match source {
+            Source::Generated(Generator::Bool) if bit => Source::Generated(Generator::Bit),
+            other => other,
+        };
+        if matches!(source, Source::Generated(Generator::Null)) && !nullable {
+            return Err(format!("列 {} 不允许 NULL", column.name));
+        }
+        let text_type = matches!(
+            source,
+            Source::Generated(
+                Generator::Name
+                    | Generator::FirstName
+                    | Generator::LastName
+                    | Generator::Email
+                    | Generator::Phone
+                    | Generator::Word
+                    | Generator::Text
+            )
+        );
+        plans.push(ColumnPlan {
+            name: column.name.clone(),
+            null_ratio: if nullable && !in_unique {
+                rule.and_then(|r| r.null_ratio)
+                    .unwrap_or(DEFAULT_NULL_RATIO)
+                    .clamp(0.0, 1.0)
+            } else {
+                0.0
+            },
+            max_len: if exact_type.to_lowercase().contains("char") {
+                type_length(exact_type)
+            } else {
+                None
+            },
+            unique_text: single_unique && text_type,
+            bit,
+            source,
+        });
+    }
+    plans.retain(|p| !matches!(p.source, Source::Generated(Generator::Skip)));
+    if plans.is_empty() {
+        return Err("没有需要生成数据的列".to_string());
+    }
+
+    let names: Vec<String> = plans.iter().map(|p| p.name.clone()).collect();
+    // PostgreSQL binds every value as text, so each placeholder is cast to the column's type.
+    // MySQL stores a string bound to a BIT column as its bytes, so '1' needs a numeric cast.
+    let templates: Vec<String> = plans
+        .iter()
+        .map(
+            |plan| match pg_types.iter().find(|(n, _)| *n == plan.name) {
+                Some((_, t)) => format!("CAST({{}} AS {})", t),
+                None if plan.bit => "CAST({} AS UNSIGNED)".to_string(),
+                None => "{}".to_string(),
+            },
+        )
+        .collect();
+    let unique_positions: Vec<Vec<usize>> = unique_sets
+        .iter()
+        .filter_map(|set| {
+            set.iter()
+                .map(|c| names.iter().position(|n| n == c))
+                .collect::<Option<Vec<usize>>>()
+        })
+        .collect();
+    let mut seen: Vec<HashSet<Vec<Option<String>>>> = vec![HashSet::new(); unique_positions.len()];
+
+    // Text suffixes for unique columns start after the rows already in the table
+    let existing = conn
+        .fetch_all(&format!(
+            "SELECT COUNT(*) AS total FROM {}",
+            sql_dialect::quote_ident(db_type, &table)
+        ))
+        .await?
+        .first()
+        .and_then(|row| num_field(row, "total"))
+        .unwrap_or(0.0) as u64;
+
+    let seed = options
+        .seed
+        .unwrap_or_else(|| chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0) as u64);
+    let mut rng = Rng(seed);
+    let batch_size = options
+        .batch_size
+        .unwrap_or(500)
+        .clamp(1, MAX_PARAMS / names.len());
+
+    let mut serial = existing;
+    let mut inserted = 0u64;
+    let mut batch: Vec<Option<String>> = Vec::with_capacity(batch_size * names.len());
+    let mut batch_rows = 0;
+    while inserted + (batch_rows as u64) < row_count {
+        let mut attempts = 0;
+        let row = loop {
+            serial += 1;
+            let picks: Vec<usize> = parents
+                .iter()
+                .map(|rows| rng.range(0, rows.len() as i64 - 1) as usize)
+                .collect();
+            let row: Vec<Option<String>> = plans
+                .iter_mut()
+                .map(|plan| match &mut plan.source {
+                    Source::Parent { key, position } => parents[*key]
+                        .get(picks[*key])
+                        .and_then(|parent| parent[*position].clone()),
+                    Source::Generated(generator) => {
+                        if plan.null_ratio > 0.0 && rng.float() < plan.null_ratio {
+                            return None;
+                        }
+                        let value = generator.generate(
+                            &mut rng,
+                            db_type,
+                            plan.unique_text.then_some(serial),
+                        )?;
+                        Some(match plan.max_len {
+                            // A unique suffix must survive, so trim those from the front
+                            Some(len) if value.chars().count() > len && plan.unique_text => {
+                                let skip = value.chars().count() - len;
+                                value.chars().skip(skip).collect()
+                            }
+                            Some(len) => value.chars().take(len).collect(),
+                            None => value,
+                        })
+                    }
+                })
+                .collect();
+
+            let keys: Vec<Vec<Option<String>>> = unique_positions
+                .iter()
+                .map(|positions| positions.iter().map(|&p| row[p].clone()).collect())
+                .collect();
+            // Keys with a NULL never collide
+            let duplicate = keys
+                .iter()
+                .zip(&seen)
+                .any(|(key, set)| key.iter().all(|v| v.is_some()) && set.contains(key));
+            if !duplicate {
+                for (key, set) in keys.into_iter().zip(seen.iter_mut()) {
+                    set.insert(key);
+                }
+                break row;
+            }
+            attempts += 1;
+            if attempts >= MAX_ATTEMPTS {
+                return Err(format!(
+                    "已生成 {} 行后无法再生成不重复的唯一键，请调整规则",
+                    inserted + batch_rows as u64
+                ));
+            }
+        };
+        batch.extend(row);
+        batch_rows += 1;
+
+        if batch_rows >= batch_size || inserted + (batch_rows as u64) >= row_count {
+            job.check_cancelled()?;
+            let sql = sql_dialect::insert_params_statement(
+                db_type, &table, &names, &templates, batch_rows,
+            );
+            conn.execute_in_transaction(&sql, &batch).await?;
+            inserted += batch_rows as u64;
+            batch.clear();
+            batch_rows = 0;
+            job.progress(
+                inserted,
+                Some(row_count),
+                Some(format!("已生成 {} / {} 行", inserted, row_count)),
+            );
+        }
+    }
+
+    Ok(GenerateSummary {
+        inserted,
+        seed,
+        columns: plans
+            .iter()
+            .map(|plan| GeneratedColumn {
+                column: plan.name.clone(),
+                generator: match &plan.source {
+                    Source::Parent { .. } => "foreign_key".to_string(),
+                    Source::Generated(generator) => generator.label().to_string(),
+                },
+            })
+            .collect(),
+    })
+}
@@ -0,0 +1,465 @@
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{Column, Row};
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+
+use crate::jobs::{JobHandle, JobSummary};
+use crate::masking::{ColumnMasker, Masker};
+use crate::sql_conn::SqlConnection;
+use crate::sql_dialect;
+use crate::{ColumnDef, ConnectionConfig, IndexDef};
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+/// Failed statements kept in the restore summary; later ones are only counted
+const MAX_REPORTED_ERRORS: usize = 1000;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DumpOptions {
+    pub path: String,
+    pub compression: Option<String>, // "gzip" or "zstd", default none
+    pub tables: Option<Vec<String>>, // Subset of tables, default all
+    pub include_schema: Option<bool>, // default true
+    pub include_data: Option<bool>,  // default true
+    pub drop_existing: Option<bool>, // Emit DROP TABLE IF EXISTS before each table, default true
+    pub batch_size: Option<usize>,   // Rows per INSERT statement, default 100
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RestoreError {
+    pub statement: u64, // 1-based position in the file
+    pub sql: String,    // Start of the statement
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RestoreSummary {
+    pub executed: u64,
+    pub failed: u64,
+    pub errors: Vec<RestoreError>, // First MAX_REPORTED_ERRORS failures
+}
+
+impl From<RestoreSummary> for JobSummary {
+    fn from(summary: RestoreSummary) -> Self {
+        JobSummary {
+            processed: summary.executed,
+            details: serde_json::to_value(&summary).ok(),
+        }
+    }
+}
+
+/// Output file, optionally compressed
+enum DumpWriter {
+    Plain(BufWriter<File>),
+    Gzip(flate2::write::GzEncoder<BufWriter<File>>),
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl DumpWriter {
+    fn create(path: &str, compression: Option<&str>) -> Result<DumpWriter, String> {
+        let file = File::create(path)
+            .map(BufWriter::new)
+            .map_err(|e| format!("无法创建文件 {}: {}", path, e))?;
+        match compression {
+            None | Some("") | Some("none") => Ok(DumpWriter::Plain(file)),
+            Some("gzip") => Ok(DumpWriter::Gzip(flate2::write::GzEncoder::new(
+                file,
+                flate2::Compression::default(),
+            ))),
+            Some("zstd") => zstd::Encoder::new(file, 3)
+                .map(DumpWriter::Zstd)
+                .map_err(|e| e.to_string()),
+            Some(other) => Err(format!("Unsupported compression: {}", other)),
+        }
+    }
+
+    fn write_str(&mut self, s: &str) -> Result<(), String> {
+        let out: &mut dyn Write = match self {
+            DumpWriter::Plain(w) => w,
+            DumpWriter::Gzip(w) => w,
+            DumpWriter::Zstd(w) => w,
+        };
+        out.write_all(s.as_bytes()).map_err(|e| e.to_string())
+    }
+
+    fn finish(self) -> Result<(), String> {
+        let mut file = match self {
+            DumpWriter::Plain(w) => w,
+            DumpWriter::Gzip(w) => w.finish().map_err(|e| e.to_string())?,
+            DumpWriter::Zstd(w) => w.finish().map_err(|e| e.to_string())?,
+        };
+        file.flush().map_err(|e| e.to_string())
+    }
+}
+
+/// Column definition for PostgreSQL. Returns whether it was turned back into a SERIAL.
+fn pg_column_ddl(col: &ColumnDef, type_name: &str) -> (String, bool) {
+    let default = col.default_value.as_deref().unwrap_or("");
+    // nextval() defaults come from SERIAL columns; the sequence is recreated by SERIAL itself
+    let serial = if default.starts_with("nextval(") {
+        match type_name {
+            "integer" => Some("serial"),
+            "bigint" => Some("bigserial"),
+            "smallint" => Some("smallserial"),
+            _ => None,
+        }
+    } else {
+        None
+    };
+
+    let mut ddl = format!(
+        "{} {}",
+        sql_dialect::quote_ident("postgresql", &col.name),
+        serial.unwrap_or(type_name)
+    );
+    if col.is_nullable == Some(false) {
+        ddl.push_str(" NOT NULL");
+    }
+    if serial.is_none() && !default.is_empty() {
+        ddl.push_str(&format!(" DEFAULT {}", default));
+    }
+    (ddl, serial.is_some())
+}
+
+/// CREATE TABLE for PostgreSQL, rebuilt from `get_columns` / `get_indexes`.
+/// Returns the table DDL and the statements to run after the data
+/// (indexes, comments, sequence positions).
+async fn pg_table_ddl(
+    conn: &mut SqlConnection,
+    config: &ConnectionConfig,
+    database: Option<String>,
+    table: &str,
+    comment: Option<&str>,
+) -> Result<(String, Vec<String>), String> {
+    let q = |name: &str| sql_dialect::quote_ident("postgresql", name);
+    let columns: Vec<ColumnDef> =
+        crate::get_columns(config.clone(), table.to_string(), database).await?;
+    let indexes: Vec<IndexDef> = crate::get_indexes(config.clone(), table.to_string()).await?;
+    let types = conn.pg_column_types(table).await?;
+
+    let mut defs = Vec::new();
+    let mut after = Vec::new();
+    for col in &columns {
+        let type_name = types
+            .iter()
+            .find(|(name, _)| *name == col.name)
+            .map(|(_, t)| t.as_str())
+            .unwrap_or(&col.type_name);
+        let (ddl, serial) = pg_column_ddl(col, type_name);
+        if serial {
+            after.push(format!(
+                "SELECT setval(pg_get_serial_sequence({}, {}), COALESCE(MAX({}), 0) + 1, false) FROM {};",
+                sql_dialect::quote_str("postgresql", &q(table)),
+                sql_dialect::quote_str("postgresql", &col.name),
+                q(&col.name),
+                q(table)
+            ));
+        }
+        defs.push(format!("  {}", ddl));
+        if let Some(c) = col.comment.as_deref().filter(|c| !c.is_empty()) {
+            after.push(format!(
+                "COMMENT ON COLUMN {}.{} IS {};",
+                q(table),
+                q(&col.name),
+                sql_dialect::quote_str("postgresql", c)
+            ));
+        }
+    }
+
+    for index in &indexes {
+        let cols: Vec<String> = index.columns.iter().map(|c| q(c)).collect();
+        if index.is_pk {
+            defs.push(format!("  PRIMARY KEY ({})", cols.join(", ")));
+        } else if let Some(definition) = &index.definition {
+            // Keeps the method, expressions, ordering and partial predicate
+            after.push(format!("{};", definition));
+        } else {
+            after.push(format!(
+                "CREATE {}INDEX {} ON {} ({});",
+                if index.is_unique { "UNIQUE " } else { "" },
+                q(&index.name),
+                q(table),
+                cols.join(", ")
+            ));
+        }
+    }
+    if let Some(c) = comment.filter(|c| !c.is_empty()) {
+        after.push(format!(
+            "COMMENT ON TABLE {} IS {};",
+            q(table),
+            sql_dialect::quote_str("postgresql", c)
+        ));
+    }
+
+    let ddl = format!("CREATE TABLE {} (\n{}\n);", q(table), defs.join(",\n"));
+    Ok((ddl, after))
+}
+
+/// Foreign keys and CHECK constraints of a PostgreSQL table as `ALTER TABLE` statements.
+/// They are added once every table is loaded so rows can be inserted in any order.
+async fn pg_constraints(conn: &mut SqlConnection, table: &str) -> Result<Vec<String>, String> {
+    let SqlConnection::Postgres(pg) = conn else {
+        return Ok(Vec::new());
+    };
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT conname::text, pg_get_constraintdef(oid)
+         FROM pg_constraint
+         WHERE conrelid = $1::regclass AND contype IN ('c', 'f')
+         ORDER BY contype, conname",
+    )
+    .bind(sql_dialect::quote_ident("postgresql", table))
+    .fetch_all(pg)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(rows
+        .into_iter()
+        .map(|(name, definition)| {
+            format!(
+                "ALTER TABLE {} ADD CONSTRAINT {} {};",
+                sql_dialect::quote_ident("postgresql", table),
+                sql_dialect::quote_ident("postgresql", &name),
+                definition
+            )
+        })
+        .collect())
+}
+
+/// `SHOW CREATE TABLE` output and whether it is a view, which carries no data
+async fn mysql_table_ddl(conn: &mut SqlConnection, table: &str) -> Result<(String, bool), String> {
+    let SqlConnection::MySql(my) = conn else {
+        return Err("Not a MySQL connection".to_string());
+    };
+    let row = sqlx::query(&format!(
+        "SHOW CREATE TABLE {}",
+        sql_dialect::quote_ident("mysql", table)
+    ))
+    .fetch_one(my)
+    .await
+    .map_err(|e| e.to_string())?;
+    let is_view = row.columns().get(1).map(|c| c.name()) == Some("Create View");
+    let ddl: Vec<u8> = row.try_get(1).map_err(|e| e.to_string())?;
+    Ok((String::from_utf8_lossy(&ddl).to_string(), is_view))
+}
+
+/// Write the rows of `table` as batched INSERT statements
+async fn dump_rows(
+    job: &JobHandle,
+    conn: &mut SqlConnection,
+    out: &mut DumpWriter,
+    table: &str,
+    batch_size: usize,
+    masker: &ColumnMasker,
+) -> Result<u64, String> {
+    let db_type = conn.db_type();
+    let sql = format!("SELECT * FROM {}", sql_dialect::quote_ident(db_type, table));
+    let binary = conn.binary_columns(&sql).await?;
+    let mut columns: Vec<String> = Vec::new();
+    let mut batch: Vec<Vec<Value>> = Vec::new();
+    let mut count = 0;
+
+    let mut rows = conn.fetch_values(&sql);
+    while let Some(row) = rows.next().await {
+        let row = row?;
+        if columns.is_empty() {
+            columns = row.iter().map(|(c, _)| c.clone()).collect();
+        }
+        let mut values: Vec<Value> = row.into_iter().map(|(_, v)| v).collect();
+        masker.mask_row(&columns, &mut values);
+        batch.push(values);
+        count += 1;
+        if batch.len() >= batch_size {
+            job.check_cancelled()?;
+            let stmt = sql_dialect::insert_statement(db_type, table, &columns, &binary, &batch);
+            out.write_str(&format!("{};\n", stmt))?;
+            batch.clear();
+        }
+    }
+    if !batch.is_empty() {
+        let stmt = sql_dialect::insert_statement(db_type, table, &columns, &binary, &batch);
+        out.write_str(&format!("{};\n", stmt))?;
+    }
+    Ok(count)
+}
+
+/// Write schema and data of a database to a `.sql` script. Rows are masked by the
+/// connection's saved masking rules.
+pub async fn dump(
+    job: JobHandle,
+    mut conn: SqlConnection,
+    config: ConnectionConfig,
+    database: Option<String>,
+    options: DumpOptions,
+    masker: Masker,
+) -> Result<u64, String> {
+    let result = write_dump(&job, &mut conn, config, database, &options, &masker).await;
+    if result.is_err() {
+        // Don't leave a truncated dump behind on error or cancellation
+        let _ = std::fs::remove_file(&options.path);
+    }
+    result
+}
+
+async fn write_dump(
+    job: &JobHandle,
+    conn: &mut SqlConnection,
+    config: ConnectionConfig,
+    database: Option<String>,
+    options: &DumpOptions,
+    masker: &Masker,
+) -> Result<u64, String> {
+    let db_type = conn.db_type();
+    let include_schema = options.include_schema.unwrap_or(true);
+    let include_data = options.include_data.unwrap_or(true);
+    let drop_existing = options.drop_existing.unwrap_or(true);
+    let batch_size = options.batch_size.unwrap_or(100).max(1);
+    // get_indexes reads the database from the config
+    let config = ConnectionConfig {
+        database: database.clone().or(config.database.clone()),
+        ..config
+    };
+
+    let mut tables = crate::get_tables(config.clone(), database.clone()).await?;
+    if let Some(only) = options.tables.as_ref().filter(|t| !t.is_empty()) {
+        tables.retain(|t| only.contains(&t.name));
+    }
+    tables.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut out = DumpWriter::create(&options.path, options.compression.as_deref())?;
+    out.write_str(&format!(
+        "-- RECCH dump of {} ({})\n-- Created at {}\n\n",
+        config.database.as_deref().unwrap_or(""),
+        db_type,
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    ))?;
+    if db_type == "mysql" {
+        out.write_str("SET NAMES utf8mb4;\nSET FOREIGN_KEY_CHECKS = 0;\n\n")?;
+    } else {
+        out.write_str("SET client_encoding = 'UTF8';\n\n")?;
+    }
+
+    let total = tables.len() as u64;
+    let mut rows = 0;
+    let mut views = Vec::new();
+    let mut after_data = Vec::new();
+    let mut constraints = Vec::new();
+    for (i, table) in tables.iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(i as u64, Some(total), Some(table.name.clone()));
+        let quoted = sql_dialect::quote_ident(db_type, &table.name);
+
+        let mut has_data = true;
+        if include_schema {
+            let ddl = if db_type == "mysql" {
+                let (ddl, is_view) = mysql_table_ddl(conn, &table.name).await?;
+                if is_view {
+                    views.push(format!("DROP VIEW IF EXISTS {};\n{};\n", quoted, ddl));
+                    continue;
+                }
+                format!("{};", ddl)
+            } else {
+                let (ddl, after) = pg_table_ddl(
+                    conn,
+                    &config,
+                    database.clone(),
+                    &table.name,
+                    table.comment.as_deref(),
+                )
+                .await?;
+                after_data.extend(after);
+                constraints.extend(pg_constraints(conn, &table.name).await?);
+                ddl
+            };
+            out.write_str(&format!("--\n-- Table {}\n--\n", quoted))?;
+            if drop_existing {
+                out.write_str(&format!("DROP TABLE IF EXISTS {};\n", quoted))?;
+            }
+            out.write_str(&format!("{}\n\n", ddl))?;
+        } else if db_type == "mysql" {
+            has_data = !mysql_table_ddl(conn, &table.name).await?.1;
+        }
+
+        if include_data && has_data {
+            let types = conn.column_types(&table.name).await?;
+            let table_masker = masker.for_table(&table.name, types);
+            rows += dump_rows(job, conn, &mut out, &table.name, batch_size, &table_masker).await?;
+            out.write_str("\n")?;
+        }
+    }
+
+    // Indexes, comments and sequences go after the data so inserts stay fast;
+    // constraints come last as foreign keys may need the unique indexes
+    for stmt in after_data.iter().chain(&constraints) {
+        out.write_str(&format!("{}\n", stmt))?;
+    }
+    for view in &views {
+        out.write_str(&format!("\n{}", view))?;
+    }
+    if db_type == "mysql" {
+        out.write_str("\nSET FOREIGN_KEY_CHECKS = 1;\n")?;
+    }
+    out.finish()?;
+    Ok(rows)
+}
+
+/// Read a script, decompressing gzip / zstd files detected by their magic bytes
+fn read_script(path: &str) -> Result<String, String> {
+    let data = std::fs::read(path).map_err(|e| format!("无法读取文件 {}: {}", path, e))?;
+    let mut text = String::new();
+    if data.starts_with(GZIP_MAGIC) {
+        flate2::read::MultiGzDecoder::new(&data[..])
+            .read_to_string(&mut text)
+            .map_err(|e| e.to_string())?;
+    } else if data.starts_with(ZSTD_MAGIC) {
+        zstd::Decoder::new(&data[..])
+            .map_err(|e| e.to_string())?
+            .read_to_string(&mut text)
+            .map_err(|e| e.to_string())?;
+    } else {
+        text = String::from_utf8(data).map_err(|_| "文件不是 UTF-8 编码的 SQL 脚本".to_string())?;
+    }
+    Ok(text)
+}
+
+/// Run a `.sql` script statement by statement. Stops at the first failure
+/// unless `continue_on_error` is set.
+pub async fn restore(
+    job: JobHandle,
+    mut conn: SqlConnection,
+    path: String,
+    continue_on_error: bool,
+) -> Result<RestoreSummary, String> {
+    let script = read_script(&path)?;
+    let statements = sql_dialect::split_statements(conn.db_type(), &script);
+    let total = statements.len() as u64;
+    let mut summary = RestoreSummary {
+        executed: 0,
+        failed: 0,
+        errors: Vec::new(),
+    };
+
+    for (i, stmt) in statements.iter().enumerate() {
+        job.check_cancelled()?;
+        if let Err(e) = conn.execute(stmt).await {
+            let preview: String = stmt.chars().take(200).collect();
+            if !continue_on_error {
+                return Err(format!("第 {} 条语句执行失败: {}\n{}", i + 1, e, preview));
+            }
+            summary.failed += 1;
+            if summary.errors.len() < MAX_REPORTED_ERRORS {
+                summary.errors.push(RestoreError {
+                    statement: i as u64 + 1,
+                    sql: preview,
+                    error: e,
+                });
+            }
+        } else {
+            summary.executed += 1;
+        }
+        if (i + 1) % 50 == 0 {
+            job.progress(i as u64 + 1, Some(total), None);
+        }
+    }
+    Ok(summary)
+}
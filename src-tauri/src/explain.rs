@@ -0,0 +1,360 @@
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::{Connection, Row};
+
+use crate::sql_conn::SqlConnection;
+
+/// Tables scanned in full above this many rows are flagged
+const FULL_SCAN_ROWS: f64 = 1000.0;
+/// Estimates off by more than this factor are flagged
+const MISESTIMATE_FACTOR: f64 = 10.0;
+
+/// One operation of a plan, the same shape for both engines
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct PlanNode {
+    pub node_type: String,
+    pub relation: Option<String>,
+    pub index: Option<String>,
+    pub startup_cost: Option<f64>,
+    pub total_cost: Option<f64>,
+    pub estimated_rows: Option<f64>,
+    pub actual_rows: Option<f64>,    // ANALYZE only
+    pub actual_time_ms: Option<f64>, // ANALYZE only, total per loop
+    pub loops: Option<f64>,
+    pub condition: Option<String>, // Filter / join / index condition
+    pub details: Vec<String>,      // Extra facts such as "Using filesort" or buffer counts
+    pub warnings: Vec<String>,
+    pub children: Vec<PlanNode>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ExplainResult {
+    pub db_type: String,
+    pub plan: PlanNode,
+    pub planning_time_ms: Option<f64>,
+    pub execution_time_ms: Option<f64>,
+    pub warnings: Vec<String>, // Every node's warnings, for a summary
+    pub raw: Value,            // Unmodified EXPLAIN output
+}
+
+fn num(v: &Value) -> Option<f64> {
+    match v {
+        Value::Number(n) => n.as_f64(),
+        // MySQL reports costs as strings
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn text(v: &Value) -> Option<String> {
+    v.as_str().map(String::from)
+}
+
+fn collect_warnings(node: &PlanNode, out: &mut Vec<String>) {
+    out.extend(node.warnings.iter().cloned());
+    for child in &node.children {
+        collect_warnings(child, out);
+    }
+}
+
+// ---- PostgreSQL ----
+
+fn pg_node(plan: &Value) -> PlanNode {
+    let mut node = PlanNode {
+        node_type: text(&plan["Node Type"]).unwrap_or_default(),
+        relation: text(&plan["Relation Name"]),
+        index: text(&plan["Index Name"]),
+        startup_cost: num(&plan["Startup Cost"]),
+        total_cost: num(&plan["Total Cost"]),
+        estimated_rows: num(&plan["Plan Rows"]),
+        actual_rows: num(&plan["Actual Rows"]),
+        actual_time_ms: num(&plan["Actual Total Time"]),
+        loops: num(&plan["Actual Loops"]),
+        condition: [
+            "Index Cond",
+            "Hash Cond",
+            "Merge Cond",
+            "Join Filter",
+            "Filter",
+        ]
+        .iter()
+        .find_map(|k| text(&plan[*k])),
+        ..Default::default()
+    };
+    if let Some(join) = text(&plan["Join Type"]) {
+        node.node_type = format!("{} ({})", node.node_type, join);
+    }
+    for key in ["Sort Method", "Sort Space Type", "Strategy"] {
+        if let Some(v) = text(&plan[key]) {
+            node.details.push(format!("{}: {}", key, v));
+        }
+    }
+    for key in [
+        "Shared Hit Blocks",
+        "Shared Read Blocks",
+        "Temp Written Blocks",
+    ] {
+        if let Some(v) = num(&plan[key]).filter(|v| *v > 0.0) {
+            node.details.push(format!("{}: {}", key, v));
+        }
+    }
+
+    let rows = node.actual_rows.or(node.estimated_rows).unwrap_or(0.0);
+    if plan["Node Type"] == "Seq Scan" && rows >= FULL_SCAN_ROWS {
+        node.warnings.push(format!(
+            "Sequential scan on {} ({} rows)",
+            node.relation.as_deref().unwrap_or("?"),
+            rows
+        ));
+    }
+    if let Some(removed) = num(&plan["Rows Removed by Filter"]) {
+        if removed >= FULL_SCAN_ROWS && removed > rows * MISESTIMATE_FACTOR {
+            node.warnings.push(format!(
+                "Filter discarded {} rows on {}, an index on the filtered columns may help",
+                removed,
+                node.relation.as_deref().unwrap_or("?")
+            ));
+        }
+    }
+    if let (Some(est), Some(actual)) = (node.estimated_rows, node.actual_rows) {
+        let (lo, hi) = if est < actual {
+            (est, actual)
+        } else {
+            (actual, est)
+        };
+        if hi >= FULL_SCAN_ROWS && hi > lo.max(1.0) * MISESTIMATE_FACTOR {
+            node.warnings.push(format!(
+                "Row estimate {} vs actual {}, statistics may be stale (ANALYZE)",
+                est, actual
+            ));
+        }
+    }
+    if text(&plan["Sort Space Type"]).as_deref() == Some("Disk") {
+        node.warnings
+            .push("Sort spilled to disk, consider raising work_mem".to_string());
+    }
+
+    if let Some(children) = plan["Plans"].as_array() {
+        node.children = children.iter().map(pg_node).collect();
+    }
+    node
+}
+
+async fn explain_pg(
+    conn: &mut sqlx::PgConnection,
+    query: &str,
+    analyze: bool,
+) -> Result<ExplainResult, String> {
+    let options = if analyze {
+        "FORMAT JSON, ANALYZE, BUFFERS"
+    } else {
+        "FORMAT JSON"
+    };
+    let sql = format!("EXPLAIN ({}) {}", options, query);
+
+    // ANALYZE really runs the statement, so keep any writes from sticking
+    let mut tx = conn.begin().await.map_err(|e| e.to_string())?;
+    let raw: Value = sqlx::query_scalar(&sql)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.rollback().await.map_err(|e| e.to_string())?;
+
+    let top = &raw[0];
+    let plan = pg_node(&top["Plan"]);
+    let mut warnings = Vec::new();
+    collect_warnings(&plan, &mut warnings);
+    Ok(ExplainResult {
+        db_type: "postgresql".to_string(),
+        planning_time_ms: num(&top["Planning Time"]),
+        execution_time_ms: num(&top["Execution Time"]),
+        plan,
+        warnings,
+        raw,
+    })
+}
+
+// ---- MySQL ----
+
+fn mysql_table(table: &Value) -> PlanNode {
+    let access = table["access_type"].as_str().unwrap_or("");
+    let node_type = match access {
+        "ALL" => "Full Table Scan",
+        "index" => "Full Index Scan",
+        "range" => "Index Range Scan",
+        "ref" | "eq_ref" | "ref_or_null" | "fulltext" => "Index Lookup",
+        "const" | "system" => "Constant Lookup",
+        "" => "Table",
+        other => other,
+    };
+    let mut node = PlanNode {
+        node_type: node_type.to_string(),
+        relation: text(&table["table_name"]),
+        index: text(&table["key"]),
+        total_cost: num(&table["cost_info"]["prefix_cost"]),
+        estimated_rows: num(&table["rows_examined_per_scan"]),
+        condition: text(&table["attached_condition"]),
+        ..Default::default()
+    };
+    if let Some(filtered) = num(&table["filtered"]) {
+        node.details.push(format!("Filtered: {}%", filtered));
+    }
+    for (key, label) in [
+        ("using_index", "Using index"),
+        ("using_temporary_table", "Using temporary"),
+        ("using_filesort", "Using filesort"),
+    ] {
+        if table[key] == true {
+            node.details.push(label.to_string());
+        }
+    }
+
+    let rows = node.estimated_rows.unwrap_or(0.0);
+    let relation = node.relation.clone().unwrap_or_default();
+    if access == "ALL" && rows >= FULL_SCAN_ROWS {
+        node.warnings
+            .push(format!("Full table scan on {} ({} rows)", relation, rows));
+    }
+    if access == "ALL" && table["possible_keys"].is_null() && node.condition.is_some() {
+        node.warnings
+            .push(format!("No index usable for the condition on {}", relation));
+    }
+    if table["using_temporary_table"] == true {
+        node.warnings.push("Uses a temporary table".to_string());
+    }
+
+    if let Some(sub) = table.get("materialized_from_subquery") {
+        node.children.extend(mysql_children(sub));
+    }
+    node
+}
+
+/// Plan nodes for the operations nested directly in a MySQL JSON object
+fn mysql_children(obj: &Value) -> Vec<PlanNode> {
+    let mut nodes = Vec::new();
+    let Some(map) = obj.as_object() else {
+        return nodes;
+    };
+    for (key, value) in map {
+        match key.as_str() {
+            "query_block" => nodes.push(mysql_query_block(value)),
+            "table" => nodes.push(mysql_table(value)),
+            "nested_loop" => {
+                let children: Vec<PlanNode> = value
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .flat_map(mysql_children)
+                    .collect();
+                nodes.push(PlanNode {
+                    node_type: "Nested Loop".to_string(),
+                    children,
+                    ..Default::default()
+                });
+            }
+            "ordering_operation" | "grouping_operation" | "duplicates_removal" | "windowing" => {
+                let mut node = PlanNode {
+                    node_type: match key.as_str() {
+                        "ordering_operation" => "Sort",
+                        "grouping_operation" => "Group",
+                        "duplicates_removal" => "Distinct",
+                        _ => "Window",
+                    }
+                    .to_string(),
+                    children: mysql_children(value),
+                    ..Default::default()
+                };
+                if value["using_filesort"] == true {
+                    node.details.push("Using filesort".to_string());
+                    node.warnings.push("Sort needs a filesort".to_string());
+                }
+                if value["using_temporary_table"] == true {
+                    node.details.push("Using temporary".to_string());
+                    node.warnings.push("Uses a temporary table".to_string());
+                }
+                nodes.push(node);
+            }
+            "union_result" => nodes.push(PlanNode {
+                node_type: "Union".to_string(),
+                children: value["query_specifications"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .flat_map(mysql_children)
+                    .collect(),
+                ..Default::default()
+            }),
+            "attached_subqueries" | "optimized_away_subqueries" | "select_list_subqueries" => {
+                nodes.extend(
+                    value
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .flat_map(mysql_children),
+                );
+            }
+            _ => {}
+        }
+    }
+    nodes
+}
+
+fn mysql_query_block(block: &Value) -> PlanNode {
+    let mut node = PlanNode {
+        node_type: match block["select_id"].as_u64() {
+            Some(id) => format!("Query Block #{}", id),
+            None => "Query Block".to_string(),
+        },
+        total_cost: num(&block["cost_info"]["query_cost"]),
+        children: mysql_children(block),
+        ..Default::default()
+    };
+    if let Some(message) = text(&block["message"]) {
+        node.details.push(message);
+    }
+    node
+}
+
+async fn explain_mysql(
+    conn: &mut sqlx::MySqlConnection,
+    query: &str,
+) -> Result<ExplainResult, String> {
+    let row = sqlx::query(&format!("EXPLAIN FORMAT=JSON {}", query))
+        .fetch_one(conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    let json = row
+        .try_get::<String, _>(0)
+        .or_else(|_| {
+            row.try_get::<Vec<u8>, _>(0)
+                .map(|b| String::from_utf8_lossy(&b).to_string())
+        })
+        .map_err(|e| e.to_string())?;
+    let raw: Value = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+
+    let plan = mysql_query_block(&raw["query_block"]);
+    let mut warnings = Vec::new();
+    collect_warnings(&plan, &mut warnings);
+    Ok(ExplainResult {
+        db_type: "mysql".to_string(),
+        plan,
+        planning_time_ms: None,
+        execution_time_ms: None,
+        warnings,
+        raw,
+    })
+}
+
+/// EXPLAIN a statement and normalize the plan. `analyze` (PostgreSQL only) executes
+/// it inside a transaction that is rolled back, giving actual rows and timing.
+pub async fn explain(
+    conn: &mut SqlConnection,
+    query: &str,
+    analyze: bool,
+) -> Result<ExplainResult, String> {
+    let query = query.trim().trim_end_matches(';');
+    match conn {
+        SqlConnection::MySql(my) => explain_mysql(my, query).await,
+        SqlConnection::Postgres(pg) => explain_pg(pg, query, analyze).await,
+    }
+}
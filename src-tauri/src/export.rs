@@ -0,0 +1,362 @@
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::jobs::JobHandle;
+use crate::masking::{ColumnMasker, Masker};
+use crate::sql_conn::SqlConnection;
+use crate::sql_dialect;
+
+/// Emit a progress event every this many rows
+const PROGRESS_EVERY: u64 = 1000;
+/// Excel's limit on characters in one cell
+const XLSX_MAX_STRING: usize = 32767;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportSource {
+    pub table: Option<String>, // Export the whole table ...
+    pub query: Option<String>, // ... or the rows of a query (takes precedence)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportOptions {
+    pub format: String, // "csv", "json", "ndjson", "sql", "xlsx"
+    pub path: String,
+    pub include_header: Option<bool>, // CSV / XLSX, default true
+    pub delimiter: Option<String>,    // CSV, default ","
+    pub quote_style: Option<String>, // CSV: "necessary" (default), "always", "non_numeric", "never"
+    pub sql_dialect: Option<String>, // SQL: "mysql" / "postgresql", defaults to the source
+    pub sql_table: Option<String>,   // SQL: target table name, defaults to the source table
+    pub batch_size: Option<usize>,   // SQL: rows per INSERT statement, default 100
+}
+
+/// Receives rows one at a time and writes them to the output file
+pub trait RowWriter: Send {
+    fn write_row(&mut self, columns: &[String], values: &[Value]) -> Result<(), String>;
+    fn finish(self: Box<Self>, columns: &[String]) -> Result<(), String>;
+}
+
+fn create_file(path: &str) -> Result<BufWriter<File>, String> {
+    File::create(path)
+        .map(BufWriter::new)
+        .map_err(|e| format!("无法创建文件 {}: {}", path, e))
+}
+
+/// Plain text for CSV cells: NULL becomes an empty field
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// `{"col": value, ...}` keeping the column order of the result set
+pub fn row_object(columns: &[String], values: &[Value]) -> String {
+    let fields: Vec<String> = columns
+        .iter()
+        .zip(values)
+        .map(|(c, v)| format!("{}:{}", Value::String(c.clone()), v))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+struct CsvWriter {
+    writer: csv::Writer<BufWriter<File>>,
+    include_header: bool,
+    wrote_header: bool,
+}
+
+impl RowWriter for CsvWriter {
+    fn write_row(&mut self, columns: &[String], values: &[Value]) -> Result<(), String> {
+        if self.include_header && !self.wrote_header {
+            self.writer
+                .write_record(columns)
+                .map_err(|e| e.to_string())?;
+            self.wrote_header = true;
+        }
+        self.writer
+            .write_record(values.iter().map(cell_text))
+            .map_err(|e| e.to_string())
+    }
+
+    fn finish(mut self: Box<Self>, _columns: &[String]) -> Result<(), String> {
+        self.writer.flush().map_err(|e| e.to_string())
+    }
+}
+
+struct JsonWriter {
+    out: BufWriter<File>,
+    ndjson: bool,
+    rows: u64,
+}
+
+impl RowWriter for JsonWriter {
+    fn write_row(&mut self, columns: &[String], values: &[Value]) -> Result<(), String> {
+        let sep = match (self.ndjson, self.rows) {
+            (true, _) => "",
+            (false, 0) => "[\n  ",
+            (false, _) => ",\n  ",
+        };
+        let end = if self.ndjson { "\n" } else { "" };
+        write!(self.out, "{}{}{}", sep, row_object(columns, values), end)
+            .map_err(|e| e.to_string())?;
+        self.rows += 1;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>, _columns: &[String]) -> Result<(), String> {
+        if !self.ndjson {
+            let tail = if self.rows == 0 { "[]\n" } else { "\n]\n" };
+            self.out
+                .write_all(tail.as_bytes())
+                .map_err(|e| e.to_string())?;
+        }
+        self.out.flush().map_err(|e| e.to_string())
+    }
+}
+
+struct SqlWriter {
+    out: BufWriter<File>,
+    db_type: String,
+    table: String,
+    batch_size: usize,
+    binary: Vec<String>, // Columns written as binary literals
+    pending: Vec<Vec<Value>>,
+}
+
+impl SqlWriter {
+    fn flush_batch(&mut self, columns: &[String]) -> Result<(), String> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let stmt = sql_dialect::insert_statement(
+            &self.db_type,
+            &self.table,
+            columns,
+            &self.binary,
+            &self.pending,
+        );
+        writeln!(self.out, "{};", stmt).map_err(|e| e.to_string())?;
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+impl RowWriter for SqlWriter {
+    fn write_row(&mut self, columns: &[String], values: &[Value]) -> Result<(), String> {
+        self.pending.push(values.to_vec());
+        if self.pending.len() >= self.batch_size {
+            self.flush_batch(columns)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>, columns: &[String]) -> Result<(), String> {
+        self.flush_batch(columns)?;
+        self.out.flush().map_err(|e| e.to_string())
+    }
+}
+
+struct XlsxWriter {
+    workbook: rust_xlsxwriter::Workbook,
+    path: String,
+    include_header: bool,
+    row: u32,
+}
+
+impl RowWriter for XlsxWriter {
+    fn write_row(&mut self, columns: &[String], values: &[Value]) -> Result<(), String> {
+        let sheet = self
+            .workbook
+            .worksheet_from_index(0)
+            .map_err(|e| e.to_string())?;
+        if self.include_header && self.row == 0 {
+            for (col, name) in columns.iter().enumerate() {
+                sheet
+                    .write_string(0, col as u16, name)
+                    .map_err(|e| e.to_string())?;
+            }
+            self.row = 1;
+        }
+        for (col, value) in values.iter().enumerate() {
+            let col = col as u16;
+            let result = match value {
+                Value::Null => continue,
+                Value::Bool(b) => sheet.write_boolean(self.row, col, *b),
+                Value::Number(n) => sheet.write_number(self.row, col, n.as_f64().unwrap_or(0.0)),
+                Value::String(s) => sheet.write_string(
+                    self.row,
+                    col,
+                    s.chars().take(XLSX_MAX_STRING).collect::<String>(),
+                ),
+                other => sheet.write_string(self.row, col, other.to_string()),
+            };
+            result.map_err(|e| e.to_string())?;
+        }
+        self.row += 1;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>, _columns: &[String]) -> Result<(), String> {
+        self.workbook
+            .save(&self.path)
+            .map_err(|e| format!("无法保存文件 {}: {}", self.path, e))
+    }
+}
+
+/// Create the writer for `options.format`. `source_db_type` and `source_table`
+/// are the defaults for SQL output; `binary` names the columns holding binary data.
+pub fn open_writer(
+    options: &ExportOptions,
+    source_db_type: &str,
+    source_table: &str,
+    binary: Vec<String>,
+) -> Result<Box<dyn RowWriter>, String> {
+    let include_header = options.include_header.unwrap_or(true);
+    match options.format.as_str() {
+        "csv" => {
+            let delimiter = match options.delimiter.as_deref() {
+                None | Some("") => b',',
+                Some("\\t") | Some("tab") => b'\t',
+                Some(d) if d.len() == 1 => d.as_bytes()[0],
+                Some(d) => return Err(format!("CSV 分隔符必须是单个字符: {}", d)),
+            };
+            let quote_style = match options.quote_style.as_deref() {
+                None | Some("") | Some("necessary") => csv::QuoteStyle::Necessary,
+                Some("always") => csv::QuoteStyle::Always,
+                Some("non_numeric") => csv::QuoteStyle::NonNumeric,
+                Some("never") => csv::QuoteStyle::Never,
+                Some(other) => return Err(format!("Unknown quote style: {}", other)),
+            };
+            let writer = csv::WriterBuilder::new()
+                .delimiter(delimiter)
+                .quote_style(quote_style)
+                .from_writer(create_file(&options.path)?);
+            Ok(Box::new(CsvWriter {
+                writer,
+                include_header,
+                wrote_header: false,
+            }))
+        }
+        "json" | "ndjson" => Ok(Box::new(JsonWriter {
+            out: create_file(&options.path)?,
+            ndjson: options.format == "ndjson",
+            rows: 0,
+        })),
+        "sql" => {
+            let db_type = options
+                .sql_dialect
+                .clone()
+                .filter(|d| !d.is_empty())
+                .unwrap_or_else(|| source_db_type.to_string());
+            let table = options
+                .sql_table
+                .clone()
+                .filter(|t| !t.is_empty())
+                .unwrap_or_else(|| source_table.to_string());
+            Ok(Box::new(SqlWriter {
+                out: create_file(&options.path)?,
+                db_type,
+                table,
+                batch_size: options.batch_size.unwrap_or(100).max(1),
+                binary,
+                pending: Vec::new(),
+            }))
+        }
+        "xlsx" => {
+            let mut workbook = rust_xlsxwriter::Workbook::new();
+            // Rows are flushed to a temp file as they are written instead of kept in memory
+            workbook.add_worksheet_with_constant_memory();
+            Ok(Box::new(XlsxWriter {
+                workbook,
+                path: options.path.clone(),
+                include_header,
+                row: 0,
+            }))
+        }
+        other => Err(format!("Unsupported export format: {}", other)),
+    }
+}
+
+/// Stream the rows of `source` into the file described by `options`, masked by the
+/// connection's saved masking rules
+pub async fn run(
+    job: JobHandle,
+    mut conn: SqlConnection,
+    source: ExportSource,
+    options: ExportOptions,
+    masker: Masker,
+) -> Result<u64, String> {
+    let db_type = conn.db_type();
+    let table = source
+        .table
+        .clone()
+        .unwrap_or_else(|| "exported".to_string());
+    let (sql, masked_table) = match source.query.as_deref().map(str::trim) {
+        Some(q) if !q.is_empty() => (q.trim_end_matches(';').to_string(), None),
+        _ => match &source.table {
+            Some(t) if !t.is_empty() => (
+                format!("SELECT * FROM {}", sql_dialect::quote_ident(db_type, t)),
+                Some(t.as_str()),
+            ),
+            _ => return Err("需要指定表名或查询语句".to_string()),
+        },
+    };
+    // Masked values are shaped by the source column types
+    let masker = match masked_table {
+        Some(t) => masker.for_table(t, conn.column_types(t).await?),
+        None => masker.for_query(
+            conn.result_sources(&sql).await?,
+            conn.result_types(&sql).await?,
+        )?,
+    };
+
+    let result = write_rows(&job, &mut conn, &sql, &options, &table, &masker).await;
+    if result.is_err() {
+        // Don't leave a truncated file behind on error or cancellation
+        let _ = std::fs::remove_file(&options.path);
+    }
+    result
+}
+
+async fn write_rows(
+    job: &JobHandle,
+    conn: &mut SqlConnection,
+    sql: &str,
+    options: &ExportOptions,
+    table: &str,
+    masker: &ColumnMasker,
+) -> Result<u64, String> {
+    let binary = if options.format == "sql" {
+        conn.binary_columns(sql).await?
+    } else {
+        Vec::new()
+    };
+    let mut writer = open_writer(options, conn.db_type(), table, binary)?;
+    let mut columns: Vec<String> = Vec::new();
+    let mut count = 0;
+
+    let mut rows = conn.fetch_values(sql);
+    while let Some(row) = rows.next().await {
+        job.check_cancelled()?;
+        let row = row?;
+        if columns.is_empty() {
+            columns = row.iter().map(|(c, _)| c.clone()).collect();
+        }
+        let mut values: Vec<Value> = row.into_iter().map(|(_, v)| v).collect();
+        masker.mask_row(&columns, &mut values);
+        writer.write_row(&columns, &values)?;
+
+        count += 1;
+        if count % PROGRESS_EVERY == 0 {
+            job.progress(count, None, None);
+        }
+    }
+    drop(rows);
+
+    writer.finish(&columns)?;
+    Ok(count)
+}
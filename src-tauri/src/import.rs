@@ -0,0 +1,589 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use crate::jobs::{JobHandle, JobSummary};
+use crate::sql_conn::SqlConnection;
+use crate::sql_dialect;
+use crate::ConnectionConfig;
+
+/// Rows sampled to infer column types and, for NDJSON, to collect the keys
+const SAMPLE_ROWS: usize = 1000;
+/// Most bind parameters a single statement may carry (MySQL and PostgreSQL both cap at 65535)
+const MAX_PARAMS: usize = 65535;
+/// Failed rows kept in the summary; later ones are only counted
+const MAX_REPORTED_ERRORS: usize = 1000;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportFile {
+    pub path: String,
+    pub format: String,            // "csv" or "ndjson"
+    pub delimiter: Option<String>, // CSV, default ","
+    pub has_header: Option<bool>, // CSV, default true. Without one columns are column_1, column_2 ...
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ColumnMapping {
+    pub source: String, // Column (CSV) or key (NDJSON) in the file
+    pub target: String, // Column of the table
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImportOptions {
+    pub mapping: Option<Vec<ColumnMapping>>, // Default: file columns matched to table columns by name
+    pub create_table: Option<bool>,          // Create the table from the inferred types if missing
+    pub batch_size: Option<usize>,           // Rows per INSERT / transaction, default 500
+    pub on_error: Option<String>,            // "abort" (default) or "skip" failing rows
+    pub empty_as_null: Option<bool>,         // CSV: empty fields become NULL, default true
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ImportPreview {
+    pub columns: Vec<String>,
+    pub inferred_types: Vec<String>, // "integer", "float", "boolean", "date", "datetime" or "text"
+    pub rows: Vec<Vec<Option<String>>>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ImportRowError {
+    pub line: u64, // Line in the file where the row starts
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ImportSummary {
+    pub inserted: u64,
+    pub skipped: u64,
+    pub created_table: bool,
+    pub errors: Vec<ImportRowError>, // First MAX_REPORTED_ERRORS failed rows
+}
+
+impl From<ImportSummary> for JobSummary {
+    fn from(summary: ImportSummary) -> Self {
+        JobSummary {
+            processed: summary.inserted,
+            details: serde_json::to_value(&summary).ok(),
+        }
+    }
+}
+
+/// One data row, values aligned with the requested columns
+struct SourceRow {
+    line: u64,
+    values: Vec<Option<String>>,
+}
+
+enum FileRows {
+    Csv {
+        reader: csv::Reader<BufReader<File>>,
+        headers: Vec<String>,
+        empty_as_null: bool,
+    },
+    Ndjson {
+        lines: std::io::Lines<BufReader<File>>,
+        line: u64,
+    },
+}
+
+impl FileRows {
+    fn open(file: &ImportFile, empty_as_null: bool) -> Result<FileRows, String> {
+        let f = File::open(&file.path).map_err(|e| format!("无法打开文件 {}: {}", file.path, e))?;
+        let reader = BufReader::new(f);
+        match file.format.as_str() {
+            "csv" => {
+                let delimiter = match file.delimiter.as_deref() {
+                    None | Some("") => b',',
+                    Some("\\t") | Some("tab") => b'\t',
+                    Some(d) if d.len() == 1 => d.as_bytes()[0],
+                    Some(d) => return Err(format!("CSV 分隔符必须是单个字符: {}", d)),
+                };
+                let has_header = file.has_header.unwrap_or(true);
+                let mut reader = csv::ReaderBuilder::new()
+                    .delimiter(delimiter)
+                    .has_headers(has_header)
+                    .flexible(true)
+                    .from_reader(reader);
+                // Without a header row this peeks at the first record without consuming it
+                let first = reader.headers().map_err(|e| e.to_string())?.clone();
+                let headers = if has_header {
+                    first.iter().map(|h| h.trim().to_string()).collect()
+                } else {
+                    (1..=first.len()).map(|i| format!("column_{}", i)).collect()
+                };
+                Ok(FileRows::Csv {
+                    reader,
+                    headers,
+                    empty_as_null,
+                })
+            }
+            "ndjson" => Ok(FileRows::Ndjson {
+                lines: reader.lines(),
+                line: 0,
+            }),
+            other => Err(format!("Unsupported import format: {}", other)),
+        }
+    }
+
+    /// Next row with values for `columns`. Errors carry the line of the bad row.
+    fn next_row(&mut self, columns: &[String]) -> Option<Result<SourceRow, ImportRowError>> {
+        match self {
+            FileRows::Csv {
+                reader,
+                headers,
+                empty_as_null,
+            } => {
+                let mut record = csv::StringRecord::new();
+                let line = reader.position().line();
+                match reader.read_record(&mut record) {
+                    Ok(false) => None,
+                    Err(e) => Some(Err(ImportRowError {
+                        line,
+                        error: e.to_string(),
+                    })),
+                    Ok(true) => {
+                        let line = record.position().map(|p| p.line()).unwrap_or(line);
+                        let values = columns
+                            .iter()
+                            .map(|c| {
+                                let idx = headers.iter().position(|h| h == c)?;
+                                let field = record.get(idx)?;
+                                if field.is_empty() && *empty_as_null {
+                                    None
+                                } else {
+                                    Some(field.to_string())
+                                }
+                            })
+                            .collect();
+                        Some(Ok(SourceRow { line, values }))
+                    }
+                }
+            }
+            FileRows::Ndjson { lines, line } => loop {
+                *line += 1;
+                let text = match lines.next()? {
+                    Ok(text) => text,
+                    Err(e) => {
+                        return Some(Err(ImportRowError {
+                            line: *line,
+                            error: e.to_string(),
+                        }))
+                    }
+                };
+                if text.trim().is_empty() {
+                    continue;
+                }
+                let object = match serde_json::from_str::<Value>(&text) {
+                    Ok(Value::Object(object)) => object,
+                    Ok(_) => {
+                        return Some(Err(ImportRowError {
+                            line: *line,
+                            error: "每行必须是一个 JSON 对象".to_string(),
+                        }))
+                    }
+                    Err(e) => {
+                        return Some(Err(ImportRowError {
+                            line: *line,
+                            error: e.to_string(),
+                        }))
+                    }
+                };
+                let values = columns
+                    .iter()
+                    .map(|c| match object.get(c) {
+                        None | Some(Value::Null) => None,
+                        Some(Value::String(s)) => Some(s.clone()),
+                        Some(other) => Some(other.to_string()),
+                    })
+                    .collect();
+                return Some(Ok(SourceRow {
+                    line: *line,
+                    values,
+                }));
+            },
+        }
+    }
+}
+
+/// Columns of the file: the CSV header, or the keys seen in the first NDJSON rows
+fn file_columns(file: &ImportFile) -> Result<Vec<String>, String> {
+    match FileRows::open(file, true)? {
+        FileRows::Csv { headers, .. } => Ok(headers),
+        FileRows::Ndjson { mut lines, .. } => {
+            let mut columns: Vec<String> = Vec::new();
+            let mut seen = 0;
+            while seen < SAMPLE_ROWS {
+                let Some(text) = lines.next() else { break };
+                let text = text.map_err(|e| e.to_string())?;
+                if let Ok(Value::Object(object)) = serde_json::from_str::<Value>(&text) {
+                    for key in object.keys() {
+                        if !columns.contains(key) {
+                            columns.push(key.clone());
+                        }
+                    }
+                    seen += 1;
+                }
+            }
+            Ok(columns)
+        }
+    }
+}
+
+/// Read up to `limit` rows for `columns`, skipping unreadable ones
+fn sample_rows(
+    file: &ImportFile,
+    columns: &[String],
+    limit: usize,
+    empty_as_null: bool,
+) -> Result<Vec<Vec<Option<String>>>, String> {
+    let mut rows = FileRows::open(file, empty_as_null)?;
+    let mut sample = Vec::new();
+    while sample.len() < limit {
+        match rows.next_row(columns) {
+            Some(Ok(row)) => sample.push(row.values),
+            Some(Err(_)) => continue,
+            None => break,
+        }
+    }
+    Ok(sample)
+}
+
+/// Narrowest type every non-empty value parses as
+fn infer_type<'a>(values: impl Iterator<Item = &'a str>) -> &'static str {
+    const DATETIME_FORMATS: [&str; 3] = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M:%S%.f",
+    ];
+    let mut candidates = ["integer", "float", "boolean", "date", "datetime"].to_vec();
+    let mut any = false;
+    for v in values.map(str::trim).filter(|v| !v.is_empty()) {
+        any = true;
+        candidates.retain(|t| match *t {
+            "integer" => v.parse::<i64>().is_ok(),
+            "float" => v.parse::<f64>().is_ok(),
+            "boolean" => matches!(v.to_ascii_lowercase().as_str(), "true" | "false"),
+            "date" => chrono::NaiveDate::parse_from_str(v, "%Y-%m-%d").is_ok(),
+            "datetime" => {
+                chrono::DateTime::parse_from_rfc3339(v).is_ok()
+                    || DATETIME_FORMATS
+                        .iter()
+                        .any(|f| chrono::NaiveDateTime::parse_from_str(v, f).is_ok())
+            }
+            _ => false,
+        });
+        if candidates.is_empty() {
+            break;
+        }
+    }
+    match candidates.first() {
+        Some(t) if any => t,
+        _ => "text",
+    }
+}
+
+fn infer_types(columns: &[String], rows: &[Vec<Option<String>>]) -> Vec<String> {
+    (0..columns.len())
+        .map(|i| infer_type(rows.iter().filter_map(|r| r.get(i)?.as_deref())).to_string())
+        .collect()
+}
+
+/// Column type used when the import creates the table
+fn column_type(db_type: &str, inferred: &str) -> &'static str {
+    match (db_type, inferred) {
+        (_, "integer") => "BIGINT",
+        ("mysql", "float") => "DOUBLE",
+        (_, "float") => "DOUBLE PRECISION",
+        (_, "boolean") => "BOOLEAN",
+        (_, "date") => "DATE",
+        ("mysql", "datetime") => "DATETIME",
+        (_, "datetime") => "TIMESTAMP",
+        _ => "TEXT",
+    }
+}
+
+/// First `limit` rows of the file with the type inferred for each column
+pub fn preview(file: &ImportFile, limit: Option<usize>) -> Result<ImportPreview, String> {
+    let columns = file_columns(file)?;
+    let sample = sample_rows(file, &columns, SAMPLE_ROWS, true)?;
+    let inferred_types = infer_types(&columns, &sample);
+    let limit = limit.unwrap_or(100);
+    Ok(ImportPreview {
+        columns,
+        inferred_types,
+        rows: sample.into_iter().take(limit).collect(),
+    })
+}
+
+/// Resolve the mapping, defaulting to file columns that match a table column by name
+fn resolve_mapping(
+    options: &ImportOptions,
+    source_columns: &[String],
+    target_columns: &[String],
+) -> Result<Vec<ColumnMapping>, String> {
+    let mapping = match &options.mapping {
+        Some(mapping) if !mapping.is_empty() => mapping.clone(),
+        _ => source_columns
+            .iter()
+            .filter_map(|s| {
+                let target = target_columns.iter().find(|t| t.eq_ignore_ascii_case(s))?;
+                Some(ColumnMapping {
+                    source: s.clone(),
+                    target: target.clone(),
+                })
+            })
+            .collect(),
+    };
+    if mapping.is_empty() {
+        return Err("文件中没有与表匹配的列，请指定列映射".to_string());
+    }
+    for m in &mapping {
+        if !source_columns.contains(&m.source) {
+            return Err(format!("文件中不存在列: {}", m.source));
+        }
+        if !target_columns.contains(&m.target) {
+            return Err(format!("表中不存在列: {}", m.target));
+        }
+    }
+    Ok(mapping)
+}
+
+struct Inserter {
+    db_type: &'static str,
+    table: String,
+    columns: Vec<String>,
+    templates: Vec<String>,
+    skip_errors: bool,
+    summary: ImportSummary,
+}
+
+impl Inserter {
+    fn statement(&self, rows: usize) -> String {
+        sql_dialect::insert_params_statement(
+            self.db_type,
+            &self.table,
+            &self.columns,
+            &self.templates,
+            rows,
+        )
+    }
+
+    fn record_error(&mut self, line: u64, error: String) {
+        self.summary.skipped += 1;
+        if self.summary.errors.len() < MAX_REPORTED_ERRORS {
+            self.summary.errors.push(ImportRowError { line, error });
+        }
+    }
+
+    /// Insert a batch in one transaction. When it fails and errors are skipped,
+    /// retry row by row so only the bad rows are left out.
+    async fn insert(
+        &mut self,
+        conn: &mut SqlConnection,
+        batch: &[SourceRow],
+    ) -> Result<(), String> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let params: Vec<Option<String>> = batch.iter().flat_map(|r| r.values.clone()).collect();
+        let err = match conn
+            .execute_in_transaction(&self.statement(batch.len()), &params)
+            .await
+        {
+            Ok(_) => {
+                self.summary.inserted += batch.len() as u64;
+                return Ok(());
+            }
+            Err(e) => e,
+        };
+        if !self.skip_errors {
+            return Err(format!(
+                "第 {}-{} 行导入失败 (已导入 {} 行): {}",
+                batch[0].line,
+                batch[batch.len() - 1].line,
+                self.summary.inserted,
+                err
+            ));
+        }
+
+        let single = self.statement(1);
+        for row in batch {
+            match conn.execute_params(&single, &row.values).await {
+                Ok(_) => self.summary.inserted += 1,
+                Err(e) => self.record_error(row.line, e),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stream the file into `table` in batches, one transaction per batch
+pub async fn run(
+    job: JobHandle,
+    mut conn: SqlConnection,
+    config: ConnectionConfig,
+    database: Option<String>,
+    table: String,
+    file: ImportFile,
+    options: ImportOptions,
+) -> Result<ImportSummary, String> {
+    let db_type = conn.db_type();
+    let empty_as_null = options.empty_as_null.unwrap_or(true);
+    let skip_errors = options.on_error.as_deref() == Some("skip");
+    let source_columns = file_columns(&file)?;
+
+    let target_defs = crate::get_columns(config, table.clone(), database.clone()).await?;
+    let mut target_columns: Vec<String> = target_defs.iter().map(|c| c.name.clone()).collect();
+    let mut target_types: Vec<String> = target_defs.into_iter().map(|c| c.type_name).collect();
+
+    let mut created_table = false;
+    if target_columns.is_empty() {
+        if !options.create_table.unwrap_or(false) {
+            return Err(format!("表 {} 不存在或没有列", table));
+        }
+        // Create the table with the mapped target names, or the file's own column names
+        let mapped: Vec<(String, String)> = match &options.mapping {
+            Some(mapping) if !mapping.is_empty() => mapping
+                .iter()
+                .map(|m| (m.source.clone(), m.target.clone()))
+                .collect(),
+            _ => source_columns
+                .iter()
+                .map(|c| (c.clone(), c.clone()))
+                .collect(),
+        };
+        let sources: Vec<String> = mapped.iter().map(|(s, _)| s.clone()).collect();
+        let sample = sample_rows(&file, &sources, SAMPLE_ROWS, empty_as_null)?;
+        let types = infer_types(&sources, &sample);
+        let defs: Vec<String> = mapped
+            .iter()
+            .zip(&types)
+            .map(|((_, target), t)| {
+                format!(
+                    "{} {}",
+                    sql_dialect::quote_ident(db_type, target),
+                    column_type(db_type, t)
+                )
+            })
+            .collect();
+        let ddl = format!(
+            "CREATE TABLE {} ({})",
+            sql_dialect::quote_ident(db_type, &table),
+            defs.join(", ")
+        );
+        conn.execute(&ddl).await?;
+        target_columns = mapped.into_iter().map(|(_, t)| t).collect();
+        target_types = types
+            .iter()
+            .map(|t| column_type(db_type, t).to_string())
+            .collect();
+        created_table = true;
+    }
+
+    let mapping = resolve_mapping(&options, &source_columns, &target_columns)?;
+    let sources: Vec<String> = mapping.iter().map(|m| m.source.clone()).collect();
+    let target_type = |name: &str| {
+        target_columns
+            .iter()
+            .position(|c| c == name)
+            .and_then(|i| target_types.get(i))
+            .map(|t| t.to_lowercase())
+            .unwrap_or_default()
+    };
+    // PostgreSQL binds every value as text, so each placeholder is cast to the column's type.
+    // MySQL stores a string bound to a BIT column as its bytes, so '1' needs a numeric cast.
+    let pg_types = conn.pg_column_types(&table).await?;
+    let templates = mapping
+        .iter()
+        .map(
+            |m| match pg_types.iter().find(|(name, _)| *name == m.target) {
+                Some((_, t)) => format!("CAST({{}} AS {})", t),
+                None if target_type(&m.target).starts_with("bit") => {
+                    "CAST({} AS UNSIGNED)".to_string()
+                }
+                None => "{}".to_string(),
+            },
+        )
+        .collect();
+
+    // MySQL has no boolean type: true / false bound as text must arrive as 1 / 0
+    let mysql_bools: Vec<bool> = if db_type == "mysql" {
+        let sample = sample_rows(&file, &sources, SAMPLE_ROWS, empty_as_null)?;
+        let inferred = infer_types(&sources, &sample);
+        mapping
+            .iter()
+            .zip(&inferred)
+            .map(|(m, t)| {
+                let target = target_type(&m.target);
+                t == "boolean"
+                    && [
+                        "bool",
+                        "tinyint",
+                        "smallint",
+                        "mediumint",
+                        "int",
+                        "bigint",
+                        "bit",
+                    ]
+                    .iter()
+                    .any(|p| target.starts_with(p))
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let batch_size = options
+        .batch_size
+        .unwrap_or(500)
+        .clamp(1, MAX_PARAMS / mapping.len());
+    let mut inserter = Inserter {
+        db_type,
+        table,
+        columns: mapping.iter().map(|m| m.target.clone()).collect(),
+        templates,
+        skip_errors,
+        summary: ImportSummary {
+            inserted: 0,
+            skipped: 0,
+            created_table,
+            errors: Vec::new(),
+        },
+    };
+
+    let mut rows = FileRows::open(&file, empty_as_null)?;
+    let mut batch = Vec::with_capacity(batch_size);
+    while let Some(row) = rows.next_row(&sources) {
+        match row {
+            Ok(mut row) => {
+                for (value, _) in row.values.iter_mut().zip(&mysql_bools).filter(|(_, b)| **b) {
+                    if let Some(v) = value {
+                        if v.eq_ignore_ascii_case("true") {
+                            *v = "1".to_string();
+                        } else if v.eq_ignore_ascii_case("false") {
+                            *v = "0".to_string();
+                        }
+                    }
+                }
+                batch.push(row);
+            }
+            Err(e) if skip_errors => inserter.record_error(e.line, e.error),
+            Err(e) => return Err(format!("第 {} 行读取失败: {}", e.line, e.error)),
+        }
+        if batch.len() >= batch_size {
+            job.check_cancelled()?;
+            inserter.insert(&mut conn, &batch).await?;
+            batch.clear();
+            let s = &inserter.summary;
+            job.progress(
+                s.inserted,
+                None,
+                Some(format!("已导入 {} 行，跳过 {} 行", s.inserted, s.skipped)),
+            );
+        }
+    }
+    job.check_cancelled()?;
+    inserter.insert(&mut conn, &batch).await?;
+
+    Ok(inserter.summary)
+}
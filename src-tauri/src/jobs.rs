@@ -0,0 +1,148 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{Emitter, Manager};
+
+/// Event reporting progress of background jobs (export, import, dump ...)
+pub const PROGRESS_EVENT: &str = "job-progress";
+
+/// Cancellation flags of running background jobs, keyed by job id
+#[derive(Default)]
+pub struct Jobs {
+    cancel_flags: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl Jobs {
+    /// Ask a job to stop, returns false if it is not running
+    pub fn cancel(&self, job_id: &str) -> bool {
+        match self.cancel_flags.lock() {
+            Ok(flags) => match flags.get(job_id) {
+                Some(flag) => {
+                    flag.store(true, Ordering::SeqCst);
+                    true
+                }
+                None => false,
+            },
+            Err(_) => false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct JobProgress {
+    pub job_id: String,
+    pub kind: String, // "export", "import", ...
+    pub processed: u64,
+    pub total: Option<u64>,
+    pub message: Option<String>,
+    pub done: bool,
+    pub cancelled: bool,
+    pub error: Option<String>,
+    pub details: Option<serde_json::Value>, // Job specific summary, only on the final event
+}
+
+/// What a finished job reports: processed items plus optional details
+pub struct JobSummary {
+    pub processed: u64,
+    pub details: Option<serde_json::Value>,
+}
+
+impl From<u64> for JobSummary {
+    fn from(processed: u64) -> Self {
+        JobSummary {
+            processed,
+            details: None,
+        }
+    }
+}
+
+/// Handed to a running job to report progress and check for cancellation
+#[derive(Clone)]
+pub struct JobHandle {
+    pub id: String,
+    kind: String,
+    app: tauri::AppHandle,
+    cancel: Arc<AtomicBool>,
+}
+
+impl JobHandle {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
+    }
+
+    /// Error for a job to return when it noticed it was cancelled
+    pub fn check_cancelled(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err("已取消".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn progress(&self, processed: u64, total: Option<u64>, message: Option<String>) {
+        self.emit(JobProgress {
+            job_id: self.id.clone(),
+            kind: self.kind.clone(),
+            processed,
+            total,
+            message,
+            done: false,
+            cancelled: false,
+            error: None,
+            details: None,
+        });
+    }
+
+    fn emit(&self, progress: JobProgress) {
+        let _ = self.app.emit(PROGRESS_EVENT, progress);
+    }
+}
+
+/// Run `job` in the background and return its id right away.
+/// The job resolves to the number of processed items (or a `JobSummary`);
+/// a final progress event with `done = true` reports the outcome.
+pub fn spawn<F, Fut, T>(app: &tauri::AppHandle, kind: &str, job: F) -> String
+where
+    F: FnOnce(JobHandle) -> Fut,
+    Fut: Future<Output = Result<T, String>> + Send + 'static,
+    T: Into<JobSummary>,
+{
+    let handle = JobHandle {
+        id: uuid::Uuid::new_v4().to_string(),
+        kind: kind.to_string(),
+        app: app.clone(),
+        cancel: Arc::new(AtomicBool::new(false)),
+    };
+    if let Ok(mut flags) = app.state::<Jobs>().cancel_flags.lock() {
+        flags.insert(handle.id.clone(), handle.cancel.clone());
+    }
+
+    let job_id = handle.id.clone();
+    let fut = job(handle.clone());
+    tauri::async_runtime::spawn(async move {
+        let result = fut.await;
+        let cancelled = handle.is_cancelled();
+        let (summary, error) = match result {
+            Ok(summary) => (summary.into(), None),
+            Err(_) if cancelled => (JobSummary::from(0), None),
+            Err(e) => (JobSummary::from(0), Some(e)),
+        };
+        handle.emit(JobProgress {
+            job_id: handle.id.clone(),
+            kind: handle.kind.clone(),
+            processed: summary.processed,
+            total: None,
+            message: None,
+            done: true,
+            cancelled,
+            error,
+            details: summary.details,
+        });
+        if let Ok(mut flags) = handle.app.state::<Jobs>().cancel_flags.lock() {
+            flags.remove(&handle.id);
+        }
+    });
+    job_id
+}
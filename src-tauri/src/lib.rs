@@ -7,11 +7,33 @@ use std::fs;
 use std::path::PathBuf;
 
 use serde_json::Value;
-use sqlx::{Column, Row, TypeInfo};
+use sqlx::Row;
 use std::collections::HashMap;
 use tauri::Manager;
 
 mod ai_service;
+mod datagen;
+mod dump;
+mod explain;
+mod export;
+mod import;
+mod jobs;
+mod maintenance;
+mod masking;
+mod query_history;
+mod redis_admin;
+mod redis_conn;
+mod redis_stream;
+mod redis_transfer;
+mod redis_value;
+mod saved_queries;
+mod sql_admin;
+mod sql_conn;
+mod sql_ddl;
+mod sql_dialect;
+mod sql_users;
+mod sql_value;
+mod table_copy;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ConnectionConfig {
@@ -23,6 +45,10 @@ pub struct ConnectionConfig {
     pub username: Option<String>,
     pub password: Option<String>,
     pub database: Option<String>,
+    pub redis_mode: Option<String>, // "standalone" (default), "cluster", "sentinel"
+    pub redis_nodes: Option<Vec<String>>, // "host:port" cluster seed nodes or sentinel addresses
+    pub sentinel_master: Option<String>, // Master name monitored by the sentinels
+    pub redis_resp3: Option<bool>,  // Use RESP3 (Redis 6+) instead of RESP2
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -110,27 +136,11 @@ async fn test_connection(config: ConnectionConfig) -> Result<String, String> {
             Ok("PostgreSQL 连接成功!".to_string())
         }
         "redis" => {
-            let url = if let Some(pass) = &config.password {
-                format!(
-                    "redis://:{}@{}:{}/{}",
-                    pass,
-                    config.host,
-                    config.port,
-                    config.database.as_deref().unwrap_or("0")
-                )
-            } else {
-                format!(
-                    "redis://{}:{}/{}",
-                    config.host,
-                    config.port,
-                    config.database.as_deref().unwrap_or("0")
-                )
-            };
-
-            let client = redis::Client::open(url).map_err(|e| e.to_string())?;
-            let mut con = client.get_connection().map_err(|e| e.to_string())?;
+            let db_index = redis_conn::parse_db_index(config.database.as_deref().unwrap_or(""));
+            let mut con = redis_conn::connect(&config, db_index).await?;
             let _: String = redis::cmd("PING")
-                .query(&mut con)
+                .query_async(&mut con)
+                .await
                 .map_err(|e| e.to_string())?;
             Ok("Redis Connection Successful!".to_string())
         }
@@ -147,8 +157,21 @@ fn get_config_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
     Ok(config_dir.join("connections.json"))
 }
 
+/// Redis connections saved before ACL support always carried the form's default
+/// `root` username, which was never sent. Drop it so they keep authenticating with
+/// the password alone instead of failing `AUTH root <pass>`.
+fn migrate_legacy_redis_user(config: &mut ConnectionConfig) {
+    if config.db_type == "redis" && config.username.as_deref() == Some("root") {
+        config.username = None;
+    }
+}
+
 #[tauri::command]
-fn save_connection(app_handle: tauri::AppHandle, config: ConnectionConfig) -> Result<(), String> {
+fn save_connection(
+    app_handle: tauri::AppHandle,
+    mut config: ConnectionConfig,
+) -> Result<(), String> {
+    migrate_legacy_redis_user(&mut config);
     let path = get_config_path(&app_handle)?;
     let mut connections = if path.exists() {
         let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
@@ -176,7 +199,8 @@ fn get_connections(app_handle: tauri::AppHandle) -> Result<Vec<ConnectionConfig>
         return Ok(Vec::new());
     }
     let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
-    let connections = serde_json::from_str(&content).unwrap_or_default();
+    let mut connections: Vec<ConnectionConfig> = serde_json::from_str(&content).unwrap_or_default();
+    connections.iter_mut().for_each(migrate_legacy_redis_user);
     Ok(connections)
 }
 
@@ -240,22 +264,15 @@ async fn get_databases(config: ConnectionConfig) -> Result<Vec<String>, String>
         "redis" => {
             // Redis has 16 databases by default (0-15)
             // Query each one for key count using DBSIZE
-            let url = format!("redis://{}:{}/", config.host, config.port);
-            let client = redis::Client::open(url).map_err(|e| e.to_string())?;
-            let mut con = client
-                .get_multiplexed_async_connection()
-                .await
-                .map_err(|e| e.to_string())?;
+            let mut con = redis_conn::connect(&config, 0).await?;
 
-            // Auth if needed
-            if let Some(pass) = &config.password {
-                if !pass.is_empty() {
-                    let _: () = redis::cmd("AUTH")
-                        .arg(pass)
-                        .query_async(&mut con)
-                        .await
-                        .map_err(|e| e.to_string())?;
-                }
+            // A cluster only has db0, DBSIZE is summed across its masters
+            if con.is_cluster() {
+                let count: i64 = redis::cmd("DBSIZE")
+                    .query_async(&mut con)
+                    .await
+                    .unwrap_or(0);
+                return Ok(vec![format!("db0 ({})", count)]);
             }
 
             let mut dbs = Vec::new();
@@ -426,47 +443,13 @@ async fn get_tables(
         }
         "redis" => {
             // For Redis, return all keys as "tables"
-            let url = format!("redis://{}:{}/", config.host, config.port);
-            let client = redis::Client::open(url).map_err(|e| e.to_string())?;
-            let mut con = client
-                .get_multiplexed_async_connection()
-                .await
-                .map_err(|e| e.to_string())?;
-
-            // Auth if needed
-            if let Some(pass) = &config.password {
-                if !pass.is_empty() {
-                    let _: () = redis::cmd("AUTH")
-                        .arg(pass)
-                        .query_async(&mut con)
-                        .await
-                        .map_err(|e| e.to_string())?;
-                }
-            }
-
             // Select DB if provided (database param could be "db0 (15)", "db0", "0", or empty)
             let db_str = database.or(config.database.clone()).unwrap_or_default();
-            // Extract just the db part before any space (for "db0 (15)" -> "db0")
-            let db_part = db_str.split_whitespace().next().unwrap_or("");
-            let db_index: i32 = if db_part.is_empty() {
-                0
-            } else if let Some(num_str) = db_part.strip_prefix("db") {
-                num_str.parse().unwrap_or(0)
-            } else {
-                db_part.parse().unwrap_or(0)
-            };
-            let _: () = redis::cmd("SELECT")
-                .arg(db_index)
-                .query_async(&mut con)
-                .await
-                .map_err(|e| e.to_string())?;
+            let mut con = redis_conn::connect(&config, redis_conn::parse_db_index(&db_str)).await?;
 
-            // Get all keys (limited to 1000 for performance)
-            let keys: Vec<String> = redis::cmd("KEYS")
-                .arg("*")
-                .query_async(&mut con)
-                .await
-                .map_err(|e| e.to_string())?;
+            // SCAN instead of KEYS so big keyspaces don't block the server,
+            // visiting every master in cluster mode
+            let keys = redis_conn::scan_keys(&mut con, "*").await?;
 
             let tables = keys
                 .into_iter()
@@ -494,6 +477,14 @@ pub struct ColumnDef {
     pub is_nullable: Option<bool>,
     pub default_value: Option<String>,
     pub comment: Option<String>,
+    pub auto_increment: Option<bool>, // MySQL AUTO_INCREMENT / PostgreSQL identity or serial
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IndexColumn {
+    pub name: String,
+    pub order: Option<String>, // "ASC" / "DESC"
+    pub length: Option<u32>,   // MySQL prefix length
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -503,16 +494,30 @@ pub struct IndexDef {
     pub is_unique: bool,
     pub is_pk: bool,
     pub comment: Option<String>,
+    pub method: Option<String>, // BTREE, HASH, FULLTEXT / GIN, GIST, BRIN ...
+    pub predicate: Option<String>, // PostgreSQL partial index WHERE clause
+    pub column_options: Option<Vec<IndexColumn>>, // Per-column order / prefix, replaces `columns` when set
+    pub definition: Option<String>,               // PostgreSQL pg_get_indexdef
+    pub size_bytes: Option<i64>,
+    pub scans: Option<i64>,     // Index scans since stats were reset
+    pub is_valid: Option<bool>, // False for a failed CREATE INDEX CONCURRENTLY
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AlterOperation {
-    pub op_type: String, // "add", "modify", "drop", "rename", "add_index", "drop_index"
+    // "add", "modify", "drop", "rename", "add_index", "drop_index", "add_foreign_key",
+    // "drop_foreign_key", "add_check", "drop_check", "add_unique" (index_def), "drop_unique"
+    pub op_type: String,
     pub column_name: Option<String>, // Optional now
     pub new_name: Option<String>,
     pub column_def: Option<ColumnDef>,
-    pub index_def: Option<IndexDef>, // For add_index
-    pub index_name: Option<String>,  // For drop_index
+    pub index_def: Option<IndexDef>,                 // For add_index
+    pub index_name: Option<String>,                  // For drop_index
+    pub using: Option<String>, // PostgreSQL modify: USING expression for the type change
+    pub foreign_key: Option<sql_ddl::ForeignKeyDef>, // For add_foreign_key
+    pub check: Option<sql_ddl::CheckDef>, // For add_check
+    pub constraint_name: Option<String>, // For drop_foreign_key, drop_check, drop_unique
+    pub concurrently: Option<bool>, // PostgreSQL add_index / drop_index, must be the only operation
 }
 
 #[tauri::command]
@@ -549,12 +554,12 @@ async fn get_columns(
 
             // Added IS_NULLABLE, COLUMN_DEFAULT
             let query = if !db_name.is_empty() {
-                "SELECT COLUMN_NAME, COLUMN_TYPE, COLUMN_KEY, IS_NULLABLE, COLUMN_DEFAULT, COLUMN_COMMENT 
+                "SELECT COLUMN_NAME, COLUMN_TYPE, COLUMN_KEY, IS_NULLABLE, COLUMN_DEFAULT, COLUMN_COMMENT, EXTRA 
                   FROM information_schema.COLUMNS 
                   WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ?
                   ORDER BY ORDINAL_POSITION"
             } else {
-                "SELECT COLUMN_NAME, COLUMN_TYPE, COLUMN_KEY, IS_NULLABLE, COLUMN_DEFAULT, COLUMN_COMMENT 
+                "SELECT COLUMN_NAME, COLUMN_TYPE, COLUMN_KEY, IS_NULLABLE, COLUMN_DEFAULT, COLUMN_COMMENT, EXTRA 
                   FROM information_schema.COLUMNS 
                   WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?
                   ORDER BY ORDINAL_POSITION"
@@ -571,6 +576,7 @@ async fn get_columns(
                     Option<Vec<u8>>,
                     Option<Vec<u8>>,
                     Option<Vec<u8>>,
+                    Option<Vec<u8>>,
                 ),
             >(query);
             let q = if !db_name.is_empty() {
@@ -585,8 +591,15 @@ async fn get_columns(
             })?;
 
             let mut result = Vec::new();
-            for (name_bytes, dtype_bytes, key_bytes, null_bytes, default_bytes, comment_bytes) in
-                rows
+            for (
+                name_bytes,
+                dtype_bytes,
+                key_bytes,
+                null_bytes,
+                default_bytes,
+                comment_bytes,
+                extra_bytes,
+            ) in rows
             {
                 let name = name_bytes
                     .map(|b| String::from_utf8_lossy(&b).to_string())
@@ -603,6 +616,9 @@ async fn get_columns(
 
                 let def_val = default_bytes.map(|b| String::from_utf8_lossy(&b).to_string());
                 let comment = comment_bytes.map(|b| String::from_utf8_lossy(&b).to_string());
+                let extra = extra_bytes
+                    .map(|b| String::from_utf8_lossy(&b).to_lowercase())
+                    .unwrap_or_default();
 
                 result.push(ColumnDef {
                     name,
@@ -611,6 +627,7 @@ async fn get_columns(
                     is_nullable: Some(null_str == "YES"),
                     default_value: def_val,
                     comment,
+                    auto_increment: Some(extra.contains("auto_increment")),
                 });
             }
             Ok(result)
@@ -637,19 +654,17 @@ async fn get_columns(
                 SELECT 
                     c.column_name, 
                     c.data_type,
-                    CASE WHEN tc.constraint_type = 'PRIMARY KEY' THEN true ELSE false END as is_pk,
+                    EXISTS (
+                        SELECT 1 FROM pg_index ix
+                        JOIN pg_attribute a ON a.attrelid = ix.indrelid AND a.attnum = ANY(ix.indkey)
+                        WHERE ix.indrelid = format('%I.%I', c.table_schema, c.table_name)::regclass
+                            AND ix.indisprimary AND a.attname = c.column_name
+                    ) as is_pk,
                     c.is_nullable, 
                     c.column_default,
-                    pg_catalog.col_description(format('%s.%s', c.table_schema, c.table_name)::regclass::oid, c.ordinal_position) as comment
+                    pg_catalog.col_description(format('%I.%I', c.table_schema, c.table_name)::regclass::oid, c.ordinal_position) as comment,
+                    (c.is_identity = 'YES' OR c.column_default LIKE 'nextval(%') as is_auto
                 FROM information_schema.columns c
-                LEFT JOIN information_schema.key_column_usage kcu 
-                    ON c.table_schema = kcu.table_schema 
-                    AND c.table_name = kcu.table_name 
-                    AND c.column_name = kcu.column_name
-                LEFT JOIN information_schema.table_constraints tc 
-                    ON kcu.constraint_name = tc.constraint_name 
-                    AND kcu.table_schema = tc.table_schema
-                    AND tc.constraint_type = 'PRIMARY KEY'
                 WHERE c.table_schema = 'public' AND c.table_name = $1
                 ORDER BY c.ordinal_position
             ";
@@ -660,6 +675,7 @@ async fn get_columns(
                 Option<String>,
                 Option<String>,
                 Option<String>,
+                Option<bool>,
             )> = sqlx::query_as(query)
                 .bind(&table)
                 .fetch_all(&mut conn)
@@ -667,7 +683,7 @@ async fn get_columns(
                 .map_err(|e| e.to_string())?;
 
             let mut result = Vec::new();
-            for (name, dtype, is_pk, is_null, def, comment) in rows {
+            for (name, dtype, is_pk, is_null, def, comment, is_auto) in rows {
                 result.push(ColumnDef {
                     name,
                     type_name: dtype,
@@ -675,46 +691,15 @@ async fn get_columns(
                     is_nullable: Some(is_null.unwrap_or("YES".to_string()) == "YES"),
                     default_value: def,
                     comment,
+                    auto_increment: is_auto,
                 });
             }
             Ok(result)
         }
         "redis" => {
             // For Redis, return key type info instead of columns
-            let url = format!("redis://{}:{}/", config.host, config.port);
-            let client = redis::Client::open(url).map_err(|e| e.to_string())?;
-            let mut con = client
-                .get_multiplexed_async_connection()
-                .await
-                .map_err(|e| e.to_string())?;
-
-            // Auth if needed
-            if let Some(pass) = &config.password {
-                if !pass.is_empty() {
-                    let _: () = redis::cmd("AUTH")
-                        .arg(pass)
-                        .query_async(&mut con)
-                        .await
-                        .map_err(|e| e.to_string())?;
-                }
-            }
-
-            // Select DB
-            if let Some(db) = &database.or(config.database.clone()) {
-                if !db.is_empty() {
-                    let db_part = db.split_whitespace().next().unwrap_or("");
-                    let db_index: i32 = if let Some(num_str) = db_part.strip_prefix("db") {
-                        num_str.parse().unwrap_or(0)
-                    } else {
-                        db_part.parse().unwrap_or(0)
-                    };
-                    let _: () = redis::cmd("SELECT")
-                        .arg(db_index)
-                        .query_async(&mut con)
-                        .await
-                        .map_err(|e| e.to_string())?;
-                }
-            }
+            let db_str = database.or(config.database.clone()).unwrap_or_default();
+            let mut con = redis_conn::connect(&config, redis_conn::parse_db_index(&db_str)).await?;
 
             // Get key type
             let key_type: String = redis::cmd("TYPE")
@@ -731,6 +716,7 @@ async fn get_columns(
                 is_nullable: Some(false),
                 default_value: None,
                 comment: Some(format!("Redis key: {}", table)),
+                auto_increment: None,
             }])
         }
         _ => Err("Unsupported database type".to_string()),
@@ -758,36 +744,75 @@ async fn get_indexes(config: ConnectionConfig, table: String) -> Result<Vec<Inde
             }
             let mut conn = opts.connect().await.map_err(|e| e.to_string())?;
 
-            let rows: Vec<(Option<Vec<u8>>, Option<Vec<u8>>, i32, Option<Vec<u8>>)> =
-                sqlx::query_as(
-                    "
-                SELECT INDEX_NAME, COLUMN_NAME, NON_UNIQUE, INDEX_COMMENT 
+            let rows = sqlx::query(
+                "
+                SELECT INDEX_NAME, COLUMN_NAME, NON_UNIQUE, INDEX_COMMENT, INDEX_TYPE, SUB_PART, COLLATION
                 FROM information_schema.STATISTICS 
                 WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?
                 ORDER BY INDEX_NAME, SEQ_IN_INDEX
             ",
-                )
-                .bind(&table)
-                .fetch_all(&mut conn)
-                .await
-                .map_err(|e| e.to_string())?;
+            )
+            .bind(&table)
+            .fetch_all(&mut conn)
+            .await
+            .map_err(|e| e.to_string())?;
+
+            // Size and usage need privileges on mysql / performance_schema; without them
+            // those fields stay empty
+            // innodb_index_stats names are utf8_bin and come back as binary strings
+            let sizes: Vec<(Vec<u8>, i64)> = sqlx::query_as(
+                "SELECT index_name, CAST(stat_value * @@innodb_page_size AS SIGNED)
+                 FROM mysql.innodb_index_stats
+                 WHERE database_name = DATABASE() AND table_name = ? AND stat_name = 'size'",
+            )
+            .bind(&table)
+            .fetch_all(&mut conn)
+            .await
+            .unwrap_or_default();
+            let scans: Vec<(String, i64)> = sqlx::query_as(
+                "SELECT INDEX_NAME, CAST(COUNT_FETCH AS SIGNED)
+                 FROM performance_schema.table_io_waits_summary_by_index_usage
+                 WHERE OBJECT_SCHEMA = DATABASE() AND OBJECT_NAME = ? AND INDEX_NAME IS NOT NULL",
+            )
+            .bind(&table)
+            .fetch_all(&mut conn)
+            .await
+            .unwrap_or_default();
+
+            let text = |row: &sqlx::mysql::MySqlRow, col: &str| {
+                row.try_get::<Option<Vec<u8>>, _>(col)
+                    .ok()
+                    .flatten()
+                    .map(|b| String::from_utf8_lossy(&b).to_string())
+            };
 
             // Group by index name
             let mut indexes: Vec<IndexDef> = Vec::new();
-            for (idx_name_bytes, col_name_bytes, non_unique, comment_bytes) in rows {
-                let idx_name = idx_name_bytes
-                    .map(|b| String::from_utf8_lossy(&b).to_string())
-                    .unwrap_or_default();
-                let col_name = col_name_bytes
-                    .map(|b| String::from_utf8_lossy(&b).to_string())
-                    .unwrap_or_default();
-                let comment = comment_bytes
-                    .map(|b| String::from_utf8_lossy(&b).to_string())
-                    .unwrap_or_default();
+            for row in &rows {
+                let idx_name = text(row, "INDEX_NAME").unwrap_or_default();
+                // Functional key parts (8.0.13+) have no column name
+                let col_name = text(row, "COLUMN_NAME").unwrap_or_default();
+                let comment = text(row, "INDEX_COMMENT").unwrap_or_default();
+                let non_unique: i64 = row.try_get("NON_UNIQUE").unwrap_or(1);
+                let column = IndexColumn {
+                    name: col_name.clone(),
+                    order: match text(row, "COLLATION").as_deref() {
+                        Some("D") => Some("DESC".to_string()),
+                        _ => None,
+                    },
+                    length: row
+                        .try_get::<Option<i64>, _>("SUB_PART")
+                        .ok()
+                        .flatten()
+                        .map(|n| n as u32),
+                };
 
                 if let Some(last) = indexes.last_mut() {
                     if last.name == idx_name {
                         last.columns.push(col_name);
+                        if let Some(options) = last.column_options.as_mut() {
+                            options.push(column);
+                        }
                         continue;
                     }
                 }
@@ -801,6 +826,19 @@ async fn get_indexes(config: ConnectionConfig, table: String) -> Result<Vec<Inde
                     } else {
                         Some(comment)
                     },
+                    method: text(row, "INDEX_TYPE"),
+                    predicate: None,
+                    column_options: Some(vec![column]),
+                    definition: None,
+                    size_bytes: sizes
+                        .iter()
+                        .find(|(name, _)| name[..] == *idx_name.as_bytes())
+                        .map(|(_, size)| *size),
+                    scans: scans
+                        .iter()
+                        .find(|(name, _)| *name == idx_name)
+                        .map(|(_, n)| *n),
+                    is_valid: Some(true), // MySQL has no invalid indexes
                 });
             }
             Ok(indexes)
@@ -821,29 +859,40 @@ async fn get_indexes(config: ConnectionConfig, table: String) -> Result<Vec<Inde
             }
             let mut conn = opts.connect().await.map_err(|e| e.to_string())?;
 
-            // Simple query over pg_indexes logic
-            let rows: Vec<(String, String, bool)> = sqlx::query_as(
+            // Key columns in index order; expression keys come back as their expression
+            let rows = sqlx::query(
                 "
-                select
-                    i.relname as index_name,
-                    array_to_string(array_agg(a.attname), ',') as column_names,
-                    ix.indisunique as is_unique
-                from
-                    pg_class t,
-                    pg_class i,
-                    pg_index ix,
-                    pg_attribute a
-                where
-                    t.oid = ix.indrelid
-                    and i.oid = ix.indexrelid
-                    and a.attrelid = t.oid
-                    and a.attnum = ANY(ix.indkey)
-                    and t.relkind = 'r'
-                    and t.relname = $1
-                group by
-                    t.relname,
-                    i.relname,
-                    ix.indisunique
+                SELECT
+                    i.relname::text AS index_name,
+                    ARRAY(
+                        SELECT CASE WHEN ix.indkey[k - 1] = 0
+                            THEN pg_get_indexdef(ix.indexrelid, k, true)
+                            ELSE (SELECT a.attname::text FROM pg_attribute a
+                                  WHERE a.attrelid = ix.indrelid AND a.attnum = ix.indkey[k - 1])
+                        END
+                        FROM generate_series(1, ix.indnkeyatts) AS k ORDER BY k
+                    ) AS columns,
+                    ARRAY(
+                        SELECT CASE WHEN ix.indoption[k - 1]::int & 1 = 1 THEN 'DESC' END
+                        FROM generate_series(1, ix.indnkeyatts) AS k ORDER BY k
+                    ) AS orders,
+                    ix.indisunique AS is_unique,
+                    ix.indisprimary AS is_pk,
+                    ix.indisvalid AS is_valid,
+                    am.amname::text AS method,
+                    pg_get_expr(ix.indpred, ix.indrelid) AS predicate,
+                    pg_get_indexdef(ix.indexrelid) AS definition,
+                    pg_relation_size(ix.indexrelid) AS size_bytes,
+                    s.idx_scan AS scans,
+                    obj_description(ix.indexrelid, 'pg_class') AS comment
+                FROM pg_index ix
+                JOIN pg_class t ON t.oid = ix.indrelid
+                JOIN pg_class i ON i.oid = ix.indexrelid
+                JOIN pg_namespace n ON n.oid = t.relnamespace
+                JOIN pg_am am ON am.oid = i.relam
+                LEFT JOIN pg_stat_user_indexes s ON s.indexrelid = ix.indexrelid
+                WHERE n.nspname = 'public' AND t.relname = $1
+                ORDER BY ix.indisprimary DESC, i.relname
             ",
             )
             .bind(&table)
@@ -852,13 +901,36 @@ async fn get_indexes(config: ConnectionConfig, table: String) -> Result<Vec<Inde
             .map_err(|e| e.to_string())?;
 
             let mut indexes = Vec::new();
-            for (name, cols, unique) in rows {
+            for row in &rows {
+                let columns: Vec<String> = row.try_get("columns").map_err(|e| e.to_string())?;
+                let orders: Vec<Option<String>> =
+                    row.try_get("orders").map_err(|e| e.to_string())?;
                 indexes.push(IndexDef {
-                    name: name.clone(),
-                    columns: cols.split(',').map(|s| s.to_string()).collect(),
-                    is_unique: unique,
-                    is_pk: name.ends_with("_pkey"), // Heuristic or check indisprimary?
-                    comment: None,
+                    name: row.try_get("index_name").map_err(|e| e.to_string())?,
+                    column_options: Some(
+                        columns
+                            .iter()
+                            .zip(orders)
+                            .map(|(name, order)| IndexColumn {
+                                name: name.clone(),
+                                order,
+                                length: None,
+                            })
+                            .collect(),
+                    ),
+                    columns,
+                    is_unique: row.try_get("is_unique").map_err(|e| e.to_string())?,
+                    is_pk: row.try_get("is_pk").map_err(|e| e.to_string())?,
+                    comment: row.try_get("comment").map_err(|e| e.to_string())?,
+                    method: row
+                        .try_get::<String, _>("method")
+                        .map(|m| m.to_uppercase())
+                        .ok(),
+                    predicate: row.try_get("predicate").map_err(|e| e.to_string())?,
+                    definition: row.try_get("definition").map_err(|e| e.to_string())?,
+                    size_bytes: row.try_get("size_bytes").map_err(|e| e.to_string())?,
+                    scans: row.try_get("scans").map_err(|e| e.to_string())?,
+                    is_valid: row.try_get("is_valid").map_err(|e| e.to_string())?,
                 });
             }
             Ok(indexes)
@@ -869,236 +941,60 @@ async fn get_indexes(config: ConnectionConfig, table: String) -> Result<Vec<Inde
 
 // ... existing code ...
 
+/// Apply a batch of column / index / constraint changes, or only return their SQL with `dry_run`.
+/// MySQL runs them as one ALTER TABLE; PostgreSQL runs them in one transaction.
 #[tauri::command]
 async fn alter_table(
     config: ConnectionConfig,
     table: String,
-    operation: AlterOperation,
-) -> Result<(), String> {
-    let query = match config.db_type.as_str() {
-        "mysql" => {
-            match operation.op_type.as_str() {
-                "add" => {
-                    let col = operation
-                        .column_def
-                        .as_ref()
-                        .ok_or("Missing column definition")?;
-                    let comment = col
-                        .comment
-                        .as_ref()
-                        .map(|c| format!("COMMENT '{}'", c.replace("'", "''")))
-                        .unwrap_or_default();
-                    let null_def = if col.is_nullable == Some(false) {
-                        "NOT NULL"
-                    } else {
-                        "NULL"
-                    };
-                    let default_def = col
-                        .default_value
-                        .as_ref()
-                        .map(|d| format!("DEFAULT {}", d))
-                        .unwrap_or_default();
-                    let pk_def = if col.is_pk { "PRIMARY KEY" } else { "" };
-
-                    format!(
-                        "ALTER TABLE {} ADD COLUMN {} {} {} {} {} {}",
-                        table, col.name, col.type_name, null_def, default_def, pk_def, comment
-                    )
-                }
-                "modify" => {
-                    let col = operation
-                        .column_def
-                        .as_ref()
-                        .ok_or("Missing column definition")?;
-                    let comment = col
-                        .comment
-                        .as_ref()
-                        .map(|c| format!("COMMENT '{}'", c.replace("'", "''")))
-                        .unwrap_or_default();
-                    let null_def = if col.is_nullable == Some(false) {
-                        "NOT NULL"
-                    } else {
-                        "NULL"
-                    };
-                    let default_def = col
-                        .default_value
-                        .as_ref()
-                        .map(|d| format!("DEFAULT {}", d))
-                        .unwrap_or_default();
-
-                    format!(
-                        "ALTER TABLE {} MODIFY COLUMN {} {} {} {} {}",
-                        table, col.name, col.type_name, null_def, default_def, comment
-                    )
-                }
-                "drop" => {
-                    let col_name = operation
-                        .column_name
-                        .as_ref()
-                        .ok_or("Missing column name")?;
-                    format!("ALTER TABLE {} DROP COLUMN {}", table, col_name)
-                }
-                "rename" => {
-                    // MySQL RENAME COLUMN old TO new
-                    let col_name = operation
-                        .column_name
-                        .as_ref()
-                        .ok_or("Missing column name")?;
-                    let new_name = operation.new_name.as_ref().ok_or("Missing new name")?;
-                    format!(
-                        "ALTER TABLE {} RENAME COLUMN {} TO {}",
-                        table, col_name, new_name
-                    )
-                }
-                "add_index" => {
-                    let idx = operation
-                        .index_def
-                        .as_ref()
-                        .ok_or("Missing index definition")?;
-                    let cols = idx.columns.join(", ");
-                    let unique = if idx.is_unique { "UNIQUE" } else { "" };
-                    format!(
-                        "CREATE {} INDEX {} ON {} ({})",
-                        unique, idx.name, table, cols
-                    )
-                }
-                "drop_index" => {
-                    let idx_name = operation.index_name.as_ref().ok_or("Missing index name")?;
-                    format!("DROP INDEX {} ON {}", idx_name, table)
-                }
-                _ => return Err("Unknown operation".to_string()),
-            }
-        }
-        "postgresql" => {
-            match operation.op_type.as_str() {
-                "add" => {
-                    let col = operation
-                        .column_def
-                        .as_ref()
-                        .ok_or("Missing column definition")?;
-                    // PG doesn't support comment in ADD COLUMN syntax directly usually, need separate COMMENT ON
-                    // But for simplicity here, we might just add column first. Detailed comment support needs multiple queries or a transaction.
-                    // For now: ALTER TABLE ... ADD COLUMN ...
-                    format!(
-                        "ALTER TABLE {} ADD COLUMN {} {}",
-                        table, col.name, col.type_name
-                    )
-                }
-                "modify" => {
-                    let col = operation
-                        .column_def
-                        .as_ref()
-                        .ok_or("Missing column definition")?;
-                    // PG: ALTER TABLE ... ALTER COLUMN ... TYPE ...
-                    format!(
-                        "ALTER TABLE {} ALTER COLUMN {} TYPE {}",
-                        table, col.name, col.type_name
-                    )
-                }
-                "drop" => {
-                    let col_name = operation
-                        .column_name
-                        .as_ref()
-                        .ok_or("Missing column name")?;
-                    format!("ALTER TABLE {} DROP COLUMN {}", table, col_name)
-                }
-                "rename" => {
-                    let col_name = operation
-                        .column_name
-                        .as_ref()
-                        .ok_or("Missing column name")?;
-                    let new_name = operation.new_name.as_ref().ok_or("Missing new name")?;
-                    format!(
-                        "ALTER TABLE {} RENAME COLUMN {} TO {}",
-                        table, col_name, new_name
-                    )
-                }
-                "add_index" => {
-                    let idx = operation
-                        .index_def
-                        .as_ref()
-                        .ok_or("Missing index definition")?;
-                    let cols = idx.columns.join(", ");
-                    let unique = if idx.is_unique { "UNIQUE" } else { "" };
-                    format!(
-                        "CREATE {} INDEX {} ON {} ({})",
-                        unique, idx.name, table, cols
-                    )
-                }
-                "drop_index" => {
-                    let idx_name = operation.index_name.as_ref().ok_or("Missing index name")?;
-                    format!("DROP INDEX {}", idx_name)
-                }
-                _ => return Err("Unknown operation".to_string()),
-            }
-        }
-        _ => return Err("Unsupported database".to_string()),
+    operations: Vec<AlterOperation>,
+    database: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<Vec<String>, String> {
+    // PostgreSQL changes nullability, defaults and comments with separate clauses,
+    // only emitted where the column differs from what is there now
+    let current = if config.db_type == "postgresql" {
+        get_columns(config.clone(), table.clone(), database.clone()).await?
+    } else {
+        Vec::new()
     };
+    let statements = sql_ddl::alter_table_sql(&config.db_type, &table, &operations, &current)?;
+    let atomic = !operations.iter().any(|op| op.concurrently == Some(true));
+    run_ddl(&config, database.as_deref(), statements, dry_run, atomic).await
+}
 
-    match config.db_type.as_str() {
-        "mysql" => {
-            let mut opts = MySqlConnectOptions::new()
-                .host(&config.host)
-                .port(config.port);
-            if let Some(user) = &config.username {
-                opts = opts.username(user);
-            }
-            if let Some(pass) = &config.password {
-                opts = opts.password(pass);
-            }
-            if let Some(db) = &config.database {
-                if !db.is_empty() {
-                    opts = opts.database(db);
-                }
-            }
-            let mut conn = opts.connect().await.map_err(|e| e.to_string())?;
-            sqlx::query(&query)
-                .execute(&mut conn)
-                .await
-                .map_err(|e| e.to_string())?;
-        }
-        "postgresql" => {
-            let mut opts = PgConnectOptions::new().host(&config.host).port(config.port);
-            if let Some(user) = &config.username {
-                opts = opts.username(user);
-            }
-            if let Some(pass) = &config.password {
-                opts = opts.password(pass);
-            }
-            if let Some(db) = &config.database {
-                if !db.is_empty() {
-                    opts = opts.database(db);
-                }
-            }
-            let mut conn = opts.connect().await.map_err(|e| e.to_string())?;
-            sqlx::query(&query)
-                .execute(&mut conn)
-                .await
-                .map_err(|e| e.to_string())?;
-
-            // Handle comment for PG separately if it's ADD
-            if operation.op_type == "add" && config.db_type == "postgresql" {
-                if let Some(col) = operation.column_def.as_ref() {
-                    if let Some(comment) = &col.comment {
-                        let comment_query = format!(
-                            "COMMENT ON COLUMN {}.{} IS '{}'",
-                            table,
-                            col.name,
-                            comment.replace("'", "''")
-                        );
-                        let _ = sqlx::query(&comment_query).execute(&mut conn).await;
-                    }
-                }
-            }
+#[tauri::command]
+async fn execute_query(
+    app: tauri::AppHandle,
+    config: ConnectionConfig,
+    query: String,
+    history: Option<bool>, // Record in the query history, default true
+) -> Result<Vec<HashMap<String, Value>>, String> {
+    let started = std::time::Instant::now();
+    let result = run_query(config.clone(), query.clone()).await;
+
+    if history.unwrap_or(true) {
+        let entry = query_history::QueryHistoryEntry::new(
+            &config,
+            &query,
+            started.elapsed().as_millis() as u64,
+            result
+                .as_ref()
+                .map(|rows| rows.len() as u64)
+                .map_err(|e| e.as_str()),
+        );
+        // Failing to write the history must not fail the query
+        if let Err(e) = app
+            .state::<query_history::QueryHistory>()
+            .record(&app, &entry)
+        {
+            println!("Failed to record query history: {}", e);
         }
-        _ => {}
     }
-
-    Ok(())
+    result
 }
 
-#[tauri::command]
-async fn execute_query(
+async fn run_query(
     config: ConnectionConfig,
     query: String,
 ) -> Result<Vec<HashMap<String, Value>>, String> {
@@ -1134,156 +1030,11 @@ async fn execute_query(
             let mut results = Vec::new();
 
             for row in rows {
-                let mut map = HashMap::new();
-                for col in row.columns() {
-                    let name = col.name();
-                    let type_name = col.type_info().name();
-
-                    let value: Value = match type_name {
-                        "BOOLEAN" | "BOOL" => {
-                            let v: Option<bool> = row.try_get(col.ordinal()).unwrap_or(None);
-                            json!(v)
-                        }
-                        _ if type_name.starts_with("TINYINT")
-                            || type_name.starts_with("SMALLINT")
-                            || type_name.starts_with("INT")
-                            || type_name.starts_with("INTEGER")
-                            || type_name.starts_with("BIGINT")
-                            || type_name.starts_with("MEDIUMINT")
-                            || type_name == "INT4"
-                            || type_name == "INT8" =>
-                        {
-                            // Try i64 first (handles TINYINT(1), INT(11), etc.)
-                            if let Ok(v) = row.try_get::<Option<i64>, _>(col.ordinal()) {
-                                json!(v)
-                            } else if let Ok(v) = row.try_get::<Option<u64>, _>(col.ordinal()) {
-                                json!(v)
-                            } else if let Ok(v) = row.try_get::<Option<i32>, _>(col.ordinal()) {
-                                json!(v)
-                            } else if let Ok(v) = row.try_get::<Option<i8>, _>(col.ordinal()) {
-                                json!(v)
-                            } else {
-                                // Fallback to string if strictly needed or overflow
-                                match row.try_get::<Option<String>, _>(col.ordinal()) {
-                                    Ok(v) => json!(v),
-                                    Err(_) => Value::Null,
-                                }
-                            }
-                        }
-                        "FLOAT" | "DOUBLE" | "REAL" | "NUMERIC" => {
-                            let v: Option<f64> = row.try_get(col.ordinal()).unwrap_or(None);
-                            json!(v)
-                        }
-                        "BIT" => {
-                            // BIT often comes as bytes or int depending on driver/length
-                            // Try u64 first
-                            if let Ok(v) = row.try_get::<Option<u64>, _>(col.ordinal()) {
-                                json!(v)
-                            } else {
-                                // Try bytes
-                                match row.try_get::<Option<Vec<u8>>, _>(col.ordinal()) {
-                                    Ok(Some(v)) => {
-                                        // Simple binary string like "0x..."
-                                        let hex: String =
-                                            v.iter().map(|b| format!("{:02X}", b)).collect();
-                                        json!(format!("0x{}", hex))
-                                    }
-                                    Ok(None) => Value::Null,
-                                    Err(_) => Value::Null,
-                                }
-                            }
-                        }
-                        "JSON" => {
-                            // Requires sqlx json feature
-                            match row.try_get::<Option<serde_json::Value>, _>(col.ordinal()) {
-                                Ok(v) => json!(v),
-                                Err(_) => Value::Null,
-                            }
-                        }
-                        "TIMESTAMP" | "DATETIME" => {
-                            match row.try_get::<Option<chrono::NaiveDateTime>, _>(col.ordinal()) {
-                                Ok(Some(v)) => json!(v.to_string()),
-                                Ok(None) => Value::Null,
-                                Err(_) => {
-                                    // Fallback if it's maybe a string already?
-                                    match row.try_get::<Option<String>, _>(col.ordinal()) {
-                                        Ok(v) => json!(v),
-                                        Err(_) => Value::Null,
-                                    }
-                                }
-                            }
-                        }
-                        "DATE" => {
-                            match row.try_get::<Option<chrono::NaiveDate>, _>(col.ordinal()) {
-                                Ok(Some(v)) => json!(v.to_string()),
-                                Ok(None) => Value::Null,
-                                Err(_) => Value::Null,
-                            }
-                        }
-                        "TIME" => {
-                            match row.try_get::<Option<chrono::NaiveTime>, _>(col.ordinal()) {
-                                Ok(Some(v)) => json!(v.to_string()),
-                                Ok(None) => Value::Null,
-                                Err(_) => Value::Null,
-                            }
-                        }
-                        "YEAR" => {
-                            match row.try_get::<Option<i32>, _>(col.ordinal()) {
-                                Ok(Some(v)) => json!(v),
-                                Ok(None) => Value::Null, // Or string
-                                Err(_) => match row.try_get::<Option<String>, _>(col.ordinal()) {
-                                    Ok(v) => json!(v),
-                                    Err(_) => Value::Null,
-                                },
-                            }
-                        }
-                        _ if type_name.to_uppercase().contains("BINARY")
-                            || type_name.to_uppercase().contains("BLOB")
-                            || type_name.to_uppercase().contains("BYTEA") =>
-                        {
-                            // Handle binary types: VARBINARY, BINARY, BLOB, TINYBLOB, MEDIUMBLOB, LONGBLOB, BYTEA (PG)
-                            match row.try_get::<Option<Vec<u8>>, _>(col.ordinal()) {
-                                Ok(Some(v)) => {
-                                    // Display as hex, truncated for readability
-                                    let hex: String =
-                                        v.iter().take(32).map(|b| format!("{:02X}", b)).collect();
-                                    let suffix = if v.len() > 32 {
-                                        format!("... ({} bytes)", v.len())
-                                    } else {
-                                        String::new()
-                                    };
-                                    json!(format!("0x{}{}", hex, suffix))
-                                }
-                                Ok(None) => Value::Null,
-                                Err(_) => Value::Null,
-                            }
-                        }
-                        _ => {
-                            // Fallback to string for TEXT, VARCHAR, etc.
-                            match row.try_get::<Option<String>, _>(col.ordinal()) {
-                                Ok(v) => json!(v),
-                                Err(_) => {
-                                    // Fallback to generic bytes debug view
-                                    match row.try_get::<Option<Vec<u8>>, _>(col.ordinal()) {
-                                        Ok(Some(v)) => {
-                                            let hex: String = v
-                                                .iter()
-                                                .take(16)
-                                                .map(|b| format!("{:02X}", b))
-                                                .collect();
-                                            let suffix = if v.len() > 16 { "..." } else { "" };
-                                            json!(format!("[BLOB: 0x{}{}]", hex, suffix))
-                                        }
-                                        Ok(None) => Value::Null,
-                                        Err(_) => Value::Null,
-                                    }
-                                }
-                            }
-                        }
-                    };
-                    map.insert(name.to_string(), value);
-                }
-                results.push(map);
+                results.push(
+                    sql_value::mysql_row_values(&row, false)
+                        .into_iter()
+                        .collect(),
+                );
             }
             Ok(results)
         }
@@ -1310,163 +1061,15 @@ async fn execute_query(
             let mut results = Vec::new();
 
             for row in rows {
-                let mut map = HashMap::new();
-                for col in row.columns() {
-                    let name = col.name();
-                    let type_name = col.type_info().name();
-
-                    let value: Value = match type_name {
-                        "BOOL" => {
-                            let v: Option<bool> = row.try_get(col.ordinal()).unwrap_or(None);
-                            json!(v)
-                        }
-                        "INT2" | "INT4" | "INT8" => {
-                            let v: Option<i64> = row.try_get(col.ordinal()).unwrap_or(None);
-                            json!(v)
-                        }
-                        "FLOAT4" | "FLOAT8" | "NUMERIC" | "MONEY" => {
-                            let v: Option<f64> = row.try_get(col.ordinal()).unwrap_or(None);
-                            json!(v)
-                        }
-                        "TIMESTAMP" | "TIMESTAMPTZ" => {
-                            // Use chrono::NaiveDateTime or DateTime<Utc>
-                            // sqlx maps TIMESTAMP -> NaiveDateTime, TIMESTAMPTZ -> DateTime<Utc> or DateTime<Local>
-                            // We try generic string first, if that fails, we try specific types
-                            if let Ok(v) = row.try_get::<Option<String>, _>(col.ordinal()) {
-                                json!(v)
-                            } else if let Ok(v) =
-                                row.try_get::<Option<chrono::NaiveDateTime>, _>(col.ordinal())
-                            {
-                                json!(v.map(|d| d.to_string()))
-                            } else if let Ok(v) = row
-                                .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>(col.ordinal())
-                            {
-                                json!(v.map(|d| d.to_string()))
-                            } else {
-                                Value::Null
-                            }
-                        }
-                        "DATE" => {
-                            if let Ok(v) = row.try_get::<Option<String>, _>(col.ordinal()) {
-                                json!(v)
-                            } else if let Ok(v) =
-                                row.try_get::<Option<chrono::NaiveDate>, _>(col.ordinal())
-                            {
-                                json!(v.map(|d| d.to_string()))
-                            } else {
-                                Value::Null
-                            }
-                        }
-                        "TIME" | "TIMETZ" => {
-                            if let Ok(v) = row.try_get::<Option<String>, _>(col.ordinal()) {
-                                json!(v)
-                            } else if let Ok(v) =
-                                row.try_get::<Option<chrono::NaiveTime>, _>(col.ordinal())
-                            {
-                                json!(v.map(|d| d.to_string()))
-                            } else {
-                                Value::Null
-                            }
-                        }
-                        "JSON" | "JSONB" => {
-                            if let Ok(v) =
-                                row.try_get::<Option<serde_json::Value>, _>(col.ordinal())
-                            {
-                                json!(v)
-                            } else if let Ok(v) = row.try_get::<Option<String>, _>(col.ordinal()) {
-                                json!(v)
-                            } else {
-                                Value::Null
-                            }
-                        }
-                        "BYTEA" | "VARBINARY" | "BINARY" | "BLOB" => {
-                            // Handle binary types explicitly for Postgres/Generic
-                            match row.try_get::<Option<Vec<u8>>, _>(col.ordinal()) {
-                                Ok(Some(v)) => {
-                                    // Display as hex, truncated for readability
-                                    let hex: String =
-                                        v.iter().take(32).map(|b| format!("{:02X}", b)).collect();
-                                    let suffix = if v.len() > 32 {
-                                        format!("... ({} bytes)", v.len())
-                                    } else {
-                                        String::new()
-                                    };
-                                    json!(format!("0x{}{}", hex, suffix))
-                                }
-                                Ok(None) => Value::Null,
-                                Err(_) => Value::Null,
-                            }
-                        }
-                        _ => {
-                            // PG also calls text TEXT, varchar VARCHAR
-                            match row.try_get::<Option<String>, _>(col.ordinal()) {
-                                Ok(v) => json!(v),
-                                Err(_) => {
-                                    // Fallback for unknown types (UUID, etc) usually behave as strings in simple fetch if cast,
-                                    // but try_get::<String> might fail if sqlx strictly maps them.
-                                    // Try simple ToString if possible or empty.
-                                    // For now, let's try to get as ANY string representation or NULL
-
-                                    // Second fallback: try as binary blob
-                                    match row.try_get::<Option<Vec<u8>>, _>(col.ordinal()) {
-                                        Ok(Some(v)) => {
-                                            let hex: String = v
-                                                .iter()
-                                                .take(16)
-                                                .map(|b| format!("{:02X}", b))
-                                                .collect();
-                                            let suffix = if v.len() > 16 { "..." } else { "" };
-                                            json!(format!("[BLOB: 0x{}{}]", hex, suffix))
-                                        }
-                                        _ => Value::Null,
-                                    }
-                                }
-                            }
-                        }
-                    };
-                    map.insert(name.to_string(), value);
-                }
-                results.push(map);
+                results.push(sql_value::pg_row_values(&row, false).into_iter().collect());
             }
             Ok(results)
         }
         "redis" => {
-            let url = format!("redis://{}:{}/", config.host, config.port);
-            let client = redis::Client::open(url).map_err(|e| e.to_string())?;
-            let mut con = client
-                .get_multiplexed_async_connection()
-                .await
-                .map_err(|e| e.to_string())?;
-
-            // Password auth if needed
-            if let Some(pass) = &config.password {
-                if !pass.is_empty() {
-                    let _: () = redis::cmd("AUTH")
-                        .arg(pass)
-                        .query_async(&mut con)
-                        .await
-                        .map_err(|e| e.to_string())?;
-                }
-            }
-
             // Select DB if provided (parse "db0 (15)", "db0", "0", etc.)
-            if let Some(db) = &config.database {
-                if !db.is_empty() {
-                    let db_part = db.split_whitespace().next().unwrap_or("");
-                    let db_index: i32 = if db_part.is_empty() {
-                        0
-                    } else if let Some(num_str) = db_part.strip_prefix("db") {
-                        num_str.parse().unwrap_or(0)
-                    } else {
-                        db_part.parse().unwrap_or(0)
-                    };
-                    let _: () = redis::cmd("SELECT")
-                        .arg(db_index)
-                        .query_async(&mut con)
-                        .await
-                        .map_err(|e| e.to_string())?;
-                }
-            }
+            // In cluster mode keyed commands follow MOVED/ASK redirects automatically
+            let db_index = redis_conn::parse_db_index(config.database.as_deref().unwrap_or(""));
+            let mut con = redis_conn::connect(&config, db_index).await?;
 
             let mut results = Vec::new();
 
@@ -1475,6 +1078,7 @@ async fn execute_query(
                 match v {
                     redis::Value::Nil => "(nil)".to_string(),
                     redis::Value::Okay => "OK".to_string(),
+                    redis::Value::BulkString(bytes) => redis_value::display_text(&bytes),
                     _ => {
                         // Use FromRedisValue to convert complex types (Data/Bulk) to String
                         // This handles formatting logic internally
@@ -1607,6 +1211,7 @@ pub struct RedisKeyInfo {
     pub ttl: i64, // -1 = no expiry, -2 = key doesn't exist
     pub value: String,
     pub length: Option<i64>, // For lists, sets, hashes, zsets
+    pub encoding: String,    // Format `value` is rendered in, e.g. "utf8", "json", "msgpack", "hex"
 }
 
 #[tauri::command]
@@ -1614,40 +1219,11 @@ async fn get_redis_key_value(
     config: ConnectionConfig,
     key: String,
     database: Option<String>,
+    format: Option<String>, // View format, None or "auto" = detect
 ) -> Result<RedisKeyInfo, String> {
-    let url = format!("redis://{}:{}/", config.host, config.port);
-    let client = redis::Client::open(url).map_err(|e| e.to_string())?;
-    let mut con = client
-        .get_multiplexed_async_connection()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    // Auth if needed
-    if let Some(pass) = &config.password {
-        if !pass.is_empty() {
-            let _: () = redis::cmd("AUTH")
-                .arg(pass)
-                .query_async(&mut con)
-                .await
-                .map_err(|e| e.to_string())?;
-        }
-    }
-
     // Select DB
-    let db_str = database.or(config.database).unwrap_or_default();
-    let db_part = db_str.split_whitespace().next().unwrap_or("");
-    let db_index: i32 = if db_part.is_empty() {
-        0
-    } else if let Some(num_str) = db_part.strip_prefix("db") {
-        num_str.parse().unwrap_or(0)
-    } else {
-        db_part.parse().unwrap_or(0)
-    };
-    let _: () = redis::cmd("SELECT")
-        .arg(db_index)
-        .query_async(&mut con)
-        .await
-        .map_err(|e| e.to_string())?;
+    let db_str = database.or(config.database.clone()).unwrap_or_default();
+    let mut con = redis_conn::connect(&config, redis_conn::parse_db_index(&db_str)).await?;
 
     // Get key type
     let key_type: String = redis::cmd("TYPE")
@@ -1663,15 +1239,18 @@ async fn get_redis_key_value(
         .await
         .unwrap_or(-1);
 
-    // Get value based on type
-    let (value, length) = match key_type.as_str() {
+    let format = format.as_deref();
+
+    // Values are fetched as raw bytes so binary payloads survive intact
+    let (value, length, encoding) = match key_type.as_str() {
         "string" => {
-            let v: String = redis::cmd("GET")
+            let v: Vec<u8> = redis::cmd("GET")
                 .arg(&key)
                 .query_async(&mut con)
                 .await
-                .unwrap_or_default();
-            (v, None)
+                .map_err(|e| e.to_string())?;
+            let (encoding, text) = redis_value::render(&v, format)?;
+            (text, None, encoding)
         }
         "list" => {
             let len: i64 = redis::cmd("LLEN")
@@ -1679,16 +1258,18 @@ async fn get_redis_key_value(
                 .query_async(&mut con)
                 .await
                 .unwrap_or(0);
-            let items: Vec<String> = redis::cmd("LRANGE")
+            let items: Vec<Vec<u8>> = redis::cmd("LRANGE")
                 .arg(&key)
                 .arg(0)
                 .arg(99)
                 .query_async(&mut con)
                 .await
                 .unwrap_or_default();
+            let (encoding, items) = redis_value::render_many(&items, format)?;
             (
                 serde_json::to_string_pretty(&items).unwrap_or_default(),
                 Some(len),
+                encoding,
             )
         }
         "set" => {
@@ -1697,14 +1278,16 @@ async fn get_redis_key_value(
                 .query_async(&mut con)
                 .await
                 .unwrap_or(0);
-            let items: Vec<String> = redis::cmd("SMEMBERS")
+            let items: Vec<Vec<u8>> = redis::cmd("SMEMBERS")
                 .arg(&key)
                 .query_async(&mut con)
                 .await
                 .unwrap_or_default();
+            let (encoding, items) = redis_value::render_many(&items, format)?;
             (
                 serde_json::to_string_pretty(&items).unwrap_or_default(),
                 Some(len),
+                encoding,
             )
         }
         "zset" => {
@@ -1713,7 +1296,7 @@ async fn get_redis_key_value(
                 .query_async(&mut con)
                 .await
                 .unwrap_or(0);
-            let items: Vec<String> = redis::cmd("ZRANGE")
+            let items: Vec<(Vec<u8>, String)> = redis::cmd("ZRANGE")
                 .arg(&key)
                 .arg(0)
                 .arg(99)
@@ -1721,9 +1304,18 @@ async fn get_redis_key_value(
                 .query_async(&mut con)
                 .await
                 .unwrap_or_default();
+            let (members, scores): (Vec<Vec<u8>>, Vec<String>) = items.into_iter().unzip();
+            let (encoding, members) = redis_value::render_many(&members, format)?;
+            // Keep the flat [member, score, ...] layout
+            let items: Vec<String> = members
+                .into_iter()
+                .zip(scores)
+                .flat_map(|(m, s)| [m, s])
+                .collect();
             (
                 serde_json::to_string_pretty(&items).unwrap_or_default(),
                 Some(len),
+                encoding,
             )
         }
         "hash" => {
@@ -1732,23 +1324,26 @@ async fn get_redis_key_value(
                 .query_async(&mut con)
                 .await
                 .unwrap_or(0);
-            let items: Vec<String> = redis::cmd("HGETALL")
+            let items: Vec<(Vec<u8>, Vec<u8>)> = redis::cmd("HGETALL")
                 .arg(&key)
                 .query_async(&mut con)
                 .await
                 .unwrap_or_default();
-            // Convert flat list to key-value pairs
-            let mut map = std::collections::HashMap::new();
-            let mut iter = items.iter();
-            while let (Some(k), Some(v)) = (iter.next(), iter.next()) {
-                map.insert(k.clone(), v.clone());
-            }
+            let (fields, values): (Vec<Vec<u8>>, Vec<Vec<u8>>) = items.into_iter().unzip();
+            let (encoding, values) = redis_value::render_many(&values, format)?;
+            // Convert pairs to a field -> value map
+            let map: std::collections::HashMap<String, String> = fields
+                .iter()
+                .map(|f| redis_value::display_text(f))
+                .zip(values)
+                .collect();
             (
                 serde_json::to_string_pretty(&map).unwrap_or_default(),
                 Some(len),
+                encoding,
             )
         }
-        _ => ("(unknown type)".to_string(), None),
+        _ => ("(unknown type)".to_string(), None, "utf8".to_string()),
     };
 
     Ok(RedisKeyInfo {
@@ -1757,13 +1352,751 @@ async fn get_redis_key_value(
         ttl,
         value,
         length,
+        encoding,
     })
 }
 
+/// Write a string value back, encoding the edited text in `encoding` so
+/// binary payloads keep their original format. The existing TTL is kept.
+#[tauri::command]
+async fn set_redis_key_value(
+    config: ConnectionConfig,
+    key: String,
+    value: String,
+    encoding: String,
+    database: Option<String>,
+) -> Result<(), String> {
+    let bytes = redis_value::encode(&value, &encoding)?;
+
+    // Select DB
+    let db_str = database.or(config.database.clone()).unwrap_or_default();
+    let mut con = redis_conn::connect(&config, redis_conn::parse_db_index(&db_str)).await?;
+
+    // Read the remaining TTL and SET with it in one script, so the key can't expire in
+    // between and come back without one. KEEPTTL would do the same but needs Redis 6.0.
+    redis::cmd("EVAL")
+        .arg(
+            "local ttl = redis.call('PTTL', KEYS[1]) \
+             if ttl > 0 then return redis.call('SET', KEYS[1], ARGV[1], 'PX', ttl) end \
+             return redis.call('SET', KEYS[1], ARGV[1])",
+        )
+        .arg(1)
+        .arg(&key)
+        .arg(&bytes)
+        .query_async(&mut con)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// ============ Redis Dashboard Commands ============
+
+#[tauri::command]
+async fn get_redis_info(
+    config: ConnectionConfig,
+    section: Option<String>,
+) -> Result<Vec<redis_admin::RedisNodeInfo>, String> {
+    let mut con = redis_conn::connect(&config, 0).await?;
+    redis_admin::info(&mut con, section.as_deref()).await
+}
+
+#[tauri::command]
+async fn get_redis_slowlog(
+    config: ConnectionConfig,
+    count: Option<i64>,
+) -> Result<Vec<redis_admin::RedisSlowlogEntry>, String> {
+    let mut con = redis_conn::connect(&config, 0).await?;
+    redis_admin::slowlog(&mut con, count.unwrap_or(128)).await
+}
+
+#[tauri::command]
+async fn get_redis_clients(
+    config: ConnectionConfig,
+) -> Result<Vec<redis_admin::RedisClientInfo>, String> {
+    let mut con = redis_conn::connect(&config, 0).await?;
+    redis_admin::clients(&mut con).await
+}
+
+#[tauri::command]
+async fn kill_redis_client(
+    config: ConnectionConfig,
+    node: Option<String>,
+    client_id: i64,
+) -> Result<(), String> {
+    let mut con = redis_conn::connect(&config, 0).await?;
+    redis_admin::kill_client(&mut con, node.as_deref(), client_id).await
+}
+
+#[tauri::command]
+async fn get_redis_memory_stats(
+    config: ConnectionConfig,
+) -> Result<Vec<redis_admin::RedisMemoryStats>, String> {
+    let mut con = redis_conn::connect(&config, 0).await?;
+    redis_admin::memory_stats(&mut con).await
+}
+
+#[tauri::command]
+async fn analyze_redis_big_keys(
+    config: ConnectionConfig,
+    database: Option<String>,
+    sample_limit: Option<usize>,
+    top_n: Option<usize>,
+) -> Result<redis_admin::RedisBigKeyReport, String> {
+    let db_str = database.or(config.database.clone()).unwrap_or_default();
+    let mut con = redis_conn::connect(&config, redis_conn::parse_db_index(&db_str)).await?;
+    redis_admin::big_keys(&mut con, sample_limit.unwrap_or(10000), top_n.unwrap_or(10)).await
+}
+
+// ============ Redis Pub/Sub & Monitor Commands ============
+
+#[tauri::command]
+async fn start_redis_subscribe(
+    app: tauri::AppHandle,
+    config: ConnectionConfig,
+    channels: Vec<String>,
+    patterns: Vec<String>,
+    max_per_second: Option<usize>,
+) -> Result<String, String> {
+    redis_stream::subscribe(app, &config, channels, patterns, max_per_second).await
+}
+
+#[tauri::command]
+async fn start_redis_monitor(
+    app: tauri::AppHandle,
+    config: ConnectionConfig,
+    max_per_second: Option<usize>,
+) -> Result<String, String> {
+    redis_stream::monitor(app, &config, max_per_second).await
+}
+
+#[tauri::command]
+fn stop_redis_stream(
+    streams: tauri::State<'_, redis_stream::RedisStreams>,
+    session_id: String,
+) -> Result<(), String> {
+    streams.stop(&session_id);
+    Ok(())
+}
+
+#[tauri::command]
+async fn publish_redis_message(
+    config: ConnectionConfig,
+    channel: String,
+    message: String,
+) -> Result<i64, String> {
+    let mut con = redis_conn::connect(&config, 0).await?;
+    // Number of clients that received the message
+    redis::cmd("PUBLISH")
+        .arg(&channel)
+        .arg(&message)
+        .query_async(&mut con)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// ============ Redis Export & Import Commands ============
+
+#[tauri::command]
+async fn export_redis_keys(
+    app: tauri::AppHandle,
+    config: ConnectionConfig,
+    options: redis_transfer::RedisExportOptions,
+    database: Option<String>,
+) -> Result<String, String> {
+    let db_str = database.or(config.database.clone()).unwrap_or_default();
+    let con = redis_conn::connect(&config, redis_conn::parse_db_index(&db_str)).await?;
+    Ok(jobs::spawn(&app, "redis_export", move |job| {
+        redis_transfer::export(job, con, options)
+    }))
+}
+
+#[tauri::command]
+async fn import_redis_keys(
+    app: tauri::AppHandle,
+    config: ConnectionConfig, // Target connection
+    path: String,
+    conflict: Option<String>, // "skip" (default) or "replace"
+    database: Option<String>,
+) -> Result<String, String> {
+    let db_str = database.or(config.database.clone()).unwrap_or_default();
+    let con = redis_conn::connect(&config, redis_conn::parse_db_index(&db_str)).await?;
+    Ok(jobs::spawn(&app, "redis_import", move |job| {
+        redis_transfer::import(job, con, path, conflict)
+    }))
+}
+
+// ============ Export, Import, Dump, Copy & Job Commands ============
+
+#[tauri::command]
+async fn export_data(
+    app: tauri::AppHandle,
+    config: ConnectionConfig,
+    source: export::ExportSource,
+    options: export::ExportOptions,
+    database: Option<String>,
+) -> Result<String, String> {
+    // Connect up front so bad credentials fail the command instead of the job
+    let conn = sql_conn::SqlConnection::connect(&config, database.as_deref()).await?;
+    let masker = load_masker(&app, &config, &database)?;
+    Ok(jobs::spawn(&app, "export", move |job| {
+        export::run(job, conn, source, options, masker)
+    }))
+}
+
+#[tauri::command]
+fn preview_import_file(
+    file: import::ImportFile,
+    limit: Option<usize>,
+) -> Result<import::ImportPreview, String> {
+    import::preview(&file, limit)
+}
+
+#[tauri::command]
+async fn import_file(
+    app: tauri::AppHandle,
+    config: ConnectionConfig,
+    table: String,
+    file: import::ImportFile,
+    options: import::ImportOptions,
+    database: Option<String>,
+) -> Result<String, String> {
+    let conn = sql_conn::SqlConnection::connect(&config, database.as_deref()).await?;
+    Ok(jobs::spawn(&app, "import", move |job| {
+        import::run(job, conn, config, database, table, file, options)
+    }))
+}
+
+#[tauri::command]
+async fn dump_database(
+    app: tauri::AppHandle,
+    config: ConnectionConfig,
+    options: dump::DumpOptions,
+    database: Option<String>,
+) -> Result<String, String> {
+    let conn = sql_conn::SqlConnection::connect(&config, database.as_deref()).await?;
+    let masker = load_masker(&app, &config, &database)?;
+    Ok(jobs::spawn(&app, "dump", move |job| {
+        dump::dump(job, conn, config, database, options, masker)
+    }))
+}
+
+#[tauri::command]
+async fn restore_sql_file(
+    app: tauri::AppHandle,
+    config: ConnectionConfig,
+    path: String,
+    database: Option<String>,
+    continue_on_error: Option<bool>,
+) -> Result<String, String> {
+    let conn = sql_conn::SqlConnection::connect(&config, database.as_deref()).await?;
+    Ok(jobs::spawn(&app, "restore", move |job| {
+        dump::restore(job, conn, path, continue_on_error.unwrap_or(false))
+    }))
+}
+
+#[tauri::command]
+async fn copy_table(
+    app: tauri::AppHandle,
+    source_conn: ConnectionConfig,
+    source_table: String,
+    target_conn: ConnectionConfig,
+    target_table: String,
+    options: table_copy::CopyOptions,
+) -> Result<String, String> {
+    let source =
+        sql_conn::SqlConnection::connect(&source_conn, options.source_database.as_deref()).await?;
+    let target =
+        sql_conn::SqlConnection::connect(&target_conn, options.target_database.as_deref()).await?;
+    let masker = load_masker(&app, &source_conn, &options.source_database)?;
+    Ok(jobs::spawn(&app, "copy", move |job| {
+        table_copy::run(
+            job,
+            source,
+            source_conn,
+            source_table,
+            target,
+            target_conn,
+            target_table,
+            options,
+            masker,
+        )
+    }))
+}
+
+#[tauri::command]
+fn cancel_job(jobs: tauri::State<'_, jobs::Jobs>, job_id: String) -> Result<bool, String> {
+    Ok(jobs.cancel(&job_id))
+}
+
+// ============ Query History Commands ============
+
+#[tauri::command]
+fn search_query_history(
+    app: tauri::AppHandle,
+    history: tauri::State<'_, query_history::QueryHistory>,
+    filter: query_history::QueryHistoryFilter,
+) -> Result<Vec<query_history::QueryHistoryEntry>, String> {
+    history.search(&app, &filter)
+}
+
+#[tauri::command]
+fn pin_query_history(
+    app: tauri::AppHandle,
+    history: tauri::State<'_, query_history::QueryHistory>,
+    id: String,
+    pinned: bool,
+) -> Result<(), String> {
+    history.set_pinned(&app, &id, pinned)
+}
+
+#[tauri::command]
+fn purge_query_history(
+    app: tauri::AppHandle,
+    history: tauri::State<'_, query_history::QueryHistory>,
+    older_than_days: Option<u32>, // None = everything
+    include_pinned: Option<bool>,
+) -> Result<u64, String> {
+    history.purge(&app, older_than_days, include_pinned.unwrap_or(false))
+}
+
+// ============ Saved Query Commands ============
+
+fn get_saved_queries_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    // Lives next to connections.json
+    Ok(get_config_path(app_handle)?.with_file_name("saved_queries.json"))
+}
+
+#[tauri::command]
+fn get_saved_queries(
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<saved_queries::SavedQuery>, String> {
+    saved_queries::load(&get_saved_queries_path(&app_handle)?)
+}
+
+#[tauri::command]
+fn save_saved_query(
+    app_handle: tauri::AppHandle,
+    query: saved_queries::SavedQuery,
+) -> Result<saved_queries::SavedQuery, String> {
+    saved_queries::save(&get_saved_queries_path(&app_handle)?, query)
+}
+
+#[tauri::command]
+fn delete_saved_query(app_handle: tauri::AppHandle, id: String) -> Result<(), String> {
+    saved_queries::delete(&get_saved_queries_path(&app_handle)?, &id)
+}
+
+#[tauri::command]
+fn export_saved_queries(
+    app_handle: tauri::AppHandle,
+    path: String,
+    ids: Option<Vec<String>>, // None = all
+) -> Result<u64, String> {
+    saved_queries::export(&get_saved_queries_path(&app_handle)?, &path, ids)
+}
+
+#[tauri::command]
+fn import_saved_queries(
+    app_handle: tauri::AppHandle,
+    path: String,
+    overwrite: Option<bool>,
+) -> Result<u64, String> {
+    saved_queries::import(
+        &get_saved_queries_path(&app_handle)?,
+        &path,
+        overwrite.unwrap_or(false),
+    )
+}
+
+#[tauri::command]
+async fn run_saved_query(
+    app_handle: tauri::AppHandle,
+    config: ConnectionConfig,
+    id: String,
+    params: HashMap<String, Value>,
+) -> Result<Vec<HashMap<String, Value>>, String> {
+    let queries = saved_queries::load(&get_saved_queries_path(&app_handle)?)?;
+    let query = queries
+        .into_iter()
+        .find(|q| q.id == id)
+        .ok_or("保存的查询不存在")?;
+    if let Some(db_type) = query.db_type.as_deref().filter(|t| !t.is_empty()) {
+        if db_type != config.db_type {
+            return Err(format!(
+                "该查询适用于 {}，当前连接是 {}",
+                db_type, config.db_type
+            ));
+        }
+    }
+
+    let started = std::time::Instant::now();
+    let (sql, values) = saved_queries::bind(&query.sql, &config.db_type, &params)?;
+    let result = match sql_conn::SqlConnection::connect(&config, None).await {
+        Ok(mut conn) => conn.fetch_all_params(&sql, &values).await,
+        Err(e) => Err(e),
+    };
+    let result: Result<Vec<HashMap<String, Value>>, String> =
+        result.map(|rows| rows.into_iter().map(|r| r.into_iter().collect()).collect());
+
+    let entry = query_history::QueryHistoryEntry::new(
+        &config,
+        &query.sql,
+        started.elapsed().as_millis() as u64,
+        result
+            .as_ref()
+            .map(|rows| rows.len() as u64)
+            .map_err(|e| e.as_str()),
+    );
+    if let Err(e) = app_handle
+        .state::<query_history::QueryHistory>()
+        .record(&app_handle, &entry)
+    {
+        println!("Failed to record query history: {}", e);
+    }
+    result
+}
+
+// ============ Query Plan Commands ============
+
+#[tauri::command]
+async fn explain_query(
+    config: ConnectionConfig,
+    query: String,
+    analyze: Option<bool>,
+    database: Option<String>,
+) -> Result<explain::ExplainResult, String> {
+    let mut conn = sql_conn::SqlConnection::connect(&config, database.as_deref()).await?;
+    explain::explain(&mut conn, &query, analyze.unwrap_or(false)).await
+}
+
+// ============ Server Activity Commands ============
+
+#[tauri::command]
+async fn get_processes(config: ConnectionConfig) -> Result<Vec<sql_admin::SqlProcess>, String> {
+    let mut conn = sql_conn::SqlConnection::connect(&config, None).await?;
+    sql_admin::processes(&mut conn).await
+}
+
+#[tauri::command]
+async fn kill_process(
+    config: ConnectionConfig,
+    id: i64,
+    mode: Option<String>,
+) -> Result<(), String> {
+    let mut conn = sql_conn::SqlConnection::connect(&config, None).await?;
+    sql_admin::kill_process(&mut conn, id, mode.as_deref().unwrap_or("query")).await
+}
+
+#[tauri::command]
+async fn get_server_health(
+    config: ConnectionConfig,
+    previous: Option<sql_admin::ServerHealth>,
+) -> Result<sql_admin::ServerHealth, String> {
+    let mut conn = sql_conn::SqlConnection::connect(&config, None).await?;
+    sql_admin::health(&mut conn, previous.as_ref()).await
+}
+
+#[tauri::command]
+async fn get_top_statements(
+    config: ConnectionConfig,
+    limit: Option<usize>,
+) -> Result<Vec<sql_admin::StatementStat>, String> {
+    let mut conn = sql_conn::SqlConnection::connect(&config, None).await?;
+    sql_admin::top_statements(&mut conn, limit.unwrap_or(20)).await
+}
+
+// ============ User & Privilege Commands ============
+
+#[tauri::command]
+async fn get_users(config: ConnectionConfig) -> Result<Vec<sql_users::DbUser>, String> {
+    let mut conn = sql_conn::SqlConnection::connect(&config, None).await?;
+    sql_users::list(&mut conn).await
+}
+
+#[tauri::command]
+async fn manage_users(
+    config: ConnectionConfig,
+    operations: Vec<sql_users::UserOperation>,
+    dry_run: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let mut conn = sql_conn::SqlConnection::connect(&config, None).await?;
+    sql_users::apply(&mut conn, &operations, dry_run.unwrap_or(false)).await
+}
+
+// ============ Database & Table Lifecycle Commands ============
+
+/// Run `statements` unless `dry_run`, returning them for preview either way.
+/// `atomic` runs them in one transaction on PostgreSQL.
+async fn run_ddl(
+    config: &ConnectionConfig,
+    database: Option<&str>,
+    statements: Vec<String>,
+    dry_run: Option<bool>,
+    atomic: bool,
+) -> Result<Vec<String>, String> {
+    if !dry_run.unwrap_or(false) {
+        let mut conn = sql_conn::SqlConnection::connect(config, database).await?;
+        if atomic {
+            sql_ddl::run_transaction(&mut conn, &statements).await?;
+        } else {
+            sql_ddl::run_statements(&mut conn, &statements).await?;
+        }
+    }
+    Ok(statements)
+}
+
+/// PostgreSQL can't create or drop the database it is connected to
+fn maintenance_database(config: &ConnectionConfig) -> Option<&'static str> {
+    match config.db_type.as_str() {
+        "postgresql" => Some("postgres"),
+        _ => None,
+    }
+}
+
+#[tauri::command]
+async fn create_database(
+    config: ConnectionConfig,
+    name: String,
+    options: Option<sql_ddl::DatabaseOptions>,
+    dry_run: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let statements =
+        sql_ddl::create_database_sql(&config.db_type, &name, &options.unwrap_or_default())?;
+    run_ddl(
+        &config,
+        maintenance_database(&config),
+        statements,
+        dry_run,
+        false,
+    )
+    .await
+}
+
+#[tauri::command]
+async fn create_table(
+    config: ConnectionConfig,
+    spec: sql_ddl::TableSpec,
+    database: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let statements = sql_ddl::create_table_sql(&config.db_type, &spec)?;
+    run_ddl(&config, database.as_deref(), statements, dry_run, true).await
+}
+
+#[tauri::command]
+async fn drop_database(
+    config: ConnectionConfig,
+    name: String,
+    confirm: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<Vec<String>, String> {
+    if !dry_run.unwrap_or(false) {
+        sql_ddl::check_confirmation(&name, confirm.as_deref())?;
+    }
+    let statements = sql_ddl::drop_database_sql(&config.db_type, &name);
+    run_ddl(
+        &config,
+        maintenance_database(&config),
+        statements,
+        dry_run,
+        false,
+    )
+    .await
+}
+
+#[tauri::command]
+async fn drop_table(
+    config: ConnectionConfig,
+    table: String,
+    database: Option<String>,
+    cascade: Option<bool>,
+    confirm: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<Vec<String>, String> {
+    if !dry_run.unwrap_or(false) {
+        sql_ddl::check_confirmation(&table, confirm.as_deref())?;
+    }
+    let statements = sql_ddl::drop_table_sql(&config.db_type, &table, cascade.unwrap_or(false));
+    run_ddl(&config, database.as_deref(), statements, dry_run, true).await
+}
+
+#[tauri::command]
+async fn truncate_table(
+    config: ConnectionConfig,
+    table: String,
+    database: Option<String>,
+    restart_identity: Option<bool>,
+    cascade: Option<bool>,
+    confirm: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<Vec<String>, String> {
+    if !dry_run.unwrap_or(false) {
+        sql_ddl::check_confirmation(&table, confirm.as_deref())?;
+    }
+    let statements = sql_ddl::truncate_table_sql(
+        &config.db_type,
+        &table,
+        restart_identity.unwrap_or(false),
+        cascade.unwrap_or(false),
+    );
+    run_ddl(&config, database.as_deref(), statements, dry_run, true).await
+}
+
+#[tauri::command]
+async fn rename_table(
+    config: ConnectionConfig,
+    table: String,
+    new_name: String,
+    database: Option<String>,
+    dry_run: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let statements = sql_ddl::rename_table_sql(&config.db_type, &table, &new_name)?;
+    run_ddl(&config, database.as_deref(), statements, dry_run, true).await
+}
+
+#[tauri::command]
+async fn duplicate_table(
+    config: ConnectionConfig,
+    table: String,
+    new_name: String,
+    database: Option<String>,
+    with_data: Option<bool>,
+    dry_run: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let statements = sql_ddl::duplicate_table_sql(
+        &config.db_type,
+        &table,
+        &new_name,
+        with_data.unwrap_or(false),
+    )?;
+    run_ddl(&config, database.as_deref(), statements, dry_run, true).await
+}
+
+// ============ Table Maintenance Commands ============
+
+#[tauri::command]
+async fn maintain_tables(
+    app: tauri::AppHandle,
+    config: ConnectionConfig,
+    options: maintenance::MaintenanceOptions,
+    database: Option<String>,
+) -> Result<String, String> {
+    let conn = sql_conn::SqlConnection::connect(&config, database.as_deref()).await?;
+    // Reject unsupported operations before starting the job
+    for table in &options.tables {
+        maintenance::maintenance_sql(conn.db_type(), table, &options)?;
+    }
+    Ok(jobs::spawn(&app, "maintenance", move |job| {
+        maintenance::run(job, conn, config, database, options)
+    }))
+}
+
+// ============ Test Data Commands ============
+
+#[tauri::command]
+async fn generate_data(
+    app: tauri::AppHandle,
+    config: ConnectionConfig,
+    table: String,
+    row_count: u64,
+    rules: Option<Vec<datagen::GenerateRule>>,
+    options: Option<datagen::GenerateOptions>,
+    database: Option<String>,
+) -> Result<String, String> {
+    let conn = sql_conn::SqlConnection::connect(&config, database.as_deref()).await?;
+    Ok(jobs::spawn(&app, "generate", move |job| {
+        datagen::run(
+            job,
+            conn,
+            config,
+            database,
+            table,
+            row_count,
+            rules.unwrap_or_default(),
+            options.unwrap_or_default(),
+        )
+    }))
+}
+
+// ============ Data Masking Commands ============
+
+fn get_masking_path(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
+    // Lives next to connections.json
+    Ok(get_config_path(app_handle)?.with_file_name("masking_rules.json"))
+}
+
+/// Database the masking rules are keyed by: the one given, else the connection's default
+fn masking_database<'a>(
+    config: &'a ConnectionConfig,
+    database: &'a Option<String>,
+) -> Option<&'a str> {
+    database
+        .as_deref()
+        .filter(|db| !db.is_empty())
+        .or(config.database.as_deref())
+}
+
+/// Saved masking rules of a connection, applied by export, dump and copy jobs
+fn load_masker(
+    app_handle: &tauri::AppHandle,
+    config: &ConnectionConfig,
+    database: &Option<String>,
+) -> Result<masking::Masker, String> {
+    masking::Masker::load(
+        &get_masking_path(app_handle)?,
+        &config.id,
+        masking_database(config, database),
+    )
+}
+
+#[tauri::command]
+fn get_masking_rules(
+    app_handle: tauri::AppHandle,
+    config: ConnectionConfig,
+    table: String,
+    database: Option<String>,
+) -> Result<Vec<masking::MaskRule>, String> {
+    masking::rules(
+        &get_masking_path(&app_handle)?,
+        &config.id,
+        masking_database(&config, &database),
+        &table,
+    )
+}
+
+#[tauri::command]
+fn save_masking_rules(
+    app_handle: tauri::AppHandle,
+    config: ConnectionConfig,
+    table: String,
+    database: Option<String>,
+    rules: Vec<masking::MaskRule>,
+) -> Result<(), String> {
+    masking::save(
+        &get_masking_path(&app_handle)?,
+        &config.id,
+        masking_database(&config, &database),
+        &table,
+        rules,
+    )
+}
+
+#[tauri::command]
+async fn suggest_masking_rules(
+    config: ConnectionConfig,
+    table: String,
+    database: Option<String>,
+) -> Result<Vec<masking::MaskRule>, String> {
+    let columns = get_columns(config, table, database).await?;
+    Ok(masking::suggest(&columns))
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .manage(redis_stream::RedisStreams::default())
+        .manage(jobs::Jobs::default())
+        .manage(query_history::QueryHistory::default())
         .invoke_handler(tauri::generate_handler![
             test_connection,
             save_connection,
@@ -1778,7 +2111,55 @@ pub fn run() {
             get_ai_config,
             save_ai_config,
             generate_sql_from_text,
-            get_redis_key_value
+            get_redis_key_value,
+            set_redis_key_value,
+            get_redis_info,
+            get_redis_slowlog,
+            get_redis_clients,
+            kill_redis_client,
+            get_redis_memory_stats,
+            analyze_redis_big_keys,
+            start_redis_subscribe,
+            start_redis_monitor,
+            stop_redis_stream,
+            publish_redis_message,
+            export_redis_keys,
+            import_redis_keys,
+            export_data,
+            preview_import_file,
+            import_file,
+            dump_database,
+            restore_sql_file,
+            copy_table,
+            cancel_job,
+            search_query_history,
+            pin_query_history,
+            purge_query_history,
+            get_saved_queries,
+            save_saved_query,
+            delete_saved_query,
+            export_saved_queries,
+            import_saved_queries,
+            run_saved_query,
+            explain_query,
+            get_processes,
+            kill_process,
+            get_server_health,
+            get_top_statements,
+            get_users,
+            manage_users,
+            create_database,
+            drop_database,
+            create_table,
+            drop_table,
+            truncate_table,
+            rename_table,
+            duplicate_table,
+            maintain_tables,
+            generate_data,
+            get_masking_rules,
+            save_masking_rules,
+            suggest_masking_rules
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
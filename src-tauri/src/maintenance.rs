@@ -0,0 +1,346 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::jobs::{JobHandle, JobSummary};
+use crate::sql_admin::{num_field, text_field};
+use crate::sql_conn::SqlConnection;
+use crate::sql_dialect::quote_ident;
+use crate::ConnectionConfig;
+
+/// How often the progress views are polled while a statement runs
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+const MYSQL_CHECK_MODES: &[&str] = &["QUICK", "FAST", "MEDIUM", "EXTENDED", "CHANGED"];
+const MYSQL_REPAIR_MODES: &[&str] = &["QUICK", "EXTENDED", "USE_FRM"];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MaintenanceOptions {
+    // MySQL: "analyze" | "optimize" | "check" | "repair"
+    // PostgreSQL: "vacuum" | "analyze" | "reindex" | "cluster"
+    pub operation: String,
+    pub tables: Vec<String>,
+    pub full: Option<bool>,    // VACUUM FULL
+    pub analyze: Option<bool>, // VACUUM ANALYZE
+    pub index: Option<String>, // CLUSTER ... USING, default the previously clustered index
+    pub mode: Option<String>,  // MySQL CHECK / REPAIR option, e.g. "EXTENDED"
+}
+
+/// One line of server output, e.g. a MySQL Msg_type / Msg_text row
+#[derive(Debug, Serialize, Clone)]
+pub struct MaintenanceMessage {
+    pub msg_type: String, // "status", "note", "info", "warning", "error"
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TableMaintenance {
+    pub table: String,
+    pub statement: String,
+    pub ok: bool,
+    pub duration_ms: u64,
+    pub size_before: Option<i64>, // PostgreSQL pg_total_relation_size
+    pub size_after: Option<i64>,
+    pub messages: Vec<MaintenanceMessage>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct MaintenanceSummary {
+    pub operation: String,
+    pub failed: u64,
+    pub results: Vec<TableMaintenance>,
+}
+
+impl From<MaintenanceSummary> for JobSummary {
+    fn from(summary: MaintenanceSummary) -> Self {
+        JobSummary {
+            processed: summary.results.len() as u64 - summary.failed,
+            details: serde_json::to_value(&summary).ok(),
+        }
+    }
+}
+
+fn message(msg_type: &str, text: impl Into<String>) -> MaintenanceMessage {
+    MaintenanceMessage {
+        msg_type: msg_type.to_string(),
+        text: text.into(),
+    }
+}
+
+/// Statement for one table, validated against the operations each engine supports
+pub fn maintenance_sql(
+    db_type: &str,
+    table: &str,
+    options: &MaintenanceOptions,
+) -> Result<String, String> {
+    let name = quote_ident(db_type, table);
+    let operation = options.operation.to_lowercase();
+    let mode = options.mode.as_deref().map(str::to_uppercase);
+    match db_type {
+        "mysql" => {
+            let allowed = match operation.as_str() {
+                "check" => MYSQL_CHECK_MODES,
+                "repair" => MYSQL_REPAIR_MODES,
+                "analyze" | "optimize" => &[][..],
+                other => return Err(format!("MySQL 不支持的维护操作: {}", other)),
+            };
+            let mut sql = format!("{} TABLE {}", operation.to_uppercase(), name);
+            if let Some(mode) = mode {
+                if !allowed.contains(&mode.as_str()) {
+                    return Err(format!(
+                        "{} TABLE 不支持选项: {}",
+                        operation.to_uppercase(),
+                        mode
+                    ));
+                }
+                sql.push(' ');
+                sql.push_str(&mode);
+            }
+            Ok(sql)
+        }
+        "postgresql" => Ok(match operation.as_str() {
+            "vacuum" => {
+                let mut flags = Vec::new();
+                if options.full.unwrap_or(false) {
+                    flags.push("FULL");
+                }
+                if options.analyze.unwrap_or(false) {
+                    flags.push("ANALYZE");
+                }
+                if flags.is_empty() {
+                    format!("VACUUM {}", name)
+                } else {
+                    format!("VACUUM ({}) {}", flags.join(", "), name)
+                }
+            }
+            "analyze" => format!("ANALYZE {}", name),
+            "reindex" => format!("REINDEX TABLE {}", name),
+            "cluster" => match options.index.as_deref().filter(|i| !i.is_empty()) {
+                Some(index) => format!("CLUSTER {} USING {}", name, quote_ident(db_type, index)),
+                None => format!("CLUSTER {}", name),
+            },
+            other => return Err(format!("PostgreSQL 不支持的维护操作: {}", other)),
+        }),
+        other => Err(format!("Unsupported database type: {}", other)),
+    }
+}
+
+/// Progress query for the statement running on backend `pid`, None when the server
+/// has no view for it (VACUUM FULL reports through the CLUSTER view, PostgreSQL 12+)
+fn progress_sql(db_type: &str, options: &MaintenanceOptions, pid: i64) -> Option<String> {
+    if db_type == "mysql" {
+        // Stage events only show up when the stage instruments are enabled
+        return Some(format!(
+            "SELECT s.EVENT_NAME AS phase, s.WORK_ESTIMATED AS total, s.WORK_COMPLETED AS done
+             FROM performance_schema.events_stages_current s
+             JOIN performance_schema.threads t ON t.THREAD_ID = s.THREAD_ID
+             WHERE t.PROCESSLIST_ID = {}",
+            pid
+        ));
+    }
+    let (view, total, done) = match options.operation.to_lowercase().as_str() {
+        "vacuum" if options.full.unwrap_or(false) => (
+            "pg_stat_progress_cluster",
+            "heap_blks_total",
+            "heap_blks_scanned",
+        ),
+        "vacuum" => (
+            "pg_stat_progress_vacuum",
+            "heap_blks_total",
+            "heap_blks_scanned",
+        ),
+        "cluster" => (
+            "pg_stat_progress_cluster",
+            "heap_blks_total",
+            "heap_blks_scanned",
+        ),
+        "reindex" => (
+            "pg_stat_progress_create_index",
+            "blocks_total",
+            "blocks_done",
+        ),
+        "analyze" => (
+            "pg_stat_progress_analyze",
+            "sample_blks_total",
+            "sample_blks_scanned",
+        ),
+        _ => return None,
+    };
+    Some(format!(
+        "SELECT phase, {} AS total, {} AS done FROM {} WHERE pid = {}",
+        total, done, view, pid
+    ))
+}
+
+async fn backend_id(conn: &mut SqlConnection) -> Result<i64, String> {
+    // CONNECTION_ID() is BIGINT UNSIGNED, pg_backend_pid() is int4
+    match conn {
+        SqlConnection::MySql(my) => sqlx::query_scalar::<_, u64>("SELECT CONNECTION_ID()")
+            .fetch_one(my)
+            .await
+            .map(|id| id as i64),
+        SqlConnection::Postgres(pg) => sqlx::query_scalar::<_, i32>("SELECT pg_backend_pid()")
+            .fetch_one(pg)
+            .await
+            .map(i64::from),
+    }
+    .map_err(|e| format!("无法获取连接 ID: {}", e))
+}
+
+async fn relation_size(conn: &mut SqlConnection, table: &str) -> Option<i64> {
+    if let SqlConnection::Postgres(pg) = conn {
+        sqlx::query_scalar("SELECT pg_total_relation_size(quote_ident($1)::regclass)")
+            .bind(table)
+            .fetch_one(pg)
+            .await
+            .ok()
+    } else {
+        None
+    }
+}
+
+/// Run `sql` on `conn` while a second connection polls the progress view and cancels
+/// the statement when the job is cancelled. Returns the result rows (MySQL) or none.
+#[allow(clippy::too_many_arguments)]
+async fn run_with_progress(
+    job: &JobHandle,
+    conn: &mut SqlConnection,
+    monitor: &mut Option<SqlConnection>,
+    sql: &str,
+    progress: Option<String>,
+    pid: i64,
+    table: &str,
+    index: u64,
+    total: u64,
+) -> Result<Vec<Vec<(String, serde_json::Value)>>, String> {
+    let is_mysql = conn.db_type() == "mysql";
+    let run = async {
+        if is_mysql {
+            conn.fetch_all(sql).await
+        } else {
+            conn.execute(sql).await.map(|_| Vec::new())
+        }
+    };
+    tokio::pin!(run);
+
+    let mut ticker = tokio::time::interval(POLL_INTERVAL);
+    let mut progress = progress;
+    let mut cancel_sent = false;
+    loop {
+        tokio::select! {
+            result = &mut run => return result,
+            _ = ticker.tick() => {
+                let Some(watcher) = monitor.as_mut() else { continue };
+                if job.is_cancelled() && !cancel_sent {
+                    cancel_sent = true;
+                    let _ = crate::sql_admin::kill_process(watcher, pid, "query").await;
+                }
+                let Some(query) = progress.as_deref() else { continue };
+                match watcher.fetch_all(query).await {
+                    Ok(rows) => {
+                        let Some(row) = rows.first() else { continue };
+                        let phase = text_field(row, "phase").unwrap_or_default();
+                        let detail = match (num_field(row, "done"), num_field(row, "total")) {
+                            (Some(done), Some(all)) if all > 0.0 => {
+                                format!("{} ({:.0}%)", phase, done / all * 100.0)
+                            }
+                            _ => phase,
+                        };
+                        job.progress(index, Some(total), Some(format!("{}: {}", table, detail)));
+                    }
+                    // View missing on older servers or no privilege: stop asking
+                    Err(_) => progress = None,
+                }
+            }
+        }
+    }
+}
+
+/// Run the maintenance operation on each table in turn. A failing table is recorded
+/// and the next one still runs; cancelling also interrupts the running statement.
+pub async fn run(
+    job: JobHandle,
+    mut conn: SqlConnection,
+    config: ConnectionConfig,
+    database: Option<String>,
+    options: MaintenanceOptions,
+) -> Result<MaintenanceSummary, String> {
+    if options.tables.is_empty() {
+        return Err("未选择表".to_string());
+    }
+    let db_type = conn.db_type();
+    let statements = options
+        .tables
+        .iter()
+        .map(|table| maintenance_sql(db_type, table, &options))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let pid = backend_id(&mut conn).await?;
+    let progress = progress_sql(db_type, &options, pid);
+    // Without a second connection the statements still run, just without progress
+    let mut monitor = SqlConnection::connect(&config, database.as_deref())
+        .await
+        .ok();
+
+    let total = options.tables.len() as u64;
+    let mut summary = MaintenanceSummary {
+        operation: options.operation.clone(),
+        failed: 0,
+        results: Vec::new(),
+    };
+    for (i, (table, sql)) in options.tables.iter().zip(statements).enumerate() {
+        job.check_cancelled()?;
+        job.progress(i as u64, Some(total), Some(table.clone()));
+
+        let size_before = relation_size(&mut conn, table).await;
+        let started = Instant::now();
+        let result = run_with_progress(
+            &job,
+            &mut conn,
+            &mut monitor,
+            &sql,
+            progress.clone(),
+            pid,
+            table,
+            i as u64,
+            total,
+        )
+        .await;
+        job.check_cancelled()?;
+
+        let mut entry = TableMaintenance {
+            table: table.clone(),
+            statement: sql,
+            ok: true,
+            duration_ms: started.elapsed().as_millis() as u64,
+            size_before,
+            size_after: None,
+            messages: Vec::new(),
+        };
+        match result {
+            Ok(rows) => {
+                // MySQL answers with Table / Op / Msg_type / Msg_text rows
+                for row in &rows {
+                    let msg_type = text_field(row, "Msg_type").unwrap_or_default();
+                    let text = text_field(row, "Msg_text").unwrap_or_default();
+                    if msg_type.eq_ignore_ascii_case("error") {
+                        entry.ok = false;
+                    }
+                    entry.messages.push(message(&msg_type.to_lowercase(), text));
+                }
+                if rows.is_empty() {
+                    entry.messages.push(message("status", "OK"));
+                }
+                entry.size_after = relation_size(&mut conn, table).await;
+            }
+            Err(e) => {
+                entry.ok = false;
+                entry.messages.push(message("error", e));
+            }
+        }
+        if !entry.ok {
+            summary.failed += 1;
+        }
+        summary.results.push(entry);
+    }
+    Ok(summary)
+}
@@ -0,0 +1,718 @@
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::datagen::{Rng, FIRST_NAMES, LAST_NAMES, WORDS};
+use crate::ColumnDef;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Weights and check characters of the 18-digit resident ID number (GB 11643)
+const ID_CARD_WEIGHTS: [u32; 17] = [7, 9, 10, 5, 8, 4, 2, 1, 6, 3, 7, 9, 10, 5, 8, 4, 2];
+const ID_CARD_CHECK: &[u8; 11] = b"10X98765432";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MaskRule {
+    pub column: String,
+    pub method: String,            // "hash", "fake", "null", "partial", "shuffle"
+    pub fake: Option<String>,      // fake: "email", "name", "phone", "id_card", "text"
+    pub keep_start: Option<usize>, // partial: characters left visible, default 3
+    pub keep_end: Option<usize>,   // partial, default 4
+    pub mask_char: Option<String>, // partial, default "*"
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MaskingProfile {
+    pub connection_id: String,
+    pub database: Option<String>,
+    pub table: String,
+    pub rules: Vec<MaskRule>,
+    #[serde(default)]
+    pub updated_at: i64, // unix millis
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct MaskingFile {
+    secret: String, // HMAC key, so hashes can't be recomputed from guessed values elsewhere
+    profiles: Vec<MaskingProfile>,
+}
+
+fn load_file(path: &Path) -> Result<MaskingFile, String> {
+    if !path.exists() {
+        return Ok(MaskingFile::default());
+    }
+    // A file that can't be read must not silently turn masking off or be overwritten
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("无法读取脱敏规则文件 {}: {}", path.display(), e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("脱敏规则文件已损坏 {}: {}", path.display(), e))
+}
+
+fn same_database(a: Option<&str>, b: Option<&str>) -> bool {
+    a.unwrap_or("") == b.unwrap_or("")
+}
+
+/// Saved rules of one table, empty when there are none
+pub fn rules(
+    path: &Path,
+    connection_id: &str,
+    database: Option<&str>,
+    table: &str,
+) -> Result<Vec<MaskRule>, String> {
+    Ok(load_file(path)?
+        .profiles
+        .into_iter()
+        .find(|p| {
+            p.connection_id == connection_id
+                && same_database(p.database.as_deref(), database)
+                && p.table == table
+        })
+        .map(|p| p.rules)
+        .unwrap_or_default())
+}
+
+/// Replace the rules of one table; no rules removes its profile
+pub fn save(
+    path: &Path,
+    connection_id: &str,
+    database: Option<&str>,
+    table: &str,
+    rules: Vec<MaskRule>,
+) -> Result<(), String> {
+    for rule in &rules {
+        if rule.column.trim().is_empty() {
+            return Err("脱敏规则的列名不能为空".to_string());
+        }
+        if !["hash", "fake", "null", "partial", "shuffle"].contains(&rule.method.as_str()) {
+            return Err(format!("Unsupported masking method: {}", rule.method));
+        }
+    }
+    let mut file = load_file(path)?;
+    if file.secret.is_empty() {
+        file.secret = uuid::Uuid::new_v4().simple().to_string();
+    }
+    file.profiles.retain(|p| {
+        !(p.connection_id == connection_id
+            && same_database(p.database.as_deref(), database)
+            && p.table == table)
+    });
+    if !rules.is_empty() {
+        file.profiles.push(MaskingProfile {
+            connection_id: connection_id.to_string(),
+            database: database.map(String::from),
+            table: table.to_string(),
+            rules,
+            updated_at: chrono::Utc::now().timestamp_millis(),
+        });
+    }
+    let json = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| e.to_string())
+}
+
+/// Suggested rules for columns whose names look like personal data
+pub fn suggest(columns: &[ColumnDef]) -> Vec<MaskRule> {
+    let rule =
+        |column: &ColumnDef, method: &str, fake: Option<&str>, keep: (usize, usize)| MaskRule {
+            column: column.name.clone(),
+            method: method.to_string(),
+            fake: fake.map(String::from),
+            keep_start: (method == "partial").then_some(keep.0),
+            keep_end: (method == "partial").then_some(keep.1),
+            mask_char: None,
+        };
+    let mut rules = Vec::new();
+    for column in columns {
+        let n = column.name.to_lowercase();
+        let has = |words: &[&str]| words.iter().any(|w| n.contains(w));
+        let suggestion = if has(&["email", "e_mail", "mail"]) {
+            rule(column, "fake", Some("email"), (0, 0))
+        } else if has(&["phone", "mobile", "tel", "cellphone"]) {
+            rule(column, "partial", None, (3, 4))
+        } else if has(&["id_card", "idcard", "id_no", "identity", "citizen"]) {
+            rule(column, "fake", Some("id_card"), (0, 0))
+        } else if has(&["ssn", "passport", "license_no", "licence_no"]) {
+            rule(column, "shuffle", None, (0, 0))
+        } else if has(&["card_no", "bank_card", "credit_card", "iban", "account_no"]) {
+            rule(column, "partial", None, (4, 4))
+        } else if has(&["password", "passwd", "pwd", "secret", "token", "salt"]) {
+            if column.is_nullable.unwrap_or(true) {
+                rule(column, "null", None, (0, 0))
+            } else {
+                rule(column, "hash", None, (0, 0))
+            }
+        } else if has(&["address", "addr"]) {
+            rule(column, "partial", None, (6, 0))
+        } else if has(&[
+            "real_name",
+            "full_name",
+            "first_name",
+            "last_name",
+            "surname",
+        ]) || n == "name"
+            || n == "realname"
+            || n == "fullname"
+        {
+            rule(column, "fake", Some("name"), (0, 0))
+        } else {
+            continue;
+        };
+        rules.push(suggestion);
+    }
+    rules
+}
+
+/// Rules keyed by table for one connection and database, loaded once per job
+#[derive(Debug, Clone, Default)]
+pub struct Masker {
+    key: Vec<u8>,
+    tables: HashMap<String, Vec<MaskRule>>,
+}
+
+impl Masker {
+    pub fn load(path: &Path, connection_id: &str, database: Option<&str>) -> Result<Self, String> {
+        let file = load_file(path)?;
+        let tables = file
+            .profiles
+            .into_iter()
+            .filter(|p| {
+                p.connection_id == connection_id && same_database(p.database.as_deref(), database)
+            })
+            .map(|p| (p.table, p.rules))
+            .collect();
+        Ok(Masker {
+            key: file.secret.into_bytes(),
+            tables,
+        })
+    }
+
+    /// Rules for the rows of `table`, `types` giving the source type of each column
+    pub fn for_table(&self, table: &str, types: Vec<(String, String)>) -> ColumnMasker {
+        ColumnMasker {
+            key: self.key.clone(),
+            rules: self.tables.get(table).cloned().unwrap_or_default(),
+            types,
+        }
+    }
+
+    /// Rules for the rows of a query, `sources` giving the (table, column) each result
+    /// column was read from. A column without a known source (computed, or any MySQL
+    /// query) could carry masked data under another name, so once the database has
+    /// rules such a query is refused instead of exported unmasked.
+    pub fn for_query(
+        &self,
+        sources: Vec<(String, Option<(String, String)>)>,
+        types: Vec<(String, String)>,
+    ) -> Result<ColumnMasker, String> {
+        let mut rules = Vec::new();
+        if self.tables.values().any(|r| !r.is_empty()) {
+            for (name, source) in sources {
+                let Some((table, column)) = source else {
+                    return Err(format!(
+                        "已配置脱敏规则，无法确定查询结果列 {} 的来源表列，请改为导出整表",
+                        name
+                    ));
+                };
+                let Some(rule) = self
+                    .tables
+                    .get(&table)
+                    .and_then(|r| r.iter().find(|r| r.column.eq_ignore_ascii_case(&column)))
+                else {
+                    continue;
+                };
+                let mut rule = rule.clone();
+                // The fake kind follows the source column, not its alias
+                if rule.fake.is_none() {
+                    rule.fake = Some(fake_kind(&rule.column).to_string());
+                }
+                rule.column = name;
+                rules.push(rule);
+            }
+        }
+        Ok(ColumnMasker {
+            key: self.key.clone(),
+            rules,
+            types,
+        })
+    }
+}
+
+/// Applies the rules of one table to result rows
+#[derive(Debug, Clone, Default)]
+pub struct ColumnMasker {
+    key: Vec<u8>,
+    rules: Vec<MaskRule>,
+    types: Vec<(String, String)>, // (column, source type)
+}
+
+/// What a masked value has to fit to be written back into its source column
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Target {
+    numeric: bool,
+    digits: usize,          // Numeric: integer digits the type holds
+    max_len: Option<usize>, // Text: declared length, e.g. varchar(20)
+}
+
+impl Target {
+    fn from_type(type_name: &str) -> Self {
+        let t = type_name.trim().to_lowercase();
+        // MySQL COLUMN_TYPE may end in attributes: "int unsigned", "bigint(20) unsigned zerofill"
+        let base = t
+            .split('(')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .trim_end_matches(" zerofill")
+            .trim_end_matches(" unsigned");
+        let args: Vec<usize> = t
+            .split_once('(')
+            .and_then(|(_, rest)| rest.split(')').next())
+            .map(|a| a.split(',').filter_map(|n| n.trim().parse().ok()).collect())
+            .unwrap_or_default();
+        let digits = match base {
+            "tinyint" | "int1" => 2,
+            "smallint" | "int2" | "smallserial" => 4,
+            "mediumint" => 6,
+            "int" | "integer" | "int4" | "serial" => 9,
+            "bigint" | "int8" | "bigserial" => 18,
+            "decimal" | "numeric" | "dec" => match args[..] {
+                [p] => p,
+                [p, s] => p.saturating_sub(s),
+                _ => 18,
+            }
+            .clamp(1, 18),
+            "float" | "double" | "double precision" | "real" | "float4" | "float8" => 15,
+            _ => {
+                let text = [
+                    "char",
+                    "varchar",
+                    "character",
+                    "character varying",
+                    "nchar",
+                    "nvarchar",
+                    "bpchar",
+                    "varbinary",
+                    "binary",
+                ];
+                return Target {
+                    numeric: false,
+                    digits: 0,
+                    max_len: args.first().copied().filter(|_| text.contains(&base)),
+                };
+            }
+        };
+        Target {
+            numeric: true,
+            digits,
+            max_len: None,
+        }
+    }
+
+    /// Cut text output to the column length
+    fn fit(&self, text: String) -> String {
+        match self.max_len {
+            Some(n) if text.chars().count() > n => text.chars().take(n).collect(),
+            _ => text,
+        }
+    }
+}
+
+impl ColumnMasker {
+    /// Mask the values of a row in place, `columns` naming each value
+    pub fn mask_row(&self, columns: &[String], values: &mut [Value]) {
+        if self.rules.is_empty() {
+            return;
+        }
+        for (column, value) in columns.iter().zip(values.iter_mut()) {
+            if let Some(rule) = self
+                .rules
+                .iter()
+                .find(|r| r.column.eq_ignore_ascii_case(column))
+            {
+                let target = match self
+                    .types
+                    .iter()
+                    .find(|(c, _)| c.eq_ignore_ascii_case(column))
+                {
+                    Some((_, type_name)) => Target::from_type(type_name),
+                    // Type unknown: keep numbers numeric
+                    None => Target {
+                        numeric: value.is_number(),
+                        digits: 18,
+                        max_len: None,
+                    },
+                };
+                *value = self.mask(rule, target, std::mem::take(value));
+            }
+        }
+    }
+
+    /// Keyed digest of a value. The same input gives the same output in every table,
+    /// so masked keys still join.
+    fn digest(&self, text: &str) -> [u8; 32] {
+        let Ok(mut mac) = HmacSha256::new_from_slice(&self.key) else {
+            // HMAC takes keys of any length
+            return [0; 32];
+        };
+        mac.update(text.as_bytes());
+        mac.finalize().into_bytes().into()
+    }
+
+    fn rng(&self, text: &str) -> Rng {
+        let digest = self.digest(text);
+        let mut seed = [0u8; 8];
+        seed.copy_from_slice(&digest[..8]);
+        Rng(u64::from_le_bytes(seed))
+    }
+
+    fn mask(&self, rule: &MaskRule, target: Target, value: Value) -> Value {
+        let text = match &value {
+            Value::Null => return Value::Null,
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        match rule.method.as_str() {
+            "null" => Value::Null,
+            "hash" | "fake" if target.numeric => {
+                number_like(&text, target.digits, &mut self.rng(&text))
+            }
+            "hash" => Value::String(target.fit(hex::encode(self.digest(&text)))),
+            "fake" => Value::String(target.fit(self.fake(rule, &text))),
+            "partial" => Value::String(partial(rule, &text)),
+            "shuffle" => {
+                let shuffled = shuffle(&text, &mut self.rng(&text));
+                match shuffled.parse::<serde_json::Number>() {
+                    Ok(n) if target.numeric => Value::Number(n),
+                    _ => Value::String(shuffled),
+                }
+            }
+            _ => value,
+        }
+    }
+
+    fn fake(&self, rule: &MaskRule, text: &str) -> String {
+        let mut rng = self.rng(text);
+        let kind = rule
+            .fake
+            .clone()
+            .unwrap_or_else(|| fake_kind(&rule.column).to_string());
+        match kind.as_str() {
+            "email" => format!(
+                "{}.{}{}@example.com",
+                rng.pick(FIRST_NAMES).to_lowercase(),
+                rng.pick(LAST_NAMES).to_lowercase(),
+                rng.range(1, 9999)
+            ),
+            "name" => format!("{} {}", rng.pick(FIRST_NAMES), rng.pick(LAST_NAMES)),
+            "phone" => format!(
+                "1{}{:09}",
+                rng.pick(&["3", "5", "8"]),
+                rng.range(0, 999_999_999)
+            ),
+            "id_card" => fake_id_card(&mut rng),
+            _ => {
+                let count = text.split_whitespace().count().clamp(1, 12) as i64;
+                let words: Vec<&str> = (0..count).map(|_| rng.pick(WORDS)).collect();
+                words.join(" ")
+            }
+        }
+    }
+}
+
+/// Fake value kind for a column name when the rule doesn't name one
+fn fake_kind(column: &str) -> &'static str {
+    let n = column.to_lowercase();
+    if n.contains("mail") {
+        "email"
+    } else if n.contains("phone") || n.contains("mobile") || n.contains("tel") {
+        "phone"
+    } else if n.contains("id_card") || n.contains("idcard") || n.contains("identity") {
+        "id_card"
+    } else if n.contains("name") {
+        "name"
+    } else {
+        "text"
+    }
+}
+
+/// A number with as many digits as `text` (at most `max_digits`), never starting with 0
+fn number_like(text: &str, max_digits: usize, rng: &mut Rng) -> Value {
+    let digits = text
+        .chars()
+        .filter(|c| c.is_ascii_digit())
+        .count()
+        .clamp(1, max_digits.clamp(1, 18));
+    let mut out = String::new();
+    for i in 0..digits {
+        let low = if i == 0 && digits > 1 { 1 } else { 0 };
+        out.push(char::from(b'0' + rng.range(low, 9) as u8));
+    }
+    match out.parse::<serde_json::Number>() {
+        Ok(n) => Value::Number(n),
+        Err(_) => Value::String(out),
+    }
+}
+
+/// Keep the first and last characters and mask the rest. Emails keep the domain.
+fn partial(rule: &MaskRule, text: &str) -> String {
+    let mask = rule
+        .mask_char
+        .as_deref()
+        .and_then(|m| m.chars().next())
+        .unwrap_or('*');
+    if let Some((local, domain)) = text.split_once('@') {
+        let first: String = local.chars().take(1).collect();
+        let hidden = local.chars().count().saturating_sub(1).max(3);
+        return format!("{}{}@{}", first, mask.to_string().repeat(hidden), domain);
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let mut start = rule.keep_start.unwrap_or(3);
+    let mut end = rule.keep_end.unwrap_or(4);
+    // Always hide at least half of the value
+    if start + end > chars.len() / 2 {
+        start = start.min(chars.len() / 4);
+        end = end.min(chars.len() / 4);
+    }
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            if i < start || i >= chars.len() - end {
+                *c
+            } else {
+                mask
+            }
+        })
+        .collect()
+}
+
+/// Format-preserving scramble: digits stay digits, letters keep their case and
+/// everything else (separators, spaces) stays in place
+fn shuffle(text: &str, rng: &mut Rng) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_ascii_digit() {
+                char::from(b'0' + rng.range(0, 9) as u8)
+            } else if c.is_ascii_lowercase() {
+                char::from(b'a' + rng.range(0, 25) as u8)
+            } else if c.is_ascii_uppercase() {
+                char::from(b'A' + rng.range(0, 25) as u8)
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// 18-digit resident ID number with a plausible birth date and a valid check digit
+fn fake_id_card(rng: &mut Rng) -> String {
+    let region = rng.range(110_000, 659_999);
+    let birth = chrono::NaiveDate::from_ymd_opt(1950, 1, 1).unwrap_or_default()
+        + chrono::Duration::days(rng.range(0, 365 * 55));
+    let body = format!(
+        "{}{}{:03}",
+        region,
+        birth.format("%Y%m%d"),
+        rng.range(0, 999)
+    );
+    let sum: u32 = body
+        .bytes()
+        .zip(ID_CARD_WEIGHTS)
+        .map(|(b, w)| (b - b'0') as u32 * w)
+        .sum();
+    format!("{}{}", body, ID_CARD_CHECK[(sum % 11) as usize] as char)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Datelike;
+
+    fn rule(method: &str) -> MaskRule {
+        MaskRule {
+            column: "c".to_string(),
+            method: method.to_string(),
+            fake: None,
+            keep_start: None,
+            keep_end: None,
+            mask_char: None,
+        }
+    }
+
+    fn keep(start: usize, end: usize) -> MaskRule {
+        MaskRule {
+            keep_start: Some(start),
+            keep_end: Some(end),
+            ..rule("partial")
+        }
+    }
+
+    /// GB 11643 check character of an 18-character ID number
+    fn id_card_valid(id: &str) -> bool {
+        let bytes = id.as_bytes();
+        if bytes.len() != 18 || !bytes[..17].iter().all(u8::is_ascii_digit) {
+            return false;
+        }
+        let sum: u32 = bytes[..17]
+            .iter()
+            .zip(ID_CARD_WEIGHTS)
+            .map(|(b, w)| (b - b'0') as u32 * w)
+            .sum();
+        ID_CARD_CHECK[(sum % 11) as usize] == bytes[17]
+    }
+
+    #[test]
+    fn partial_masks() {
+        let cases = [
+            (rule("partial"), "alice@example.com", "a****@example.com"),
+            (rule("partial"), "bo@x.com", "b***@x.com"),
+            // Defaults would leave too much of a short value, so both ends shrink
+            (rule("partial"), "13812345678", "13*******78"),
+            (rule("partial"), "abc", "***"),
+            (rule("partial"), "", ""),
+            (keep(4, 4), "6222020200112233445", "6222***********3445"),
+            (keep(6, 0), "北京市朝阳区建国路88号", "北京市朝阳区******"),
+            (
+                MaskRule {
+                    mask_char: Some("#".to_string()),
+                    ..keep(1, 1)
+                },
+                "abcdefghij",
+                "a########j",
+            ),
+        ];
+        for (rule, text, expected) in cases {
+            assert_eq!(partial(&rule, text), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn shuffle_keeps_the_format() {
+        let text = "AB-12 cd/é9";
+        let shuffled = shuffle(text, &mut Rng(42));
+        assert_eq!(shuffled.chars().count(), text.chars().count());
+        for (a, b) in text.chars().zip(shuffled.chars()) {
+            match a {
+                '0'..='9' => assert!(b.is_ascii_digit()),
+                'a'..='z' => assert!(b.is_ascii_lowercase()),
+                'A'..='Z' => assert!(b.is_ascii_uppercase()),
+                _ => assert_eq!(a, b),
+            }
+        }
+        assert_eq!(shuffled, shuffle(text, &mut Rng(42)));
+        assert_ne!(shuffled, shuffle(text, &mut Rng(43)));
+    }
+
+    #[test]
+    fn fake_id_cards_are_valid() {
+        assert!(id_card_valid("11010519491231002X"));
+        assert!(!id_card_valid("110105194912310021"));
+        for seed in 0..200 {
+            let id = fake_id_card(&mut Rng(seed));
+            assert!(id_card_valid(&id), "{}", id);
+            let birth = chrono::NaiveDate::parse_from_str(&id[6..14], "%Y%m%d").unwrap();
+            assert!((1950..=2005).contains(&birth.year()), "{}", id);
+        }
+    }
+
+    #[test]
+    fn targets_follow_the_column_type() {
+        let cases = [
+            ("int", true, 9, None),
+            ("int unsigned", true, 9, None),
+            ("bigint(20) unsigned zerofill", true, 18, None),
+            ("smallint", true, 4, None),
+            ("numeric(10,2)", true, 8, None),
+            ("decimal(5)", true, 5, None),
+            ("double precision", true, 15, None),
+            ("varchar(20)", false, 0, Some(20)),
+            ("character varying(8)", false, 0, Some(8)),
+            ("char(2)", false, 0, Some(2)),
+            ("text", false, 0, None),
+            ("timestamp(3)", false, 0, None),
+        ];
+        for (type_name, numeric, digits, max_len) in cases {
+            assert_eq!(
+                Target::from_type(type_name),
+                Target {
+                    numeric,
+                    digits,
+                    max_len
+                },
+                "{}",
+                type_name
+            );
+        }
+    }
+
+    #[test]
+    fn masked_values_fit_their_columns() {
+        let masker = ColumnMasker {
+            key: b"secret".to_vec(),
+            rules: vec![rule("hash")],
+            types: Vec::new(),
+        };
+        let short = Target::from_type("varchar(10)");
+        let hashed = masker.mask(&rule("hash"), short, Value::String("x".into()));
+        assert_eq!(hashed.as_str().map(str::len), Some(10));
+        // Same input, same output, so masked keys still join
+        assert_eq!(
+            hashed,
+            masker.mask(&rule("hash"), short, Value::String("x".into()))
+        );
+
+        // Text read from a numeric column still comes back as a number that fits
+        let int = Target::from_type("smallint");
+        let number = masker.mask(&rule("hash"), int, Value::String("123456".into()));
+        assert!(number.as_u64().is_some_and(|n| n < 10_000), "{}", number);
+        let fake = masker.mask(&rule("fake"), int, Value::String("42".into()));
+        assert!(fake.as_u64().is_some_and(|n| n < 100), "{}", fake);
+
+        let name = MaskRule {
+            fake: Some("email".to_string()),
+            ..rule("fake")
+        };
+        let email = masker.mask(&name, Target::from_type("varchar(12)"), "a@b.c".into());
+        assert!(email.as_str().is_some_and(|e| e.chars().count() <= 12));
+        assert_eq!(masker.mask(&rule("null"), int, 7.into()), Value::Null);
+    }
+
+    #[test]
+    fn query_columns_are_masked_by_their_source() {
+        let email = MaskRule {
+            column: "email".to_string(),
+            ..rule("null")
+        };
+        let masker = Masker {
+            key: b"secret".to_vec(),
+            tables: HashMap::from([("users".to_string(), vec![email])]),
+        };
+        let source = |t: &str, c: &str| Some((t.to_string(), c.to_string()));
+
+        // SELECT id, email AS e FROM users
+        let columns = masker
+            .for_query(
+                vec![
+                    ("id".to_string(), source("users", "id")),
+                    ("e".to_string(), source("users", "email")),
+                ],
+                Vec::new(),
+            )
+            .unwrap();
+        let mut values = vec![Value::from(1), Value::from("a@b.c")];
+        columns.mask_row(&["id".to_string(), "e".to_string()], &mut values);
+        assert_eq!(values, vec![Value::from(1), Value::Null]);
+
+        // SELECT lower(email) FROM users: the source is unknown, so nothing is exported
+        assert!(masker
+            .for_query(vec![("lower".to_string(), None)], Vec::new())
+            .is_err());
+        // Without rules there is nothing to leak
+        assert!(Masker::default()
+            .for_query(vec![("lower".to_string(), None)], Vec::new())
+            .is_ok());
+    }
+}
@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::ConnectionConfig;
+
+/// One JSON entry per line in the app config dir
+const FILE_NAME: &str = "query_history.jsonl";
+/// Once the file grows past this, the oldest unpinned entries are dropped
+const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+/// Unpinned entries kept when the file is trimmed
+const TRIM_KEEP: usize = 5000;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct QueryHistoryEntry {
+    pub id: String,
+    pub connection_id: String,
+    pub connection_name: String,
+    pub db_type: String,
+    pub database: Option<String>,
+    pub sql: String,
+    pub duration_ms: u64,
+    pub row_count: Option<u64>,
+    pub error: Option<String>,
+    pub executed_at: i64, // unix millis
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+impl QueryHistoryEntry {
+    pub fn new(
+        config: &ConnectionConfig,
+        sql: &str,
+        duration_ms: u64,
+        result: Result<u64, &str>,
+    ) -> Self {
+        QueryHistoryEntry {
+            id: uuid::Uuid::new_v4().to_string(),
+            connection_id: config.id.clone(),
+            connection_name: config.name.clone(),
+            db_type: config.db_type.clone(),
+            database: config.database.clone().filter(|d| !d.is_empty()),
+            sql: sql.to_string(),
+            duration_ms,
+            row_count: result.ok(),
+            error: result.err().map(String::from),
+            executed_at: chrono::Utc::now().timestamp_millis(),
+            pinned: false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct QueryHistoryFilter {
+    pub text: Option<String>, // Case-insensitive match on the SQL
+    pub connection_id: Option<String>,
+    pub pinned_only: Option<bool>,
+    pub limit: Option<usize>, // default 200
+}
+
+/// Serializes access to the history file
+#[derive(Default)]
+pub struct QueryHistory {
+    lock: Mutex<()>,
+}
+
+fn history_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let config_dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    fs::create_dir_all(&config_dir).map_err(|e| e.to_string())?;
+    Ok(config_dir.join(FILE_NAME))
+}
+
+/// All entries, oldest first. Unreadable lines are skipped.
+fn read_entries(path: &PathBuf) -> Result<Vec<QueryHistoryEntry>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+fn write_entries(path: &PathBuf, entries: &[QueryHistoryEntry]) -> Result<(), String> {
+    let mut content = String::new();
+    for entry in entries {
+        content.push_str(&serde_json::to_string(entry).map_err(|e| e.to_string())?);
+        content.push('\n');
+    }
+    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+impl QueryHistory {
+    /// Append an entry, trimming old unpinned entries when the file gets large
+    pub fn record(&self, app: &tauri::AppHandle, entry: &QueryHistoryEntry) -> Result<(), String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let path = history_path(app)?;
+        let line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| e.to_string())?;
+        writeln!(file, "{}", line).map_err(|e| e.to_string())?;
+
+        if file.metadata().map(|m| m.len()).unwrap_or(0) > MAX_FILE_BYTES {
+            let entries = read_entries(&path)?;
+            let unpinned = entries.iter().filter(|e| !e.pinned).count();
+            let mut drop = unpinned.saturating_sub(TRIM_KEEP);
+            let kept: Vec<QueryHistoryEntry> = entries
+                .into_iter()
+                .filter(|e| {
+                    if !e.pinned && drop > 0 {
+                        drop -= 1;
+                        false
+                    } else {
+                        true
+                    }
+                })
+                .collect();
+            write_entries(&path, &kept)?;
+        }
+        Ok(())
+    }
+
+    /// Matching entries, newest first
+    pub fn search(
+        &self,
+        app: &tauri::AppHandle,
+        filter: &QueryHistoryFilter,
+    ) -> Result<Vec<QueryHistoryEntry>, String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let text = filter
+            .text
+            .as_deref()
+            .map(|t| t.trim().to_lowercase())
+            .filter(|t| !t.is_empty());
+        let pinned_only = filter.pinned_only.unwrap_or(false);
+
+        let mut entries: Vec<QueryHistoryEntry> = read_entries(&history_path(app)?)?
+            .into_iter()
+            .rev()
+            .filter(|e| !pinned_only || e.pinned)
+            .filter(|e| {
+                filter
+                    .connection_id
+                    .as_deref()
+                    .is_none_or(|id| id.is_empty() || e.connection_id == id)
+            })
+            .filter(|e| {
+                text.as_deref()
+                    .is_none_or(|t| e.sql.to_lowercase().contains(t))
+            })
+            .collect();
+        entries.truncate(filter.limit.unwrap_or(200));
+        Ok(entries)
+    }
+
+    pub fn set_pinned(&self, app: &tauri::AppHandle, id: &str, pinned: bool) -> Result<(), String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let path = history_path(app)?;
+        let mut entries = read_entries(&path)?;
+        let entry = entries
+            .iter_mut()
+            .find(|e| e.id == id)
+            .ok_or("历史记录不存在")?;
+        entry.pinned = pinned;
+        write_entries(&path, &entries)
+    }
+
+    /// Delete entries older than `older_than_days` (all when None). Pinned entries
+    /// are kept unless `include_pinned`. Returns the number removed.
+    pub fn purge(
+        &self,
+        app: &tauri::AppHandle,
+        older_than_days: Option<u32>,
+        include_pinned: bool,
+    ) -> Result<u64, String> {
+        let _guard = self.lock.lock().map_err(|e| e.to_string())?;
+        let path = history_path(app)?;
+        let entries = read_entries(&path)?;
+        let cutoff = older_than_days.map(|days| {
+            chrono::Utc::now().timestamp_millis() - i64::from(days) * 24 * 60 * 60 * 1000
+        });
+
+        let before = entries.len();
+        let kept: Vec<QueryHistoryEntry> = entries
+            .into_iter()
+            .filter(|e| (e.pinned && !include_pinned) || cutoff.is_some_and(|c| e.executed_at >= c))
+            .collect();
+        let removed = (before - kept.len()) as u64;
+        write_entries(&path, &kept)?;
+        Ok(removed)
+    }
+}
@@ -0,0 +1,417 @@
+use base64::Engine;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use crate::redis_conn::{self, RedisConnection};
+use crate::redis_value;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RedisInfoSection {
+    pub name: String,
+    pub items: Vec<(String, String)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RedisKeyspaceStat {
+    pub db: String,
+    pub keys: i64,
+    pub expires: i64,
+    pub avg_ttl: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RedisNodeInfo {
+    pub node: String, // "host:port" in cluster mode, empty otherwise
+    pub sections: Vec<RedisInfoSection>,
+    pub keyspace: Vec<RedisKeyspaceStat>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RedisSlowlogEntry {
+    pub node: String,
+    pub id: i64,
+    pub timestamp: i64,   // unix seconds
+    pub duration_us: i64, // microseconds
+    pub command: Vec<String>,
+    pub client_addr: Option<String>,
+    pub client_name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RedisClientInfo {
+    pub node: String,
+    pub id: i64,
+    pub addr: String,
+    pub name: String,
+    pub user: Option<String>,
+    pub db: i64,
+    pub age: i64,  // seconds
+    pub idle: i64, // seconds
+    pub cmd: String,
+    pub flags: String,
+    pub fields: HashMap<String, String>, // Every field reported by CLIENT LIST
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RedisMemoryStats {
+    pub node: String,
+    pub stats: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RedisBigKey {
+    pub key: String,
+    pub key_encoding: Option<String>, // "base64" when the key name isn't UTF-8
+    pub key_type: String,
+    pub memory_bytes: i64,
+    pub length: Option<i64>, // Elements for collections, bytes for strings
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RedisBigKeyReport {
+    pub scanned: usize,
+    pub by_type: HashMap<String, Vec<RedisBigKey>>, // Largest keys per type, biggest first
+}
+
+fn node_label(node: &Option<(String, u16)>) -> String {
+    node.as_ref()
+        .map(|(host, port)| format!("{}:{}", host, port))
+        .unwrap_or_default()
+}
+
+/// Split `INFO` output into sections, pulling the keyspace lines into typed stats
+pub fn parse_info(text: &str) -> (Vec<RedisInfoSection>, Vec<RedisKeyspaceStat>) {
+    let mut sections: Vec<RedisInfoSection> = Vec::new();
+    let mut keyspace = Vec::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix("# ") {
+            sections.push(RedisInfoSection {
+                name: name.to_string(),
+                items: Vec::new(),
+            });
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        // "db0:keys=1,expires=0,avg_ttl=0"
+        if key.starts_with("db") && value.contains("keys=") {
+            let fields: HashMap<&str, i64> = value
+                .split(',')
+                .filter_map(|kv| kv.split_once('='))
+                .map(|(k, v)| (k, v.parse().unwrap_or(0)))
+                .collect();
+            keyspace.push(RedisKeyspaceStat {
+                db: key.to_string(),
+                keys: fields.get("keys").copied().unwrap_or(0),
+                expires: fields.get("expires").copied().unwrap_or(0),
+                avg_ttl: fields.get("avg_ttl").copied().unwrap_or(0),
+            });
+        }
+        if sections.is_empty() {
+            sections.push(RedisInfoSection {
+                name: String::new(),
+                items: Vec::new(),
+            });
+        }
+        if let Some(section) = sections.last_mut() {
+            section.items.push((key.to_string(), value.to_string()));
+        }
+    }
+    (sections, keyspace)
+}
+
+/// `INFO` for every node (every master in cluster mode)
+pub async fn info(
+    con: &mut RedisConnection,
+    section: Option<&str>,
+) -> Result<Vec<RedisNodeInfo>, String> {
+    let mut result = Vec::new();
+    for node in redis_conn::scan_nodes(con).await? {
+        let mut cmd = redis::cmd("INFO");
+        if let Some(section) = section.filter(|s| !s.is_empty()) {
+            cmd.arg(section);
+        }
+        let text: String = con.query_node(&cmd, node.as_ref()).await?;
+        let (sections, keyspace) = parse_info(&text);
+        result.push(RedisNodeInfo {
+            node: node_label(&node),
+            sections,
+            keyspace,
+        });
+    }
+    Ok(result)
+}
+
+/// `SLOWLOG GET <count>` from every node, slowest first
+pub async fn slowlog(
+    con: &mut RedisConnection,
+    count: i64,
+) -> Result<Vec<RedisSlowlogEntry>, String> {
+    let mut entries = Vec::new();
+    for node in redis_conn::scan_nodes(con).await? {
+        let mut cmd = redis::cmd("SLOWLOG");
+        cmd.arg("GET").arg(count);
+        let raw: Vec<redis::Value> = con.query_node(&cmd, node.as_ref()).await?;
+        for item in raw {
+            let redis::Value::Array(fields) = item else {
+                continue;
+            };
+            let int_at = |i: usize| -> i64 {
+                fields
+                    .get(i)
+                    .and_then(|v| redis::from_redis_value(v).ok())
+                    .unwrap_or(0)
+            };
+            let str_at = |i: usize| -> Option<String> {
+                fields.get(i).and_then(|v| redis::from_redis_value(v).ok())
+            };
+            let command = match fields.get(3) {
+                Some(redis::Value::Array(args)) => args
+                    .iter()
+                    .map(|a| match a {
+                        redis::Value::BulkString(b) => redis_value::display_text(b),
+                        other => redis::from_redis_value(other).unwrap_or_default(),
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            entries.push(RedisSlowlogEntry {
+                node: node_label(&node),
+                id: int_at(0),
+                timestamp: int_at(1),
+                duration_us: int_at(2),
+                command,
+                client_addr: str_at(4),
+                client_name: str_at(5),
+            });
+        }
+    }
+    entries.sort_by_key(|e| std::cmp::Reverse(e.duration_us));
+    Ok(entries)
+}
+
+/// Parse one `CLIENT LIST` line: "id=3 addr=127.0.0.1:52555 name= age=5 ..."
+fn parse_client_line(line: &str) -> HashMap<String, String> {
+    line.split_whitespace()
+        .filter_map(|kv| kv.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+/// `CLIENT LIST` from every node
+pub async fn clients(con: &mut RedisConnection) -> Result<Vec<RedisClientInfo>, String> {
+    let mut result = Vec::new();
+    for node in redis_conn::scan_nodes(con).await? {
+        let mut cmd = redis::cmd("CLIENT");
+        cmd.arg("LIST");
+        let text: String = con.query_node(&cmd, node.as_ref()).await?;
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            let fields = parse_client_line(line);
+            let get = |k: &str| fields.get(k).cloned().unwrap_or_default();
+            let num = |k: &str| fields.get(k).and_then(|v| v.parse().ok()).unwrap_or(0);
+            result.push(RedisClientInfo {
+                node: node_label(&node),
+                id: num("id"),
+                addr: get("addr"),
+                name: get("name"),
+                user: fields.get("user").cloned(),
+                db: num("db"),
+                age: num("age"),
+                idle: num("idle"),
+                cmd: get("cmd"),
+                flags: get("flags"),
+                fields,
+            });
+        }
+    }
+    Ok(result)
+}
+
+/// `CLIENT KILL ID <id>` on the node the client is connected to
+pub async fn kill_client(
+    con: &mut RedisConnection,
+    node: Option<&str>,
+    client_id: i64,
+) -> Result<(), String> {
+    let node = node
+        .filter(|n| !n.is_empty())
+        .and_then(|n| n.rsplit_once(':'))
+        .and_then(|(host, port)| Some((host.to_string(), port.parse().ok()?)));
+    let mut cmd = redis::cmd("CLIENT");
+    cmd.arg("KILL").arg("ID").arg(client_id);
+    let killed: i64 = con.query_node(&cmd, node.as_ref()).await?;
+    if killed == 0 {
+        return Err(format!("Client {} not found", client_id));
+    }
+    Ok(())
+}
+
+/// Convert an arbitrary reply (e.g. the nested `MEMORY STATS` array) into JSON.
+/// Flat key/value arrays become objects.
+fn reply_to_json(value: &redis::Value) -> Value {
+    match value {
+        redis::Value::Nil => Value::Null,
+        redis::Value::Int(i) => json!(i),
+        redis::Value::Double(d) => json!(d),
+        redis::Value::Boolean(b) => json!(b),
+        redis::Value::Okay => json!("OK"),
+        redis::Value::SimpleString(s) => json!(s),
+        redis::Value::BulkString(b) => {
+            let text = redis_value::display_text(b);
+            // Numeric strings such as "1.5" come back as bulk strings
+            match text.parse::<f64>() {
+                Ok(n) if n.is_finite() => json!(n),
+                _ => json!(text),
+            }
+        }
+        redis::Value::Array(items) | redis::Value::Set(items) => {
+            let is_pairs = items.len() % 2 == 0
+                && items.iter().step_by(2).all(|k| {
+                    matches!(
+                        k,
+                        redis::Value::BulkString(_) | redis::Value::SimpleString(_)
+                    )
+                });
+            if is_pairs && !items.is_empty() {
+                let mut map = serde_json::Map::new();
+                for pair in items.chunks(2) {
+                    let key: String = redis::from_redis_value(&pair[0]).unwrap_or_default();
+                    map.insert(key, reply_to_json(&pair[1]));
+                }
+                Value::Object(map)
+            } else {
+                Value::Array(items.iter().map(reply_to_json).collect())
+            }
+        }
+        redis::Value::Map(pairs) => {
+            let mut map = serde_json::Map::new();
+            for (k, v) in pairs {
+                let key: String = redis::from_redis_value(k).unwrap_or_default();
+                map.insert(key, reply_to_json(v));
+            }
+            Value::Object(map)
+        }
+        other => json!(format!("{:?}", other)),
+    }
+}
+
+/// `MEMORY STATS` from every node
+pub async fn memory_stats(con: &mut RedisConnection) -> Result<Vec<RedisMemoryStats>, String> {
+    let mut result = Vec::new();
+    for node in redis_conn::scan_nodes(con).await? {
+        let mut cmd = redis::cmd("MEMORY");
+        cmd.arg("STATS");
+        let raw: redis::Value = con.query_node(&cmd, node.as_ref()).await?;
+        result.push(RedisMemoryStats {
+            node: node_label(&node),
+            stats: reply_to_json(&raw),
+        });
+    }
+    Ok(result)
+}
+
+/// Length command for each key type, strings report their byte length
+fn length_command(key_type: &str) -> Option<&'static str> {
+    match key_type {
+        "string" => Some("STRLEN"),
+        "list" => Some("LLEN"),
+        "set" => Some("SCARD"),
+        "zset" => Some("ZCARD"),
+        "hash" => Some("HLEN"),
+        "stream" => Some("XLEN"),
+        _ => None,
+    }
+}
+
+async fn measure_key(mut con: RedisConnection, key: Vec<u8>) -> Option<RedisBigKey> {
+    let key_type: String = redis::cmd("TYPE")
+        .arg(&key)
+        .query_async(&mut con)
+        .await
+        .ok()?;
+    // Key may have expired between SCAN and TYPE
+    if key_type == "none" {
+        return None;
+    }
+    let memory_bytes: Option<i64> = redis::cmd("MEMORY")
+        .arg("USAGE")
+        .arg(&key)
+        .query_async(&mut con)
+        .await
+        .ok()?;
+    let length = match length_command(&key_type) {
+        Some(cmd) => redis::cmd(cmd).arg(&key).query_async(&mut con).await.ok(),
+        None => None,
+    };
+    let (key, key_encoding) = match String::from_utf8(key) {
+        Ok(key) => (key, None),
+        Err(e) => (
+            base64::engine::general_purpose::STANDARD.encode(e.into_bytes()),
+            Some("base64".to_string()),
+        ),
+    };
+    Some(RedisBigKey {
+        key,
+        key_encoding,
+        key_type,
+        memory_bytes: memory_bytes.unwrap_or(0),
+        length,
+    })
+}
+
+/// Sample up to `sample_limit` keys with SCAN and measure them with `MEMORY USAGE`.
+/// Requests for a batch are issued concurrently over the multiplexed connection,
+/// so they are pipelined instead of paying one round trip per key.
+pub async fn big_keys(
+    con: &mut RedisConnection,
+    sample_limit: usize,
+    top_n: usize,
+) -> Result<RedisBigKeyReport, String> {
+    let mut measured: Vec<RedisBigKey> = Vec::new();
+    let mut scanned = 0;
+
+    'nodes: for node in redis_conn::scan_nodes(con).await? {
+        let mut cursor: u64 = 0;
+        loop {
+            let mut cmd = redis::cmd("SCAN");
+            cmd.arg(cursor).arg("COUNT").arg(500);
+            // Raw bytes, so a key that isn't UTF-8 doesn't abort the whole scan
+            let (next, batch): (u64, Vec<Vec<u8>>) = con.query_node(&cmd, node.as_ref()).await?;
+
+            let take = batch.len().min(sample_limit - scanned);
+            scanned += take;
+            let futures = batch
+                .into_iter()
+                .take(take)
+                .map(|key| measure_key(con.clone(), key));
+            measured.extend(join_all(futures).await.into_iter().flatten());
+
+            cursor = next;
+            if scanned >= sample_limit {
+                break 'nodes;
+            }
+            if cursor == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut by_type: HashMap<String, Vec<RedisBigKey>> = HashMap::new();
+    for key in measured {
+        by_type.entry(key.key_type.clone()).or_default().push(key);
+    }
+    for keys in by_type.values_mut() {
+        keys.sort_by_key(|k| std::cmp::Reverse(k.memory_bytes));
+        keys.truncate(top_n);
+    }
+
+    Ok(RedisBigKeyReport { scanned, by_type })
+}
@@ -0,0 +1,309 @@
+use redis::aio::{ConnectionLike, MultiplexedConnection};
+use redis::cluster_async::ClusterConnection;
+use redis::cluster_routing::{RoutingInfo, SingleNodeRoutingInfo};
+use redis::{
+    Cmd, FromRedisValue, IntoConnectionInfo, Pipeline, ProtocolVersion, RedisConnectionInfo,
+    RedisFuture, Value,
+};
+
+use crate::ConnectionConfig;
+
+/// Name shown for our connections in `CLIENT LIST`
+const CLIENT_NAME: &str = "recch";
+
+/// A connection to a standalone server, a sentinel-managed primary or a whole cluster.
+/// Implements `ConnectionLike`, so `query_async` works the same for every mode.
+#[derive(Clone)]
+pub enum RedisConnection {
+    Single(MultiplexedConnection),
+    Cluster(ClusterConnection),
+}
+
+impl ConnectionLike for RedisConnection {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        match self {
+            RedisConnection::Single(con) => con.req_packed_command(cmd),
+            RedisConnection::Cluster(con) => con.req_packed_command(cmd),
+        }
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        match self {
+            RedisConnection::Single(con) => con.req_packed_commands(cmd, offset, count),
+            RedisConnection::Cluster(con) => con.req_packed_commands(cmd, offset, count),
+        }
+    }
+
+    fn get_db(&self) -> i64 {
+        match self {
+            RedisConnection::Single(con) => con.get_db(),
+            RedisConnection::Cluster(con) => con.get_db(),
+        }
+    }
+}
+
+impl RedisConnection {
+    pub fn is_cluster(&self) -> bool {
+        matches!(self, RedisConnection::Cluster(_))
+    }
+
+    /// Run a command on one specific node ("host", port). Without a node, or on a
+    /// standalone connection, the command goes through the normal routing.
+    pub async fn query_node<T: FromRedisValue>(
+        &mut self,
+        cmd: &Cmd,
+        node: Option<&(String, u16)>,
+    ) -> Result<T, String> {
+        match (self, node) {
+            (RedisConnection::Cluster(con), Some((host, port))) => {
+                let routing = RoutingInfo::SingleNode(SingleNodeRoutingInfo::ByAddress {
+                    host: host.clone(),
+                    port: *port,
+                });
+                let value = con
+                    .route_command(cmd, routing)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                redis::from_redis_value(&value).map_err(|e| e.to_string())
+            }
+            (con, _) => cmd.query_async(con).await.map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// "standalone" (default), "cluster" or "sentinel"
+pub fn redis_mode(config: &ConnectionConfig) -> &str {
+    match config.redis_mode.as_deref() {
+        Some(mode) if !mode.is_empty() => mode,
+        _ => "standalone",
+    }
+}
+
+/// Seed nodes (cluster) or sentinel addresses as "host:port", falling back to host/port
+fn node_addresses(config: &ConnectionConfig) -> Vec<String> {
+    let nodes: Vec<String> = config
+        .redis_nodes
+        .iter()
+        .flatten()
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .collect();
+    if nodes.is_empty() {
+        vec![format!("{}:{}", config.host, config.port)]
+    } else {
+        nodes
+    }
+}
+
+/// Parse a database selector such as "db0 (15)", "db0", "0" or "" into its index
+pub fn parse_db_index(db: &str) -> i64 {
+    let db_part = db.split_whitespace().next().unwrap_or("");
+    if db_part.is_empty() {
+        0
+    } else if let Some(num_str) = db_part.strip_prefix("db") {
+        num_str.parse().unwrap_or(0)
+    } else {
+        db_part.parse().unwrap_or(0)
+    }
+}
+
+/// Handshake settings shared by every mode: ACL user + password, database and protocol
+fn connection_info(config: &ConnectionConfig, db: i64) -> RedisConnectionInfo {
+    RedisConnectionInfo {
+        db,
+        username: config.username.clone().filter(|u| !u.is_empty()),
+        password: config.password.clone().filter(|p| !p.is_empty()),
+        protocol: if config.redis_resp3.unwrap_or(false) {
+            ProtocolVersion::RESP3
+        } else {
+            ProtocolVersion::RESP2
+        },
+    }
+}
+
+/// Open a connection for the configured mode. Every Redis command goes through here so
+/// auth (`AUTH user pass`), database selection, protocol and client name behave the same.
+/// Cluster mode only has db 0, so `db` is ignored there.
+pub async fn connect(config: &ConnectionConfig, db: i64) -> Result<RedisConnection, String> {
+    let mut con = open(config, connection_info(config, db)).await?;
+
+    // Servers or proxies that don't allow CLIENT SETNAME are still usable
+    let _: Result<(), _> = redis::cmd("CLIENT")
+        .arg("SETNAME")
+        .arg(CLIENT_NAME)
+        .query_async(&mut con)
+        .await;
+
+    Ok(con)
+}
+
+/// Dedicated Pub/Sub connection. Channels are cluster-wide, so in cluster mode
+/// subscribing through a single node receives every message.
+pub async fn pubsub(config: &ConnectionConfig) -> Result<redis::aio::PubSub, String> {
+    node_client(config, connection_info(config, 0))
+        .await?
+        .get_async_pubsub()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Dedicated MONITOR connection. In cluster mode this watches the first seed node only.
+pub async fn monitor(config: &ConnectionConfig) -> Result<redis::aio::Monitor, String> {
+    node_client(config, connection_info(config, 0))
+        .await?
+        .get_async_monitor()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Client for a single server: the standalone host, the sentinel's current
+/// master, or the first seed node of a cluster
+async fn node_client(
+    config: &ConnectionConfig,
+    info: RedisConnectionInfo,
+) -> Result<redis::Client, String> {
+    match redis_mode(config) {
+        "sentinel" => {
+            let master = config
+                .sentinel_master
+                .as_deref()
+                .filter(|m| !m.is_empty())
+                .ok_or("Sentinel 模式需要填写 master 名称")?;
+            let sentinels: Vec<String> = node_addresses(config)
+                .iter()
+                .map(|n| format!("redis://{}/", n))
+                .collect();
+            let mut sentinel =
+                redis::sentinel::Sentinel::build(sentinels).map_err(|e| e.to_string())?;
+            let node_info = redis::sentinel::SentinelNodeConnectionInfo {
+                tls_mode: None,
+                redis_connection_info: Some(info),
+            };
+            sentinel
+                .async_master_for(master, Some(&node_info))
+                .await
+                .map_err(|e| e.to_string())
+        }
+        "cluster" => {
+            let seed = node_addresses(config).remove(0);
+            let mut conn_info = format!("redis://{}/", seed)
+                .into_connection_info()
+                .map_err(|e| e.to_string())?;
+            conn_info.redis = RedisConnectionInfo { db: 0, ..info };
+            redis::Client::open(conn_info).map_err(|e| e.to_string())
+        }
+        "standalone" => {
+            let conn_info = redis::ConnectionInfo {
+                addr: redis::ConnectionAddr::Tcp(config.host.clone(), config.port),
+                redis: info,
+            };
+            redis::Client::open(conn_info).map_err(|e| e.to_string())
+        }
+        other => Err(format!("Unsupported Redis mode: {}", other)),
+    }
+}
+
+async fn open(
+    config: &ConnectionConfig,
+    info: RedisConnectionInfo,
+) -> Result<RedisConnection, String> {
+    if redis_mode(config) != "cluster" {
+        let con = node_client(config, info)
+            .await?
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok(RedisConnection::Single(con));
+    }
+
+    let nodes: Vec<String> = node_addresses(config)
+        .iter()
+        .map(|n| format!("redis://{}/", n))
+        .collect();
+    let mut builder = redis::cluster::ClusterClientBuilder::new(nodes).use_protocol(info.protocol);
+    if let Some(user) = info.username {
+        builder = builder.username(user);
+    }
+    if let Some(pass) = info.password {
+        builder = builder.password(pass);
+    }
+    let client = builder.build().map_err(|e| e.to_string())?;
+    let con = client
+        .get_async_connection()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(RedisConnection::Cluster(con))
+}
+
+/// Addresses of the healthy masters in a cluster, parsed from `CLUSTER NODES`
+pub async fn cluster_masters(con: &mut RedisConnection) -> Result<Vec<(String, u16)>, String> {
+    let nodes: String = redis::cmd("CLUSTER")
+        .arg("NODES")
+        .query_async(con)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut masters = Vec::new();
+    for line in nodes.lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 3 {
+            continue;
+        }
+        let flags = parts[2];
+        if !flags.split(',').any(|f| f == "master")
+            || flags.contains("fail")
+            || flags.contains("noaddr")
+        {
+            continue;
+        }
+        // Address looks like "ip:port@cport[,hostname]"
+        let addr = parts[1].split('@').next().unwrap_or("");
+        if let Some((host, port)) = addr.rsplit_once(':') {
+            if let Ok(port) = port.parse() {
+                masters.push((host.to_string(), port));
+            }
+        }
+    }
+    Ok(masters)
+}
+
+/// Nodes a key scan has to visit: every master in cluster mode, or just the one server
+pub async fn scan_nodes(con: &mut RedisConnection) -> Result<Vec<Option<(String, u16)>>, String> {
+    if con.is_cluster() {
+        Ok(cluster_masters(con).await?.into_iter().map(Some).collect())
+    } else {
+        Ok(vec![None])
+    }
+}
+
+/// Collect all keys matching `pattern` using SCAN, fanning out across cluster masters.
+/// `K` is `Vec<u8>` where keys may not be UTF-8.
+pub async fn scan_keys<K: FromRedisValue>(
+    con: &mut RedisConnection,
+    pattern: &str,
+) -> Result<Vec<K>, String> {
+    let mut keys = Vec::new();
+    for node in scan_nodes(con).await? {
+        let mut cursor: u64 = 0;
+        loop {
+            let mut cmd = redis::cmd("SCAN");
+            cmd.arg(cursor)
+                .arg("MATCH")
+                .arg(pattern)
+                .arg("COUNT")
+                .arg(1000);
+            let (next, batch): (u64, Vec<K>) = con.query_node(&cmd, node.as_ref()).await?;
+            keys.extend(batch);
+            cursor = next;
+            if cursor == 0 {
+                break;
+            }
+        }
+    }
+    Ok(keys)
+}
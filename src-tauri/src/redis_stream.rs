@@ -0,0 +1,201 @@
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+
+use crate::redis_conn;
+use crate::redis_value;
+use crate::ConnectionConfig;
+
+/// Event carrying batches of Pub/Sub or MONITOR messages to the frontend
+pub const STREAM_EVENT: &str = "redis-stream";
+
+const FLUSH_INTERVAL_MS: u64 = 100;
+const DEFAULT_MAX_PER_SECOND: usize = 200;
+
+/// Running subscriptions / monitors, keyed by session id
+#[derive(Default)]
+pub struct RedisStreams {
+    tasks: Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>,
+}
+
+impl RedisStreams {
+    /// Spawn the session task and register it. The lock is held across the spawn so
+    /// a stream that ends at once can't run its `remove` before the handle is stored.
+    fn spawn<F>(&self, session_id: String, task: F)
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let mut tasks = self.tasks.lock();
+        let handle = tauri::async_runtime::spawn(task);
+        if let Ok(tasks) = tasks.as_mut() {
+            tasks.insert(session_id, handle);
+        }
+    }
+
+    fn remove(&self, session_id: &str) -> Option<tauri::async_runtime::JoinHandle<()>> {
+        self.tasks.lock().ok()?.remove(session_id)
+    }
+
+    /// Stop a session, returns false if it was not running
+    pub fn stop(&self, session_id: &str) -> bool {
+        match self.remove(session_id) {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RedisStreamMessage {
+    pub kind: String, // "message", "pmessage" or "monitor"
+    pub channel: Option<String>,
+    pub pattern: Option<String>,
+    pub payload: String,
+    pub timestamp: i64, // unix millis when received
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RedisStreamEvent {
+    pub session_id: String,
+    pub messages: Vec<RedisStreamMessage>,
+    pub dropped: u64, // Messages discarded by the rate limit since the last event
+    pub closed: bool, // The server closed the stream, no more events follow
+}
+
+/// Forward messages to the frontend in batches, at most `max_per_second`.
+/// Anything above the limit is counted but not sent so a busy server can't flood the UI.
+async fn pump(
+    app: tauri::AppHandle,
+    session_id: String,
+    stream: impl Stream<Item = RedisStreamMessage> + Send + 'static,
+    max_per_second: usize,
+) {
+    let budget = (max_per_second * FLUSH_INTERVAL_MS as usize / 1000).max(1);
+    let mut stream = Box::pin(stream);
+    let mut tick = tokio::time::interval(Duration::from_millis(FLUSH_INTERVAL_MS));
+    let mut buffer = Vec::new();
+    let mut dropped = 0;
+
+    loop {
+        tokio::select! {
+            msg = stream.next() => match msg {
+                Some(msg) if buffer.len() < budget => buffer.push(msg),
+                Some(_) => dropped += 1,
+                None => break,
+            },
+            _ = tick.tick() => {
+                if !buffer.is_empty() || dropped > 0 {
+                    let _ = app.emit(STREAM_EVENT, RedisStreamEvent {
+                        session_id: session_id.clone(),
+                        messages: std::mem::take(&mut buffer),
+                        dropped,
+                        closed: false,
+                    });
+                    dropped = 0;
+                }
+            }
+        }
+    }
+
+    let _ = app.emit(
+        STREAM_EVENT,
+        RedisStreamEvent {
+            session_id: session_id.clone(),
+            messages: buffer,
+            dropped,
+            closed: true,
+        },
+    );
+    app.state::<RedisStreams>().remove(&session_id);
+}
+
+fn start(
+    app: tauri::AppHandle,
+    stream: impl Stream<Item = RedisStreamMessage> + Send + 'static,
+    max_per_second: Option<usize>,
+) -> String {
+    let session_id = uuid::Uuid::new_v4().to_string();
+    let limit = max_per_second
+        .filter(|n| *n > 0)
+        .unwrap_or(DEFAULT_MAX_PER_SECOND);
+    app.state::<RedisStreams>().spawn(
+        session_id.clone(),
+        pump(app.clone(), session_id.clone(), stream, limit),
+    );
+    session_id
+}
+
+/// SUBSCRIBE / PSUBSCRIBE and stream messages until stopped. Returns the session id.
+pub async fn subscribe(
+    app: tauri::AppHandle,
+    config: &ConnectionConfig,
+    channels: Vec<String>,
+    patterns: Vec<String>,
+    max_per_second: Option<usize>,
+) -> Result<String, String> {
+    if channels.is_empty() && patterns.is_empty() {
+        return Err("请至少指定一个频道或模式".to_string());
+    }
+
+    let mut pubsub = redis_conn::pubsub(config).await?;
+    for channel in &channels {
+        pubsub.subscribe(channel).await.map_err(|e| e.to_string())?;
+    }
+    for pattern in &patterns {
+        pubsub
+            .psubscribe(pattern)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+
+    let stream = pubsub.into_on_message().map(|msg| {
+        let pattern: Option<String> = if msg.from_pattern() {
+            msg.get_pattern().ok()
+        } else {
+            None
+        };
+        RedisStreamMessage {
+            kind: if pattern.is_some() {
+                "pmessage"
+            } else {
+                "message"
+            }
+            .to_string(),
+            channel: Some(msg.get_channel_name().to_string()),
+            pattern,
+            payload: redis_value::display_text(msg.get_payload_bytes()),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        }
+    });
+
+    Ok(start(app, stream, max_per_second))
+}
+
+/// MONITOR and stream every command the server executes until stopped
+pub async fn monitor(
+    app: tauri::AppHandle,
+    config: &ConnectionConfig,
+    max_per_second: Option<usize>,
+) -> Result<String, String> {
+    let mut monitor = redis_conn::monitor(config).await?;
+    monitor.monitor().await.map_err(|e| e.to_string())?;
+
+    // Lines look like: 1339518083.107412 [0 127.0.0.1:60866] "keys" "*"
+    let stream = monitor
+        .into_on_message::<String>()
+        .map(|line| RedisStreamMessage {
+            kind: "monitor".to_string(),
+            channel: None,
+            pattern: None,
+            payload: line,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        });
+
+    Ok(start(app, stream, max_per_second))
+}
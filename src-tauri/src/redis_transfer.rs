@@ -0,0 +1,455 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+
+use crate::jobs::{JobHandle, JobSummary};
+use crate::redis_conn::RedisConnection;
+
+/// Emit a progress event every this many keys
+const PROGRESS_EVERY: u64 = 100;
+/// Failed keys kept in the import summary; later ones are only counted
+const MAX_REPORTED_ERRORS: usize = 1000;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RedisExportOptions {
+    pub path: String,
+    pub pattern: Option<String>, // SCAN MATCH pattern, default "*"
+    pub format: Option<String>, // "dump" (default, DUMP payload + TTL) or "json" (readable, by type)
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RedisImportError {
+    pub line: u64,
+    pub key: Option<String>,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RedisImportSummary {
+    pub imported: u64,
+    pub skipped: u64, // Keys that already existed with the "skip" policy
+    pub failed: u64,
+    pub errors: Vec<RedisImportError>, // First MAX_REPORTED_ERRORS failures
+}
+
+impl From<RedisImportSummary> for JobSummary {
+    fn from(summary: RedisImportSummary) -> Self {
+        JobSummary {
+            processed: summary.imported,
+            details: serde_json::to_value(&summary).ok(),
+        }
+    }
+}
+
+fn b64() -> base64::engine::GeneralPurpose {
+    base64::engine::general_purpose::STANDARD
+}
+
+/// Members as text, or all of them in base64 when `binary`
+fn texts(items: Vec<Vec<u8>>, binary: bool) -> Vec<String> {
+    items.into_iter().map(|b| text(b, binary)).collect()
+}
+
+fn text(bytes: Vec<u8>, binary: bool) -> String {
+    if binary {
+        b64().encode(bytes)
+    } else {
+        String::from_utf8(bytes).unwrap_or_default()
+    }
+}
+
+fn is_binary<'a>(mut items: impl Iterator<Item = &'a Vec<u8>>) -> bool {
+    items.any(|b| std::str::from_utf8(b).is_err())
+}
+
+/// `{"key": ...}`, with non-UTF-8 key names in base64 and marked by "key_encoding"
+fn key_entry(key: &[u8]) -> Value {
+    match std::str::from_utf8(key) {
+        Ok(k) => json!({ "key": k }),
+        Err(_) => json!({ "key": b64().encode(key), "key_encoding": "base64" }),
+    }
+}
+
+/// `{"key", "ttl", "dump"}` with the base64 DUMP payload, or None if the key vanished
+async fn export_dump(con: &mut RedisConnection, key: &[u8]) -> Result<Option<Value>, String> {
+    let (pttl, payload): (i64, Option<Vec<u8>>) = redis::pipe()
+        .cmd("PTTL")
+        .arg(key)
+        .cmd("DUMP")
+        .arg(key)
+        .query_async(con)
+        .await
+        .map_err(|e| e.to_string())?;
+    let Some(payload) = payload else {
+        return Ok(None);
+    };
+    let mut entry = key_entry(key);
+    entry["ttl"] = json!(pttl.max(0)); // 0 = no expiry, as RESTORE expects
+    entry["dump"] = json!(b64().encode(payload));
+    Ok(Some(entry))
+}
+
+/// `{"key", "type", "ttl", "value"}` with the value in a readable shape per type.
+/// When any member isn't UTF-8, every member is base64 and "encoding" says so.
+async fn export_json(con: &mut RedisConnection, key: &[u8]) -> Result<Option<Value>, String> {
+    let (key_type, pttl): (String, i64) = redis::pipe()
+        .cmd("TYPE")
+        .arg(key)
+        .cmd("PTTL")
+        .arg(key)
+        .query_async(con)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut entry = key_entry(key);
+    entry["type"] = json!(key_type);
+    entry["ttl"] = json!(pttl.max(0));
+    let binary;
+    let value = match key_type.as_str() {
+        "none" => return Ok(None),
+        "string" => {
+            let bytes: Vec<u8> = redis::cmd("GET")
+                .arg(key)
+                .query_async(con)
+                .await
+                .map_err(|e| e.to_string())?;
+            binary = std::str::from_utf8(&bytes).is_err();
+            json!(text(bytes, binary))
+        }
+        "hash" => {
+            let pairs: Vec<(Vec<u8>, Vec<u8>)> = redis::cmd("HGETALL")
+                .arg(key)
+                .query_async(con)
+                .await
+                .map_err(|e| e.to_string())?;
+            binary = is_binary(pairs.iter().flat_map(|(f, v)| [f, v]));
+            let map: Map<String, Value> = pairs
+                .into_iter()
+                .map(|(f, v)| (text(f, binary), json!(text(v, binary))))
+                .collect();
+            Value::Object(map)
+        }
+        "list" => {
+            let items: Vec<Vec<u8>> = redis::cmd("LRANGE")
+                .arg(key)
+                .arg(0)
+                .arg(-1)
+                .query_async(con)
+                .await
+                .map_err(|e| e.to_string())?;
+            binary = is_binary(items.iter());
+            json!(texts(items, binary))
+        }
+        "set" => {
+            let items: Vec<Vec<u8>> = redis::cmd("SMEMBERS")
+                .arg(key)
+                .query_async(con)
+                .await
+                .map_err(|e| e.to_string())?;
+            binary = is_binary(items.iter());
+            json!(texts(items, binary))
+        }
+        "zset" => {
+            let items: Vec<(Vec<u8>, f64)> = redis::cmd("ZRANGE")
+                .arg(key)
+                .arg(0)
+                .arg(-1)
+                .arg("WITHSCORES")
+                .query_async(con)
+                .await
+                .map_err(|e| e.to_string())?;
+            binary = is_binary(items.iter().map(|(m, _)| m));
+            json!(items
+                .into_iter()
+                .map(|(m, s)| json!([text(m, binary), s]))
+                .collect::<Vec<_>>())
+        }
+        "stream" => {
+            let entries: Vec<(String, Vec<Vec<u8>>)> = redis::cmd("XRANGE")
+                .arg(key)
+                .arg("-")
+                .arg("+")
+                .query_async(con)
+                .await
+                .map_err(|e| e.to_string())?;
+            binary = is_binary(entries.iter().flat_map(|(_, fields)| fields));
+            json!(entries
+                .into_iter()
+                .map(|(id, fields)| {
+                    let map: Map<String, Value> = fields
+                        .chunks(2)
+                        .map(|kv| {
+                            let v = kv.get(1).cloned().unwrap_or_default();
+                            (text(kv[0].clone(), binary), json!(text(v, binary)))
+                        })
+                        .collect();
+                    json!({ "id": id, "fields": map })
+                })
+                .collect::<Vec<_>>())
+        }
+        other => return Err(format!("Unsupported key type: {}", other)),
+    };
+    if binary {
+        entry["encoding"] = json!("base64");
+    }
+    entry["value"] = value;
+    Ok(Some(entry))
+}
+
+/// Write keys matching the pattern to an NDJSON file, one key per line
+pub async fn export(
+    job: JobHandle,
+    mut con: RedisConnection,
+    options: RedisExportOptions,
+) -> Result<u64, String> {
+    let result = write_keys(&job, &mut con, &options).await;
+    if result.is_err() {
+        let _ = std::fs::remove_file(&options.path);
+    }
+    result
+}
+
+async fn write_keys(
+    job: &JobHandle,
+    con: &mut RedisConnection,
+    options: &RedisExportOptions,
+) -> Result<u64, String> {
+    let json_format = match options.format.as_deref() {
+        None | Some("") | Some("dump") => false,
+        Some("json") => true,
+        Some(other) => return Err(format!("Unsupported export format: {}", other)),
+    };
+    let pattern = options
+        .pattern
+        .as_deref()
+        .filter(|p| !p.is_empty())
+        .unwrap_or("*");
+    let keys: Vec<Vec<u8>> = crate::redis_conn::scan_keys(con, pattern).await?;
+    let total = keys.len() as u64;
+
+    let mut out = File::create(&options.path)
+        .map(BufWriter::new)
+        .map_err(|e| format!("无法创建文件 {}: {}", options.path, e))?;
+    let mut count = 0;
+    for (i, key) in keys.iter().enumerate() {
+        job.check_cancelled()?;
+        let entry = if json_format {
+            export_json(con, key).await?
+        } else {
+            export_dump(con, key).await?
+        };
+        // Keys that expired since the scan are left out
+        if let Some(entry) = entry {
+            writeln!(out, "{}", entry).map_err(|e| e.to_string())?;
+            count += 1;
+        }
+        if (i as u64 + 1).is_multiple_of(PROGRESS_EVERY) {
+            job.progress(i as u64 + 1, Some(total), None);
+        }
+    }
+    out.flush().map_err(|e| e.to_string())?;
+    Ok(count)
+}
+
+/// Raw bytes of an exported string, decoding base64 when `binary`
+fn raw(text: &str, binary: bool) -> Result<Vec<u8>, String> {
+    if binary {
+        b64()
+            .decode(text)
+            .map_err(|e| format!("base64 解码失败: {}", e))
+    } else {
+        Ok(text.as_bytes().to_vec())
+    }
+}
+
+fn raw_value(value: &Value, binary: bool) -> Result<Vec<u8>, String> {
+    match value {
+        Value::String(s) => raw(s, binary),
+        other => Ok(other.to_string().into_bytes()),
+    }
+}
+
+fn raw_items(value: &Value, binary: bool) -> Result<Vec<Vec<u8>>, String> {
+    let items = value.as_array().ok_or("value 应为数组")?;
+    items.iter().map(|v| raw_value(v, binary)).collect()
+}
+
+/// Field / value pairs of a hash or stream entry
+type RawPairs = Vec<(Vec<u8>, Vec<u8>)>;
+
+fn raw_map(value: &Value, binary: bool) -> Result<RawPairs, String> {
+    let map = value.as_object().ok_or("value 应为对象")?;
+    map.iter()
+        .map(|(k, v)| Ok((raw(k, binary)?, raw_value(v, binary)?)))
+        .collect()
+}
+
+/// Key name of an exported line
+fn entry_key(entry: &Value) -> Result<Vec<u8>, String> {
+    let key = entry["key"].as_str().ok_or("缺少 key 字段")?;
+    raw(key, entry["key_encoding"].as_str() == Some("base64"))
+}
+
+/// Commands that recreate a key from its JSON export. They run in one MULTI / EXEC
+/// (every command targets the same key, so also in cluster mode), so a failing command
+/// doesn't leave the key deleted.
+fn json_commands(key: &[u8], entry: &Value) -> Result<redis::Pipeline, String> {
+    let key_type = entry["type"].as_str().ok_or("缺少 type 字段")?;
+    let value = &entry["value"];
+    let binary = entry["encoding"].as_str() == Some("base64");
+    let mut pipe = redis::pipe();
+    pipe.atomic();
+    pipe.cmd("DEL").arg(key).ignore();
+
+    match key_type {
+        "string" => {
+            let s = value.as_str().ok_or("value 应为字符串")?;
+            pipe.cmd("SET").arg(key).arg(raw(s, binary)?).ignore();
+        }
+        "hash" => {
+            let fields = raw_map(value, binary)?;
+            if !fields.is_empty() {
+                pipe.cmd("HSET").arg(key).arg(fields).ignore();
+            }
+        }
+        "list" | "set" => {
+            let items = raw_items(value, binary)?;
+            if !items.is_empty() {
+                let cmd = if key_type == "list" { "RPUSH" } else { "SADD" };
+                pipe.cmd(cmd).arg(key).arg(items).ignore();
+            }
+        }
+        "zset" => {
+            let items = value.as_array().ok_or("value 应为数组")?;
+            if !items.is_empty() {
+                let cmd = pipe.cmd("ZADD").arg(key);
+                for item in items {
+                    let member = item[0].as_str().ok_or("zset 成员应为字符串")?;
+                    let score = item[1].as_f64().ok_or("zset 分数应为数字")?;
+                    cmd.arg(score).arg(raw(member, binary)?);
+                }
+                cmd.ignore();
+            }
+        }
+        "stream" => {
+            let entries = value.as_array().ok_or("value 应为数组")?;
+            for e in entries {
+                let id = e["id"].as_str().ok_or("stream 条目缺少 id")?;
+                pipe.cmd("XADD")
+                    .arg(key)
+                    .arg(id)
+                    .arg(raw_map(&e["fields"], binary)?)
+                    .ignore();
+            }
+        }
+        other => return Err(format!("Unsupported key type: {}", other)),
+    }
+
+    if let Some(ttl) = entry["ttl"].as_i64().filter(|t| *t > 0) {
+        pipe.cmd("PEXPIRE").arg(key).arg(ttl).ignore();
+    }
+    Ok(pipe)
+}
+
+/// Restore one exported line. Returns false when the key existed and was skipped.
+async fn import_entry(
+    con: &mut RedisConnection,
+    entry: &Value,
+    replace: bool,
+) -> Result<bool, String> {
+    let key = entry_key(entry)?;
+    let key = &key[..];
+
+    if !replace {
+        let exists: bool = redis::cmd("EXISTS")
+            .arg(key)
+            .query_async(con)
+            .await
+            .map_err(|e| e.to_string())?;
+        if exists {
+            return Ok(false);
+        }
+    }
+
+    if let Some(dump) = entry["dump"].as_str() {
+        let payload = b64().decode(dump).map_err(|e| e.to_string())?;
+        let mut cmd = redis::cmd("RESTORE");
+        cmd.arg(key)
+            .arg(entry["ttl"].as_i64().unwrap_or(0).max(0))
+            .arg(payload);
+        if replace {
+            cmd.arg("REPLACE");
+        }
+        let _: () = cmd.query_async(con).await.map_err(|e| e.to_string())?;
+    } else {
+        let _: () = json_commands(key, entry)?
+            .query_async(con)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(true)
+}
+
+/// Load an export file into the target connection. `conflict` is "skip" (default) or "replace".
+pub async fn import(
+    job: JobHandle,
+    mut con: RedisConnection,
+    path: String,
+    conflict: Option<String>,
+) -> Result<RedisImportSummary, String> {
+    let replace = match conflict.as_deref() {
+        None | Some("") | Some("skip") => false,
+        Some("replace") => true,
+        Some(other) => return Err(format!("Unknown conflict policy: {}", other)),
+    };
+    let file = File::open(&path).map_err(|e| format!("无法打开文件 {}: {}", path, e))?;
+
+    let mut summary = RedisImportSummary {
+        imported: 0,
+        skipped: 0,
+        failed: 0,
+        errors: Vec::new(),
+    };
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        job.check_cancelled()?;
+        let line_no = i as u64 + 1;
+        let line = line.map_err(|e| e.to_string())?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let result = match serde_json::from_str::<Value>(&line) {
+            Ok(entry) => import_entry(&mut con, &entry, replace)
+                .await
+                .map_err(|e| (entry["key"].as_str().map(String::from), e)),
+            Err(e) => Err((None, e.to_string())),
+        };
+        match result {
+            Ok(true) => summary.imported += 1,
+            Ok(false) => summary.skipped += 1,
+            Err((key, error)) => {
+                summary.failed += 1;
+                if summary.errors.len() < MAX_REPORTED_ERRORS {
+                    summary.errors.push(RedisImportError {
+                        line: line_no,
+                        key,
+                        error,
+                    });
+                }
+            }
+        }
+        if line_no.is_multiple_of(PROGRESS_EVERY) {
+            job.progress(
+                summary.imported,
+                None,
+                Some(format!(
+                    "已导入 {} 个键，跳过 {} 个，失败 {} 个",
+                    summary.imported, summary.skipped, summary.failed
+                )),
+            );
+        }
+    }
+    Ok(summary)
+}
@@ -0,0 +1,300 @@
+use base64::Engine;
+use std::io::{Cursor, Read, Write};
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+const JAVA_MAGIC: &[u8] = &[0xac, 0xed, 0x00, 0x05];
+
+/// Guess how a raw Redis value is encoded
+pub fn detect_encoding(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(GZIP_MAGIC) {
+        return "gzip";
+    }
+    if bytes.starts_with(ZSTD_MAGIC) {
+        return "zstd";
+    }
+    if bytes.starts_with(JAVA_MAGIC) {
+        return "java_serialized";
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => {
+            let trimmed = text.trim();
+            if (trimmed.starts_with('{') || trimmed.starts_with('['))
+                && serde_json::from_str::<serde_json::Value>(trimmed).is_ok()
+            {
+                "json"
+            } else if is_php_serialized(trimmed) {
+                "php_serialized"
+            } else {
+                "utf8"
+            }
+        }
+        Err(_) => {
+            // Only treat complete maps/arrays as msgpack, scalars match too much random data
+            let is_container = matches!(bytes.first(), Some(0x80..=0x9f | 0xdc..=0xdf));
+            if is_container && msgpack_to_json(bytes).is_ok() {
+                "msgpack"
+            } else {
+                "hex"
+            }
+        }
+    }
+}
+
+/// Render raw bytes as text in the requested format (or the detected one).
+/// Returns the format actually used together with the text. Compressed values
+/// report the payload format too, e.g. "gzip+msgpack", so saving them round-trips.
+pub fn render(bytes: &[u8], format: Option<&str>) -> Result<(String, String), String> {
+    let format = match format {
+        Some(f) if !f.is_empty() && f != "auto" => f,
+        _ => detect_encoding(bytes),
+    };
+    if let Some((codec, inner)) = split_compression(format) {
+        let out = decompress(bytes, codec)?;
+        let (inner, text) = match inner {
+            Some(inner) => (inner, decode(&out, inner)?),
+            None => {
+                let detected = detect_encoding(&out);
+                match decode(&out, detected) {
+                    Ok(text) => (detected, text),
+                    Err(_) => ("hex", to_hex(&out)),
+                }
+            }
+        };
+        return Ok((format!("{}+{}", codec, inner), text));
+    }
+    let text = decode(bytes, format)?;
+    Ok((format.to_string(), text))
+}
+
+/// "gzip" / "zstd" with the optional payload format of "gzip+msgpack"
+fn split_compression(format: &str) -> Option<(&str, Option<&str>)> {
+    let (codec, inner) = match format.split_once('+') {
+        Some((codec, inner)) => (codec, Some(inner)),
+        None => (format, None),
+    };
+    matches!(codec, "gzip" | "zstd").then_some((codec, inner))
+}
+
+fn decompress(bytes: &[u8], codec: &str) -> Result<Vec<u8>, String> {
+    if codec == "zstd" {
+        return zstd::decode_all(bytes).map_err(|e| format!("zstd 解压失败: {}", e));
+    }
+    let mut out = Vec::new();
+    flate2::read::GzDecoder::new(bytes)
+        .read_to_end(&mut out)
+        .map_err(|e| format!("gzip 解压失败: {}", e))?;
+    Ok(out)
+}
+
+fn compress(bytes: &[u8], codec: &str) -> Result<Vec<u8>, String> {
+    if codec == "zstd" {
+        return zstd::encode_all(bytes, 0).map_err(|e| e.to_string());
+    }
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(bytes).map_err(|e| e.to_string())?;
+    encoder.finish().map_err(|e| e.to_string())
+}
+
+/// Decode raw bytes into display text for the given format
+pub fn decode(bytes: &[u8], format: &str) -> Result<String, String> {
+    match format {
+        "utf8" | "php_serialized" => std::str::from_utf8(bytes)
+            .map(|s| s.to_string())
+            .map_err(|_| "值不是有效的 UTF-8 文本，请使用 hex 或 base64 查看".to_string()),
+        "json" => {
+            let value: serde_json::Value =
+                serde_json::from_slice(bytes).map_err(|e| format!("JSON 解析失败: {}", e))?;
+            serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+        }
+        "hex" | "java_serialized" => Ok(to_hex(bytes)),
+        "base64" => Ok(base64::engine::general_purpose::STANDARD.encode(bytes)),
+        "msgpack" => {
+            let value = msgpack_to_json(bytes)?;
+            serde_json::to_string_pretty(&value).map_err(|e| e.to_string())
+        }
+        f if split_compression(f).is_some() => render(bytes, Some(f)).map(|(_, text)| text),
+        _ => Err(format!("Unsupported encoding: {}", format)),
+    }
+}
+
+/// Encode edited text back into raw bytes using the given format
+pub fn encode(text: &str, format: &str) -> Result<Vec<u8>, String> {
+    match format {
+        "utf8" | "php_serialized" => Ok(text.as_bytes().to_vec()),
+        "json" => {
+            let value: serde_json::Value =
+                serde_json::from_str(text).map_err(|e| format!("JSON 解析失败: {}", e))?;
+            serde_json::to_vec(&value).map_err(|e| e.to_string())
+        }
+        "hex" | "java_serialized" => from_hex(text),
+        "base64" => base64::engine::general_purpose::STANDARD
+            .decode(text.trim())
+            .map_err(|e| format!("base64 解码失败: {}", e)),
+        "msgpack" => {
+            let value: serde_json::Value =
+                serde_json::from_str(text).map_err(|e| format!("JSON 解析失败: {}", e))?;
+            rmp_serde::to_vec(&value).map_err(|e| e.to_string())
+        }
+        f => match split_compression(f) {
+            Some((codec, Some(inner))) => compress(&encode(text, inner)?, codec),
+            Some((codec, None)) => compress(&encode_inner(text)?, codec),
+            None => Err(format!("Unsupported encoding: {}", format)),
+        },
+    }
+}
+
+/// Payload of a bare "gzip" / "zstd" format, guessed from the text the way
+/// `render` would have shown it
+fn encode_inner(text: &str) -> Result<Vec<u8>, String> {
+    match detect_encoding(text.as_bytes()) {
+        "json" => encode(text, "json"),
+        _ if is_hex_dump(text) => from_hex(text),
+        _ => Ok(text.as_bytes().to_vec()),
+    }
+}
+
+fn msgpack_to_json(bytes: &[u8]) -> Result<serde_json::Value, String> {
+    let mut de = rmp_serde::Deserializer::new(Cursor::new(bytes));
+    let value: serde_json::Value =
+        serde::Deserialize::deserialize(&mut de).map_err(|e| format!("msgpack 解析失败: {}", e))?;
+    // Reject payloads with trailing garbage, they are not a single msgpack document
+    if de.get_ref().position() as usize != bytes.len() {
+        return Err("msgpack 解析失败: 存在多余数据".to_string());
+    }
+    Ok(value)
+}
+
+fn is_php_serialized(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    if text == "N;" {
+        return true;
+    }
+    if bytes.len() < 4 || bytes[1] != b':' {
+        return false;
+    }
+    match bytes[0] {
+        b'a' | b'O' | b's' | b'C' => {
+            bytes[2].is_ascii_digit() && (text.ends_with('}') || text.ends_with(';'))
+        }
+        b'i' | b'b' | b'd' => text.ends_with(';') && !text.contains(' '),
+        _ => false,
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode_upper(bytes))
+}
+
+fn is_hex_dump(text: &str) -> bool {
+    text.starts_with("0x") && text.len() > 2 && text[2..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn from_hex(text: &str) -> Result<Vec<u8>, String> {
+    let cleaned: String = text
+        .trim()
+        .trim_start_matches("0x")
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    hex::decode(cleaned).map_err(|e| format!("hex 解码失败: {}", e))
+}
+
+/// Render collection members one by one. The reported encoding is the one
+/// shared by every member, or "mixed" when they differ.
+pub fn render_many(
+    items: &[Vec<u8>],
+    format: Option<&str>,
+) -> Result<(String, Vec<String>), String> {
+    let mut encoding: Option<String> = None;
+    let mut rendered = Vec::with_capacity(items.len());
+    for item in items {
+        // One member that doesn't decode shouldn't hide the rest
+        let (item_encoding, text) =
+            render(item, format).unwrap_or_else(|_| ("hex".to_string(), to_hex(item)));
+        encoding = match encoding {
+            None => Some(item_encoding),
+            Some(e) if e == item_encoding => Some(e),
+            Some(_) => Some("mixed".to_string()),
+        };
+        rendered.push(text);
+    }
+    Ok((encoding.unwrap_or_else(|| "utf8".to_string()), rendered))
+}
+
+/// Plain text when the bytes are UTF-8, otherwise a hex dump
+pub fn display_text(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s.to_string(),
+        Err(_) => to_hex(bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Render in the detected format, then encode the shown text back
+    fn round_trip(bytes: &[u8]) -> (String, Vec<u8>) {
+        let (format, text) = render(bytes, None).unwrap();
+        let encoded = encode(&text, &format).unwrap();
+        (format, encoded)
+    }
+
+    #[test]
+    fn msgpack_round_trips() {
+        let packed =
+            rmp_serde::to_vec(&json!({ "id": 7, "tags": ["a", "b"], "ok": true })).unwrap();
+        assert_eq!(detect_encoding(&packed), "msgpack");
+        assert_eq!(round_trip(&packed), ("msgpack".to_string(), packed));
+    }
+
+    #[test]
+    fn compressed_values_keep_their_payload_format() {
+        let packed = rmp_serde::to_vec(&json!({ "name": "x", "n": [1, 2, 3] })).unwrap();
+        let gzipped = compress(&packed, "gzip").unwrap();
+        assert_eq!(detect_encoding(&gzipped), "gzip");
+        let (format, encoded) = round_trip(&gzipped);
+        assert_eq!(format, "gzip+msgpack");
+        assert_eq!(decompress(&encoded, "gzip").unwrap(), packed);
+
+        let doc = br#"{"a":1,"b":"two"}"#.to_vec();
+        let zstd = compress(&doc, "zstd").unwrap();
+        assert_eq!(detect_encoding(&zstd), "zstd");
+        let (format, encoded) = round_trip(&zstd);
+        assert_eq!(format, "zstd+json");
+        assert_eq!(decompress(&encoded, "zstd").unwrap(), doc);
+
+        // Binary payloads are shown as hex and compressed again from it
+        let binary = vec![0xff, 0x00, 0x7f];
+        let (format, encoded) = round_trip(&compress(&binary, "gzip").unwrap());
+        assert_eq!(format, "gzip+hex");
+        assert_eq!(decompress(&encoded, "gzip").unwrap(), binary);
+        let bare = encode("0xFF007F", "gzip").unwrap();
+        assert_eq!(decompress(&bare, "gzip").unwrap(), binary);
+    }
+
+    #[test]
+    fn hex_and_base64_round_trip() {
+        let binary = vec![0xde, 0xad, 0x00, 0xbe, 0xef];
+        assert_eq!(render(&binary, None).unwrap().1, "0xDEAD00BEEF");
+        assert_eq!(round_trip(&binary), ("hex".to_string(), binary.clone()));
+
+        let (format, text) = render(&binary, Some("base64")).unwrap();
+        assert_eq!((format.as_str(), text.as_str()), ("base64", "3q0Avu8="));
+        assert_eq!(encode(&text, &format).unwrap(), binary);
+    }
+
+    #[test]
+    fn render_many_falls_back_to_hex_per_member() {
+        let items = vec![br#"{"a":1}"#.to_vec(), vec![0xff]];
+        let (encoding, texts) = render_many(&items, Some("json")).unwrap();
+        assert_eq!(encoding, "mixed");
+        assert_eq!(
+            texts,
+            vec!["{\n  \"a\": 1\n}".to_string(), "0xFF".to_string()]
+        );
+    }
+}
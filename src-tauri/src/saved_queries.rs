@@ -0,0 +1,353 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::sql_dialect;
+
+/// Version of the file written by `export`
+const EXPORT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedQuery {
+    pub id: String,
+    pub name: String,
+    pub folder: Option<String>, // "/"-separated path, e.g. "diagnostics/locks"
+    pub db_type: Option<String>, // "mysql" / "postgresql", None = any
+    pub sql: String,            // May contain :name or ${name} placeholders
+    pub description: Option<String>,
+    #[serde(default)]
+    pub params: Vec<String>, // Placeholder names in order of first use, filled in on save
+    #[serde(default)]
+    pub updated_at: i64, // unix millis
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedQueryFile {
+    version: u32,
+    queries: Vec<SavedQuery>,
+}
+
+pub fn load(path: &Path) -> Result<Vec<SavedQuery>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    Ok(serde_json::from_str(&content).unwrap_or_default())
+}
+
+fn store(path: &Path, queries: &[SavedQuery]) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(queries).map_err(|e| e.to_string())?;
+    fs::write(path, json).map_err(|e| e.to_string())
+}
+
+/// Insert or update by id. Returns the stored query with its parameters filled in.
+pub fn save(path: &Path, mut query: SavedQuery) -> Result<SavedQuery, String> {
+    if query.name.trim().is_empty() {
+        return Err("查询名称不能为空".to_string());
+    }
+    if query.id.is_empty() {
+        query.id = uuid::Uuid::new_v4().to_string();
+    }
+    query.params =
+        unique_params(&compile(&query.sql, query.db_type.as_deref().unwrap_or("mysql")).1);
+    query.updated_at = chrono::Utc::now().timestamp_millis();
+
+    let mut queries = load(path)?;
+    match queries.iter().position(|q| q.id == query.id) {
+        Some(idx) => queries[idx] = query.clone(),
+        None => queries.push(query.clone()),
+    }
+    store(path, &queries)?;
+    Ok(query)
+}
+
+pub fn delete(path: &Path, id: &str) -> Result<(), String> {
+    let mut queries = load(path)?;
+    queries.retain(|q| q.id != id);
+    store(path, &queries)
+}
+
+/// Write the selected queries (all when `ids` is None) to a shareable file
+pub fn export(path: &Path, target: &str, ids: Option<Vec<String>>) -> Result<u64, String> {
+    let mut queries = load(path)?;
+    if let Some(ids) = ids.filter(|ids| !ids.is_empty()) {
+        queries.retain(|q| ids.contains(&q.id));
+    }
+    let file = SavedQueryFile {
+        version: EXPORT_VERSION,
+        queries,
+    };
+    let json = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    fs::write(target, json).map_err(|e| format!("无法写入文件 {}: {}", target, e))?;
+    Ok(file.queries.len() as u64)
+}
+
+/// Merge queries from an exported file. Existing ids are replaced only with `overwrite`.
+/// Returns the number of queries added or replaced.
+pub fn import(path: &Path, source: &str, overwrite: bool) -> Result<u64, String> {
+    let content =
+        fs::read_to_string(source).map_err(|e| format!("无法读取文件 {}: {}", source, e))?;
+    let file: SavedQueryFile =
+        serde_json::from_str(&content).map_err(|e| format!("文件格式不正确: {}", e))?;
+
+    let mut queries = load(path)?;
+    let mut count = 0;
+    for mut query in file.queries {
+        query.params =
+            unique_params(&compile(&query.sql, query.db_type.as_deref().unwrap_or("mysql")).1);
+        match queries.iter().position(|q| q.id == query.id) {
+            Some(_) if !overwrite => continue,
+            Some(idx) => queries[idx] = query,
+            None => {
+                if query.id.is_empty() {
+                    query.id = uuid::Uuid::new_v4().to_string();
+                }
+                queries.push(query)
+            }
+        }
+        count += 1;
+    }
+    store(path, &queries)?;
+    Ok(count)
+}
+
+fn unique_params(names: &[String]) -> Vec<String> {
+    let mut unique: Vec<String> = Vec::new();
+    for name in names {
+        if !unique.contains(name) {
+            unique.push(name.clone());
+        }
+    }
+    unique
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Replace `:name` and `${name}` placeholders with the dialect's bind placeholders.
+/// Returns the SQL and the parameter name of every placeholder in order; a name used
+/// twice appears twice. Quotes, comments (including MySQL `#` ones), PostgreSQL
+/// dollar-quoted bodies and `::` casts are left alone.
+pub fn compile(sql: &str, db_type: &str) -> (String, Vec<String>) {
+    let mysql = db_type == "mysql";
+    let chars: Vec<char> = sql.chars().collect();
+    let mut out = String::new();
+    let mut names = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            '\'' | '"' | '`' => {
+                out.push(c);
+                i += 1;
+                while i < chars.len() {
+                    out.push(chars[i]);
+                    i += 1;
+                    if chars[i - 1] == '\\' && mysql {
+                        if let Some(&escaped) = chars.get(i) {
+                            out.push(escaped);
+                            i += 1;
+                        }
+                    } else if chars[i - 1] == c {
+                        break;
+                    }
+                }
+            }
+            '-' if next == Some('-') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    out.push(chars[i]);
+                    i += 1;
+                }
+            }
+            '#' if mysql => {
+                while i < chars.len() && chars[i] != '\n' {
+                    out.push(chars[i]);
+                    i += 1;
+                }
+            }
+            '/' if next == Some('*') => {
+                let start = i;
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i = (i + 2).min(chars.len());
+                out.extend(&chars[start..i]);
+            }
+            ':' if next == Some(':') => {
+                out.push_str("::");
+                i += 2;
+            }
+            ':' if next.is_some_and(is_ident_start) => {
+                let start = i + 1;
+                let mut end = start;
+                while end < chars.len() && is_ident(chars[end]) {
+                    end += 1;
+                }
+                names.push(chars[start..end].iter().collect());
+                out.push_str(&sql_dialect::placeholder(db_type, names.len()));
+                i = end;
+            }
+            '$' if next == Some('{') => match chars[i + 2..].iter().position(|&ch| ch == '}') {
+                Some(len)
+                    if len > 0 && chars[i + 2..i + 2 + len].iter().all(|&ch| is_ident(ch)) =>
+                {
+                    names.push(chars[i + 2..i + 2 + len].iter().collect());
+                    out.push_str(&sql_dialect::placeholder(db_type, names.len()));
+                    i += len + 3;
+                }
+                _ => {
+                    out.push(c);
+                    i += 1;
+                }
+            },
+            '$' if !mysql => {
+                // Dollar quote tag: $$ or $name$, copied through with its body
+                let mut j = i + 1;
+                while j < chars.len() && (chars[j].is_alphanumeric() || chars[j] == '_') {
+                    j += 1;
+                }
+                let is_tag = chars.get(j) == Some(&'$')
+                    && !chars.get(i + 1).is_some_and(|ch| ch.is_ascii_digit());
+                if !is_tag {
+                    out.push(c);
+                    i += 1;
+                    continue;
+                }
+                let tag: Vec<char> = chars[i..=j].to_vec();
+                let start = i;
+                i = j + 1;
+                while i < chars.len() && !chars[i..].starts_with(&tag) {
+                    i += 1;
+                }
+                i = (i + tag.len()).min(chars.len());
+                out.extend(&chars[start..i]);
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    (out, names)
+}
+
+/// Compiled SQL plus the values to bind, in placeholder order
+pub fn bind(
+    sql: &str,
+    db_type: &str,
+    values: &HashMap<String, Value>,
+) -> Result<(String, Vec<Value>), String> {
+    let (compiled, names) = compile(sql, db_type);
+    let params = names
+        .iter()
+        .map(|name| {
+            values
+                .get(name)
+                .cloned()
+                .ok_or_else(|| format!("缺少参数: {}", name))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((compiled, params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compile_placeholders() {
+        let cases = [
+            (
+                "mysql",
+                "SELECT * FROM t WHERE a = :a AND b = ${b} OR c = :a",
+                "SELECT * FROM t WHERE a = ? AND b = ? OR c = ?",
+                vec!["a", "b", "a"],
+            ),
+            (
+                "postgresql",
+                "SELECT * FROM t WHERE a = :a AND b = ${b} OR c = :a",
+                "SELECT * FROM t WHERE a = $1 AND b = $2 OR c = $3",
+                vec!["a", "b", "a"],
+            ),
+            (
+                "mysql",
+                "SELECT ':x', \"${y}\", `:z`, 'it\\'s :w' FROM t WHERE c = :c",
+                "SELECT ':x', \"${y}\", `:z`, 'it\\'s :w' FROM t WHERE c = ?",
+                vec!["c"],
+            ),
+            (
+                "mysql",
+                "-- :a\nSELECT /* :b */ :c # :d\nFROM t",
+                "-- :a\nSELECT /* :b */ ? # :d\nFROM t",
+                vec!["c"],
+            ),
+            // `#` is an operator, not a comment, on PostgreSQL
+            (
+                "postgresql",
+                "SELECT :a # :b",
+                "SELECT $1 # $2",
+                vec!["a", "b"],
+            ),
+            (
+                "postgresql",
+                "SELECT :v::int, created_at::date FROM t",
+                "SELECT $1::int, created_at::date FROM t",
+                vec!["v"],
+            ),
+            (
+                "postgresql",
+                "DO $$ BEGIN PERFORM :x; END $$; SELECT $fn$ ${y} $fn$, :z",
+                "DO $$ BEGIN PERFORM :x; END $$; SELECT $fn$ ${y} $fn$, $1",
+                vec!["z"],
+            ),
+            ("postgresql", "SELECT $$ :x", "SELECT $$ :x", vec![]),
+            (
+                "postgresql",
+                "SELECT $1, '10:30', ${}, ${a b}",
+                "SELECT $1, '10:30', ${}, ${a b}",
+                vec![],
+            ),
+            (
+                "mysql",
+                "SELECT price$ FROM t WHERE 10:30 = :t_1",
+                "SELECT price$ FROM t WHERE 10:30 = ?",
+                vec!["t_1"],
+            ),
+        ];
+        for (db_type, sql, expected, names) in cases {
+            let (compiled, params) = compile(sql, db_type);
+            assert_eq!(compiled, expected, "{}", sql);
+            assert_eq!(params, names, "{}", sql);
+        }
+    }
+
+    #[test]
+    fn bind_orders_values_by_placeholder() {
+        let values: HashMap<String, Value> = [
+            ("a".to_string(), Value::from(1)),
+            ("b".to_string(), Value::from("x")),
+        ]
+        .into_iter()
+        .collect();
+        let (sql, params) = bind("SELECT :b, :a, :b", "postgresql", &values).unwrap();
+        assert_eq!(sql, "SELECT $1, $2, $3");
+        assert_eq!(
+            params,
+            vec![Value::from("x"), Value::from(1), Value::from("x")]
+        );
+        assert_eq!(
+            bind("SELECT :c", "mysql", &values).unwrap_err(),
+            "缺少参数: c"
+        );
+    }
+}
@@ -0,0 +1,445 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::sql_conn::SqlConnection;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SqlProcess {
+    pub id: i64,
+    pub user: Option<String>,
+    pub host: Option<String>,
+    pub database: Option<String>,
+    pub command: Option<String>, // MySQL Command (Query, Sleep ...) / PostgreSQL backend_type
+    pub state: Option<String>,   // MySQL State / PostgreSQL state (active, idle in transaction ...)
+    pub wait: Option<String>,    // PostgreSQL wait_event_type:wait_event
+    pub duration_secs: Option<f64>, // Time spent in the current query or state
+    pub query: Option<String>,
+}
+
+/// Column of a row by name, ignoring case (SHOW output is capitalized)
+pub fn field<'a>(row: &'a [(String, Value)], name: &str) -> &'a Value {
+    row.iter()
+        .find(|(col, _)| col.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v)
+        .unwrap_or(&Value::Null)
+}
+
+pub fn text_field(row: &[(String, Value)], name: &str) -> Option<String> {
+    match field(row, name) {
+        Value::Null => None,
+        Value::String(s) if s.is_empty() => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+pub fn num_field(row: &[(String, Value)], name: &str) -> Option<f64> {
+    match field(row, name) {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+// Columns are aliased to the SHOW FULL PROCESSLIST names so one parser reads all three
+const MYSQL_THREADS: &str = "SELECT PROCESSLIST_ID AS Id, PROCESSLIST_USER AS User,
+        PROCESSLIST_HOST AS Host, PROCESSLIST_DB AS db, PROCESSLIST_COMMAND AS Command,
+        PROCESSLIST_TIME AS Time, PROCESSLIST_STATE AS State, PROCESSLIST_INFO AS Info
+    FROM performance_schema.threads
+    WHERE PROCESSLIST_ID IS NOT NULL AND PROCESSLIST_ID <> CONNECTION_ID()";
+
+const PG_ACTIVITY: &str = "SELECT pid::int8 AS id, usename AS user,
+        COALESCE(client_hostname, host(client_addr)) || COALESCE(':' || client_port, '') AS host,
+        datname AS db, backend_type AS command, state,
+        wait_event_type || ':' || wait_event AS wait,
+        EXTRACT(EPOCH FROM now() - CASE WHEN state = 'active' THEN query_start
+            ELSE COALESCE(state_change, backend_start) END)::float8 AS time,
+        query AS info
+    FROM pg_stat_activity
+    WHERE pid <> pg_backend_pid()";
+
+/// Sessions on the server except the one making the request, longest running first
+pub async fn processes(conn: &mut SqlConnection) -> Result<Vec<SqlProcess>, String> {
+    let rows = match conn {
+        // performance_schema.threads doesn't take the global mutex SHOW PROCESSLIST does,
+        // but it may be disabled
+        SqlConnection::MySql(_) => match conn.fetch_all(MYSQL_THREADS).await {
+            Ok(rows) => rows,
+            Err(_) => {
+                let own = conn.fetch_all("SELECT CONNECTION_ID() AS Id").await?;
+                let own_id = own.first().and_then(|row| num_field(row, "Id"));
+                conn.fetch_all("SHOW FULL PROCESSLIST")
+                    .await?
+                    .into_iter()
+                    .filter(|row| num_field(row, "Id") != own_id)
+                    .collect()
+            }
+        },
+        SqlConnection::Postgres(_) => conn.fetch_all(PG_ACTIVITY).await?,
+    };
+
+    let mut list: Vec<SqlProcess> = rows
+        .iter()
+        .map(|row| SqlProcess {
+            id: num_field(row, "Id").unwrap_or(0.0) as i64,
+            user: text_field(row, "User"),
+            host: text_field(row, "Host"),
+            database: text_field(row, "db"),
+            command: text_field(row, "Command"),
+            state: text_field(row, "State"),
+            wait: text_field(row, "wait"),
+            duration_secs: num_field(row, "Time"),
+            query: text_field(row, "Info"),
+        })
+        .collect();
+    list.sort_by(|a, b| {
+        b.duration_secs
+            .unwrap_or(0.0)
+            .total_cmp(&a.duration_secs.unwrap_or(0.0))
+    });
+    Ok(list)
+}
+
+/// Cancel the running query of a session (`mode` "query") or close it ("connection")
+pub async fn kill_process(conn: &mut SqlConnection, id: i64, mode: &str) -> Result<(), String> {
+    if mode != "query" && mode != "connection" {
+        return Err(format!("Unsupported kill mode: {}", mode));
+    }
+    match conn {
+        SqlConnection::MySql(_) => {
+            let sql = if mode == "query" {
+                format!("KILL QUERY {}", id)
+            } else {
+                format!("KILL CONNECTION {}", id)
+            };
+            conn.execute(&sql).await?;
+            Ok(())
+        }
+        SqlConnection::Postgres(pg) => {
+            let sql = if mode == "query" {
+                "SELECT pg_cancel_backend($1)"
+            } else {
+                "SELECT pg_terminate_backend($1)"
+            };
+            let signalled: bool = sqlx::query_scalar(sql)
+                .bind(id as i32)
+                .fetch_one(pg)
+                .await
+                .map_err(|e| e.to_string())?;
+            if !signalled {
+                return Err(format!("Process {} not found", id));
+            }
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ReplicationStatus {
+    pub name: String, // Channel / standby application name, "standby" on a PostgreSQL replica
+    pub state: Option<String>,
+    pub lag_secs: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LockWait {
+    pub waiting_id: i64,
+    pub waiting_query: Option<String>,
+    pub blocking_id: i64,
+    pub blocking_query: Option<String>,
+    pub wait_secs: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ServerHealth {
+    pub db_type: String,
+    pub version: String,
+    pub uptime_secs: Option<f64>,
+    pub connections: Option<f64>,
+    pub active_connections: Option<f64>,
+    pub max_connections: Option<f64>,
+    pub sampled_at: i64,                 // unix millis
+    pub queries_total: Option<f64>,      // Questions / pg_stat_statements calls since start
+    pub transactions_total: Option<f64>, // Commits + rollbacks since start
+    pub qps: Option<f64>,                // Rates since the `previous` sample, if given
+    pub tps: Option<f64>,
+    pub cache_hit_ratio: Option<f64>, // 0..1, InnoDB buffer pool / shared buffers
+    pub replication: Vec<ReplicationStatus>,
+    pub lock_waits: Vec<LockWait>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatementStat {
+    pub query: String,
+    pub database: Option<String>,
+    pub calls: f64,
+    pub total_time_ms: f64,
+    pub mean_time_ms: f64,
+    pub rows: Option<f64>,
+}
+
+/// Rows of an optional view (replication, sys schema ...): missing objects or
+/// privileges just mean there is nothing to show
+async fn optional_rows(conn: &mut SqlConnection, sql: &str) -> Vec<Vec<(String, Value)>> {
+    conn.fetch_all(sql).await.unwrap_or_default()
+}
+
+async fn first_row(conn: &mut SqlConnection, sql: &str) -> Result<Vec<(String, Value)>, String> {
+    Ok(conn
+        .fetch_all(sql)
+        .await?
+        .into_iter()
+        .next()
+        .unwrap_or_default())
+}
+
+async fn mysql_health(conn: &mut SqlConnection, health: &mut ServerHealth) -> Result<(), String> {
+    let vars = first_row(
+        conn,
+        "SELECT VERSION() AS version, @@max_connections AS max_connections",
+    )
+    .await?;
+    health.version = text_field(&vars, "version").unwrap_or_default();
+    health.max_connections = num_field(&vars, "max_connections");
+
+    let status: Vec<(String, Value)> = conn
+        .fetch_all("SHOW GLOBAL STATUS")
+        .await?
+        .iter()
+        .filter_map(|row| {
+            Some((
+                text_field(row, "Variable_name")?,
+                field(row, "Value").clone(),
+            ))
+        })
+        .collect();
+    let stat = |name: &str| num_field(&status, name);
+    health.uptime_secs = stat("Uptime");
+    health.connections = stat("Threads_connected");
+    health.active_connections = stat("Threads_running");
+    health.queries_total = stat("Questions");
+    health.transactions_total = match (stat("Com_commit"), stat("Com_rollback")) {
+        (Some(c), Some(r)) => Some(c + r),
+        _ => None,
+    };
+    if let (Some(requests), Some(reads)) = (
+        stat("Innodb_buffer_pool_read_requests"),
+        stat("Innodb_buffer_pool_reads"),
+    ) {
+        if requests > 0.0 {
+            health.cache_hit_ratio = Some(1.0 - reads / requests);
+        }
+    }
+
+    // SHOW REPLICA STATUS is 8.0.22+, older servers only know the SLAVE spelling
+    let mut replicas = optional_rows(conn, "SHOW REPLICA STATUS").await;
+    if replicas.is_empty() {
+        replicas = optional_rows(conn, "SHOW SLAVE STATUS").await;
+    }
+    health.replication = replicas
+        .iter()
+        .map(|row| {
+            let io = text_field(row, "Replica_IO_Running").or(text_field(row, "Slave_IO_Running"));
+            let sql =
+                text_field(row, "Replica_SQL_Running").or(text_field(row, "Slave_SQL_Running"));
+            ReplicationStatus {
+                name: text_field(row, "Channel_Name")
+                    .or(text_field(row, "Source_Host"))
+                    .or(text_field(row, "Master_Host"))
+                    .unwrap_or_default(),
+                state: Some(format!(
+                    "IO: {}, SQL: {}",
+                    io.unwrap_or_default(),
+                    sql.unwrap_or_default()
+                )),
+                lag_secs: num_field(row, "Seconds_Behind_Source")
+                    .or(num_field(row, "Seconds_Behind_Master")),
+            }
+        })
+        .collect();
+
+    health.lock_waits = optional_rows(
+        conn,
+        "SELECT waiting_pid, waiting_query, blocking_pid, blocking_query, wait_age_secs
+         FROM sys.innodb_lock_waits ORDER BY wait_age_secs DESC",
+    )
+    .await
+    .iter()
+    .map(|row| LockWait {
+        waiting_id: num_field(row, "waiting_pid").unwrap_or(0.0) as i64,
+        waiting_query: text_field(row, "waiting_query"),
+        blocking_id: num_field(row, "blocking_pid").unwrap_or(0.0) as i64,
+        blocking_query: text_field(row, "blocking_query"),
+        wait_secs: num_field(row, "wait_age_secs"),
+    })
+    .collect();
+    Ok(())
+}
+
+async fn pg_health(conn: &mut SqlConnection, health: &mut ServerHealth) -> Result<(), String> {
+    let row = first_row(
+        conn,
+        "SELECT current_setting('server_version') AS version,
+            EXTRACT(EPOCH FROM now() - pg_postmaster_start_time())::float8 AS uptime,
+            current_setting('max_connections')::float8 AS max_connections,
+            (SELECT count(*) FROM pg_stat_activity WHERE backend_type = 'client backend')::float8 AS connections,
+            (SELECT count(*) FROM pg_stat_activity WHERE state = 'active')::float8 AS active,
+            (SELECT sum(xact_commit + xact_rollback) FROM pg_stat_database)::float8 AS transactions,
+            (SELECT sum(blks_hit)::float8 / NULLIF(sum(blks_hit + blks_read), 0)
+                FROM pg_stat_database) AS hit_ratio,
+            pg_is_in_recovery() AS in_recovery",
+    )
+    .await?;
+    health.version = text_field(&row, "version").unwrap_or_default();
+    health.uptime_secs = num_field(&row, "uptime");
+    health.max_connections = num_field(&row, "max_connections");
+    health.connections = num_field(&row, "connections");
+    health.active_connections = num_field(&row, "active");
+    health.transactions_total = num_field(&row, "transactions");
+    health.cache_hit_ratio = num_field(&row, "hit_ratio");
+
+    // PostgreSQL has no global query counter; pg_stat_statements has one when installed
+    health.queries_total = optional_rows(
+        conn,
+        "SELECT sum(calls)::float8 AS calls FROM pg_stat_statements",
+    )
+    .await
+    .first()
+    .and_then(|row| num_field(row, "calls"));
+
+    health.replication = if field(&row, "in_recovery") == &Value::Bool(true) {
+        optional_rows(
+            conn,
+            "SELECT 'standby' AS name, status AS state,
+                EXTRACT(EPOCH FROM now() - pg_last_xact_replay_timestamp())::float8 AS lag
+             FROM pg_stat_wal_receiver",
+        )
+        .await
+    } else {
+        optional_rows(
+            conn,
+            "SELECT COALESCE(NULLIF(application_name, ''), host(client_addr)) AS name, state,
+                EXTRACT(EPOCH FROM replay_lag)::float8 AS lag
+             FROM pg_stat_replication",
+        )
+        .await
+    }
+    .iter()
+    .map(|row| ReplicationStatus {
+        name: text_field(row, "name").unwrap_or_default(),
+        state: text_field(row, "state"),
+        lag_secs: num_field(row, "lag"),
+    })
+    .collect();
+
+    health.lock_waits = optional_rows(
+        conn,
+        "SELECT w.pid::int8 AS waiting_id, w.query AS waiting_query,
+            b.pid::int8 AS blocking_id, b.query AS blocking_query,
+            EXTRACT(EPOCH FROM now() - w.query_start)::float8 AS wait_secs
+         FROM pg_stat_activity w
+         CROSS JOIN LATERAL unnest(pg_blocking_pids(w.pid)) AS blocker(pid)
+         JOIN pg_stat_activity b ON b.pid = blocker.pid
+         ORDER BY wait_secs DESC",
+    )
+    .await
+    .iter()
+    .map(|row| LockWait {
+        waiting_id: num_field(row, "waiting_id").unwrap_or(0.0) as i64,
+        waiting_query: text_field(row, "waiting_query"),
+        blocking_id: num_field(row, "blocking_id").unwrap_or(0.0) as i64,
+        blocking_query: text_field(row, "blocking_query"),
+        wait_secs: num_field(row, "wait_secs"),
+    })
+    .collect();
+    Ok(())
+}
+
+/// One sample of server health. Passing the previous sample fills in QPS / TPS
+/// from the counter deltas, so the caller can poll this on an interval.
+pub async fn health(
+    conn: &mut SqlConnection,
+    previous: Option<&ServerHealth>,
+) -> Result<ServerHealth, String> {
+    let mut health = ServerHealth {
+        db_type: conn.db_type().to_string(),
+        sampled_at: chrono::Utc::now().timestamp_millis(),
+        ..Default::default()
+    };
+    match conn {
+        SqlConnection::MySql(_) => mysql_health(conn, &mut health).await?,
+        SqlConnection::Postgres(_) => pg_health(conn, &mut health).await?,
+    }
+
+    if let Some(prev) = previous.filter(|p| p.db_type == health.db_type) {
+        let secs = (health.sampled_at - prev.sampled_at) as f64 / 1000.0;
+        let rate = |now: Option<f64>, before: Option<f64>| match (now, before) {
+            // A counter going backwards means the server restarted in between
+            (Some(now), Some(before)) if secs > 0.0 && now >= before => Some((now - before) / secs),
+            _ => None,
+        };
+        health.qps = rate(health.queries_total, prev.queries_total);
+        health.tps = rate(health.transactions_total, prev.transactions_total);
+    }
+    Ok(health)
+}
+
+/// Most expensive statements by total time, from performance_schema (MySQL)
+/// or pg_stat_statements (PostgreSQL)
+pub async fn top_statements(
+    conn: &mut SqlConnection,
+    limit: usize,
+) -> Result<Vec<StatementStat>, String> {
+    let rows = match conn {
+        // Timer columns are in picoseconds
+        SqlConnection::MySql(_) => conn
+            .fetch_all(&format!(
+                "SELECT DIGEST_TEXT AS query, SCHEMA_NAME AS db, COUNT_STAR AS calls,
+                    CAST(SUM_TIMER_WAIT / 1000000000 AS DOUBLE) AS total_ms,
+                    CAST(AVG_TIMER_WAIT / 1000000000 AS DOUBLE) AS mean_ms,
+                    SUM_ROWS_SENT AS row_count
+                 FROM performance_schema.events_statements_summary_by_digest
+                 WHERE DIGEST_TEXT IS NOT NULL
+                 ORDER BY SUM_TIMER_WAIT DESC LIMIT {}",
+                limit
+            ))
+            .await
+            .map_err(|e| format!("无法读取 performance_schema: {}", e))?,
+        SqlConnection::Postgres(_) => {
+            // total_exec_time is 13+, older versions call it total_time
+            let query = |total: &str, mean: &str| {
+                format!(
+                    "SELECT s.query, d.datname AS db, s.calls::float8 AS calls,
+                        s.{total}::float8 AS total_ms, s.{mean}::float8 AS mean_ms,
+                        s.rows::float8 AS row_count
+                     FROM pg_stat_statements s LEFT JOIN pg_database d ON d.oid = s.dbid
+                     ORDER BY s.{total} DESC LIMIT {limit}",
+                    total = total,
+                    mean = mean,
+                    limit = limit
+                )
+            };
+            match conn
+                .fetch_all(&query("total_exec_time", "mean_exec_time"))
+                .await
+            {
+                Ok(rows) => rows,
+                Err(_) => conn
+                    .fetch_all(&query("total_time", "mean_time"))
+                    .await
+                    .map_err(|e| format!("pg_stat_statements 扩展未安装或不可用: {}", e))?,
+            }
+        }
+    };
+    Ok(rows
+        .iter()
+        .map(|row| StatementStat {
+            query: text_field(row, "query").unwrap_or_default(),
+            database: text_field(row, "db"),
+            calls: num_field(row, "calls").unwrap_or(0.0),
+            total_time_ms: num_field(row, "total_ms").unwrap_or(0.0),
+            mean_time_ms: num_field(row, "mean_ms").unwrap_or(0.0),
+            rows: num_field(row, "row_count"),
+        })
+        .collect())
+}
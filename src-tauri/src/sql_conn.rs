@@ -0,0 +1,359 @@
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use serde_json::Value;
+use sqlx::mysql::{MySqlConnectOptions, MySqlConnection};
+use sqlx::postgres::{PgConnectOptions, PgConnection};
+use sqlx::{Column, ConnectOptions, Connection, Executor, TypeInfo};
+
+use crate::sql_dialect;
+use crate::sql_value;
+use crate::ConnectionConfig;
+
+/// A single MySQL or PostgreSQL connection for the background jobs
+/// (export, import, dump, copy ...) that need to hold one open.
+pub enum SqlConnection {
+    MySql(MySqlConnection),
+    Postgres(PgConnection),
+}
+
+impl SqlConnection {
+    /// Connect to `database`, or the connection's default database when not given
+    pub async fn connect(
+        config: &ConnectionConfig,
+        database: Option<&str>,
+    ) -> Result<SqlConnection, String> {
+        let database = database
+            .filter(|db| !db.is_empty())
+            .or(config.database.as_deref())
+            .filter(|db| !db.is_empty());
+
+        match config.db_type.as_str() {
+            "mysql" => {
+                let mut opts = MySqlConnectOptions::new()
+                    .host(&config.host)
+                    .port(config.port);
+                if let Some(user) = &config.username {
+                    opts = opts.username(user);
+                }
+                if let Some(pass) = &config.password {
+                    opts = opts.password(pass);
+                }
+                if let Some(db) = database {
+                    opts = opts.database(db);
+                }
+                let conn = opts.connect().await.map_err(|e| e.to_string())?;
+                Ok(SqlConnection::MySql(conn))
+            }
+            "postgresql" => {
+                let mut opts = PgConnectOptions::new().host(&config.host).port(config.port);
+                if let Some(user) = &config.username {
+                    opts = opts.username(user);
+                }
+                if let Some(pass) = &config.password {
+                    opts = opts.password(pass);
+                }
+                if let Some(db) = database {
+                    opts = opts.database(db);
+                }
+                let conn = opts.connect().await.map_err(|e| e.to_string())?;
+                Ok(SqlConnection::Postgres(conn))
+            }
+            other => Err(format!("Unsupported database type: {}", other)),
+        }
+    }
+
+    /// "mysql" or "postgresql", matching `ConnectionConfig.db_type`
+    pub fn db_type(&self) -> &'static str {
+        match self {
+            SqlConnection::MySql(_) => "mysql",
+            SqlConnection::Postgres(_) => "postgresql",
+        }
+    }
+
+    /// Run a statement without results, returning the affected row count
+    pub async fn execute(&mut self, sql: &str) -> Result<u64, String> {
+        // A plain &str runs unprepared: these are one-off DDL / script statements
+        match self {
+            SqlConnection::MySql(conn) => conn
+                .execute(sql)
+                .await
+                .map(|r| r.rows_affected())
+                .map_err(|e| e.to_string()),
+            SqlConnection::Postgres(conn) => conn
+                .execute(sql)
+                .await
+                .map(|r| r.rows_affected())
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Exact column types (`format_type`) of a PostgreSQL table, empty for MySQL
+    pub async fn pg_column_types(&mut self, table: &str) -> Result<Vec<(String, String)>, String> {
+        let SqlConnection::Postgres(pg) = self else {
+            return Ok(Vec::new());
+        };
+        sqlx::query_as(
+            "SELECT a.attname::text, format_type(a.atttypid, a.atttypmod)
+             FROM pg_attribute a
+             WHERE a.attrelid = $1::regclass AND a.attnum > 0 AND NOT a.attisdropped
+             ORDER BY a.attnum",
+        )
+        .bind(sql_dialect::quote_ident("postgresql", table))
+        .fetch_all(pg)
+        .await
+        .map_err(|e| e.to_string())
+    }
+
+    /// Declared column types of a table including lengths, e.g. `varchar(20)`
+    pub async fn column_types(&mut self, table: &str) -> Result<Vec<(String, String)>, String> {
+        let SqlConnection::MySql(my) = self else {
+            return self.pg_column_types(table).await;
+        };
+        sqlx::query_as(
+            "SELECT CONVERT(COLUMN_NAME USING utf8mb4), CONVERT(COLUMN_TYPE USING utf8mb4)
+             FROM information_schema.COLUMNS
+             WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?
+             ORDER BY ORDINAL_POSITION",
+        )
+        .bind(table)
+        .fetch_all(my)
+        .await
+        .map_err(|e| e.to_string())
+    }
+
+    /// Run a statement with `?` / `$n` parameters bound as text (NULL for `None`)
+    pub async fn execute_params(
+        &mut self,
+        sql: &str,
+        params: &[Option<String>],
+    ) -> Result<u64, String> {
+        match self {
+            SqlConnection::MySql(conn) => {
+                let mut query = sqlx::query(sql);
+                for p in params {
+                    query = query.bind(p.clone());
+                }
+                query.execute(conn).await.map(|r| r.rows_affected())
+            }
+            SqlConnection::Postgres(conn) => {
+                let mut query = sqlx::query(sql);
+                for p in params {
+                    query = query.bind(p.clone());
+                }
+                query.execute(conn).await.map(|r| r.rows_affected())
+            }
+        }
+        .map_err(|e| e.to_string())
+    }
+
+    /// Like `execute_params`, but inside its own transaction that is rolled back on error
+    pub async fn execute_in_transaction(
+        &mut self,
+        sql: &str,
+        params: &[Option<String>],
+    ) -> Result<u64, String> {
+        match self {
+            SqlConnection::MySql(conn) => {
+                let mut tx = conn.begin().await.map_err(|e| e.to_string())?;
+                let mut query = sqlx::query(sql);
+                for p in params {
+                    query = query.bind(p.clone());
+                }
+                let affected = query
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .rows_affected();
+                tx.commit().await.map_err(|e| e.to_string())?;
+                Ok(affected)
+            }
+            SqlConnection::Postgres(conn) => {
+                let mut tx = conn.begin().await.map_err(|e| e.to_string())?;
+                let mut query = sqlx::query(sql);
+                for p in params {
+                    query = query.bind(p.clone());
+                }
+                let affected = query
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .rows_affected();
+                tx.commit().await.map_err(|e| e.to_string())?;
+                Ok(affected)
+            }
+        }
+    }
+
+    /// Run a query with typed parameters and collect its rows. JSON numbers, booleans
+    /// and null bind as their SQL counterparts; anything else binds as text.
+    pub async fn fetch_all_params(
+        &mut self,
+        sql: &str,
+        params: &[Value],
+    ) -> Result<Vec<Vec<(String, Value)>>, String> {
+        match self {
+            SqlConnection::MySql(conn) => {
+                let mut query = sqlx::query(sql);
+                for p in params {
+                    query = match p {
+                        Value::Null => query.bind(None::<String>),
+                        Value::Bool(b) => query.bind(*b),
+                        Value::Number(n) if n.is_i64() => query.bind(n.as_i64()),
+                        Value::Number(n) => query.bind(n.as_f64()),
+                        Value::String(s) => query.bind(s.clone()),
+                        other => query.bind(other.to_string()),
+                    };
+                }
+                let rows = query.fetch_all(conn).await.map_err(|e| e.to_string())?;
+                Ok(rows
+                    .iter()
+                    .map(|r| sql_value::mysql_row_values(r, false))
+                    .collect())
+            }
+            SqlConnection::Postgres(conn) => {
+                let mut query = sqlx::query(sql);
+                for p in params {
+                    query = match p {
+                        Value::Null => query.bind(None::<String>),
+                        Value::Bool(b) => query.bind(*b),
+                        Value::Number(n) if n.is_i64() => query.bind(n.as_i64()),
+                        Value::Number(n) => query.bind(n.as_f64()),
+                        Value::String(s) => query.bind(s.clone()),
+                        other => query.bind(other.to_string()),
+                    };
+                }
+                let rows = query.fetch_all(conn).await.map_err(|e| e.to_string())?;
+                Ok(rows
+                    .iter()
+                    .map(|r| sql_value::pg_row_values(r, false))
+                    .collect())
+            }
+        }
+    }
+
+    /// Run a query unprepared and collect its rows, for statements such as SHOW
+    /// that MySQL can't prepare
+    pub async fn fetch_all(&mut self, sql: &str) -> Result<Vec<Vec<(String, Value)>>, String> {
+        match self {
+            SqlConnection::MySql(conn) => {
+                let rows = conn.fetch_all(sql).await.map_err(|e| e.to_string())?;
+                Ok(rows
+                    .iter()
+                    .map(|r| sql_value::mysql_row_values(r, false))
+                    .collect())
+            }
+            SqlConnection::Postgres(conn) => {
+                let rows = conn.fetch_all(sql).await.map_err(|e| e.to_string())?;
+                Ok(rows
+                    .iter()
+                    .map(|r| sql_value::pg_row_values(r, false))
+                    .collect())
+            }
+        }
+    }
+
+    /// Column names and type names a query returns, from its description without running it
+    pub async fn result_types(&mut self, sql: &str) -> Result<Vec<(String, String)>, String> {
+        Ok(match self {
+            SqlConnection::MySql(conn) => conn
+                .describe(sql)
+                .await
+                .map_err(|e| e.to_string())?
+                .columns()
+                .iter()
+                .map(|c| (c.name().to_string(), c.type_info().name().to_string()))
+                .collect(),
+            SqlConnection::Postgres(conn) => conn
+                .describe(sql)
+                .await
+                .map_err(|e| e.to_string())?
+                .columns()
+                .iter()
+                .map(|c| (c.name().to_string(), c.type_info().name().to_string()))
+                .collect(),
+        })
+    }
+
+    /// Source (table, column) of each column a query returns, where the server reports one.
+    /// Computed columns have none, and MySQL results carry no origin here at all.
+    pub async fn result_sources(
+        &mut self,
+        sql: &str,
+    ) -> Result<Vec<(String, Option<(String, String)>)>, String> {
+        match self {
+            SqlConnection::MySql(conn) => Ok(conn
+                .describe(sql)
+                .await
+                .map_err(|e| e.to_string())?
+                .columns()
+                .iter()
+                .map(|c| (c.name().to_string(), None))
+                .collect()),
+            SqlConnection::Postgres(conn) => {
+                let origins: Vec<_> = conn
+                    .describe(sql)
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .columns()
+                    .iter()
+                    .map(|c| {
+                        let origin = c.relation_id().zip(c.relation_attribute_no());
+                        (c.name().to_string(), origin)
+                    })
+                    .collect();
+                let mut sources = Vec::with_capacity(origins.len());
+                for (name, origin) in origins {
+                    let source = match origin {
+                        Some((relation, attnum)) => sqlx::query_as(
+                            "SELECT c.relname::text, a.attname::text
+                             FROM pg_class c JOIN pg_attribute a ON a.attrelid = c.oid
+                             WHERE c.oid = $1 AND a.attnum = $2",
+                        )
+                        .bind(relation)
+                        .bind(attnum)
+                        .fetch_optional(&mut *conn)
+                        .await
+                        .map_err(|e| e.to_string())?,
+                        None => None,
+                    };
+                    sources.push((name, source));
+                }
+                Ok(sources)
+            }
+        }
+    }
+
+    /// Names of the binary columns a query returns
+    pub async fn binary_columns(&mut self, sql: &str) -> Result<Vec<String>, String> {
+        Ok(self
+            .result_types(sql)
+            .await?
+            .into_iter()
+            .filter(|(_, type_name)| sql_value::is_binary_type(type_name))
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    /// Stream the rows of a query as (column, value) pairs without loading them all
+    pub fn fetch_values<'a>(
+        &'a mut self,
+        sql: &'a str,
+    ) -> BoxStream<'a, Result<Vec<(String, Value)>, String>> {
+        match self {
+            SqlConnection::MySql(conn) => sqlx::query(sql)
+                .fetch(conn)
+                .map(|row| {
+                    row.map(|r| sql_value::mysql_row_values(&r, true))
+                        .map_err(|e| e.to_string())
+                })
+                .boxed(),
+            SqlConnection::Postgres(conn) => sqlx::query(sql)
+                .fetch(conn)
+                .map(|row| {
+                    row.map(|r| sql_value::pg_row_values(&r, true))
+                        .map_err(|e| e.to_string())
+                })
+                .boxed(),
+        }
+    }
+}
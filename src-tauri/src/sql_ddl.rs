@@ -0,0 +1,1479 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection, Executor};
+
+use crate::sql_conn::SqlConnection;
+use crate::sql_dialect::{quote_ident, quote_str};
+use crate::{AlterOperation, ColumnDef, IndexDef};
+
+const MYSQL_INDEX_METHODS: &[&str] = &["BTREE", "HASH", "FULLTEXT", "SPATIAL"];
+const PG_INDEX_METHODS: &[&str] = &["BTREE", "HASH", "GIN", "GIST", "BRIN", "SPGIST"];
+
+/// Accepted ON DELETE / ON UPDATE actions
+const REFERENTIAL_ACTIONS: &[&str] = &[
+    "CASCADE",
+    "SET NULL",
+    "SET DEFAULT",
+    "RESTRICT",
+    "NO ACTION",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ForeignKeyDef {
+    pub name: Option<String>,
+    pub columns: Vec<String>,
+    pub ref_table: String,
+    pub ref_columns: Vec<String>,
+    pub on_delete: Option<String>, // "CASCADE", "SET NULL", "SET DEFAULT", "RESTRICT", "NO ACTION"
+    pub on_update: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CheckDef {
+    pub name: Option<String>,
+    pub expression: String, // SQL boolean expression, e.g. "price >= 0"
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TableSpec {
+    pub name: String,
+    pub columns: Vec<ColumnDef>, // Primary key from `is_pk`
+    #[serde(default)]
+    pub indexes: Vec<IndexDef>,
+    #[serde(default)]
+    pub foreign_keys: Vec<ForeignKeyDef>,
+    #[serde(default)]
+    pub checks: Vec<CheckDef>,
+    pub comment: Option<String>,
+    pub engine: Option<String>,       // MySQL, e.g. "InnoDB"
+    pub charset: Option<String>,      // MySQL
+    pub collation: Option<String>,    // MySQL
+    pub tablespace: Option<String>,   // PostgreSQL
+    pub partition_by: Option<String>, // Clause after PARTITION BY, e.g. "RANGE (created_at)"
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct DatabaseOptions {
+    pub charset: Option<String>,   // MySQL CHARACTER SET
+    pub collation: Option<String>, // MySQL COLLATE / PostgreSQL LC_COLLATE
+    pub owner: Option<String>,     // PostgreSQL
+    pub template: Option<String>,  // PostgreSQL, e.g. "template0"
+    pub encoding: Option<String>,  // PostgreSQL, e.g. "UTF8"
+}
+
+/// Charset, collation and encoding names end up unquoted in the DDL
+fn check_name(kind: &str, name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-.".contains(c))
+    {
+        return Err(format!("无效的{}: {}", kind, name));
+    }
+    Ok(())
+}
+
+fn non_empty(v: &Option<String>) -> Option<&str> {
+    v.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// Destructive operations need the object's name typed back as confirmation
+pub fn check_confirmation(name: &str, confirm: Option<&str>) -> Result<(), String> {
+    if confirm != Some(name) {
+        return Err(format!("请输入 {} 以确认此操作", name));
+    }
+    Ok(())
+}
+
+/// Run statements in order, naming the one that failed
+pub async fn run_statements(conn: &mut SqlConnection, statements: &[String]) -> Result<(), String> {
+    for (i, sql) in statements.iter().enumerate() {
+        conn.execute(sql)
+            .await
+            .map_err(|e| format!("第 {} 条语句执行失败 ({}): {}", i + 1, sql, e))?;
+    }
+    Ok(())
+}
+
+/// Run statements in one transaction on PostgreSQL, where DDL is transactional, and
+/// one by one on MySQL, where each DDL statement commits implicitly
+pub async fn run_transaction(
+    conn: &mut SqlConnection,
+    statements: &[String],
+) -> Result<(), String> {
+    let SqlConnection::Postgres(pg) = conn else {
+        return run_statements(conn, statements).await;
+    };
+    let mut tx = pg.begin().await.map_err(|e| e.to_string())?;
+    for (i, sql) in statements.iter().enumerate() {
+        if let Err(e) = tx.execute(sql.as_str()).await {
+            // Dropping the transaction rolls it back
+            return Err(format!(
+                "第 {} 条语句执行失败 ({}): {}，所有更改已回滚",
+                i + 1,
+                sql,
+                e
+            ));
+        }
+    }
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+pub fn create_database_sql(
+    db_type: &str,
+    name: &str,
+    options: &DatabaseOptions,
+) -> Result<Vec<String>, String> {
+    if name.trim().is_empty() {
+        return Err("数据库名不能为空".to_string());
+    }
+    let mut sql = format!("CREATE DATABASE {}", quote_ident(db_type, name));
+    match db_type {
+        "mysql" => {
+            if let Some(charset) = non_empty(&options.charset) {
+                check_name("字符集", charset)?;
+                sql.push_str(&format!(" CHARACTER SET {}", charset));
+            }
+            if let Some(collation) = non_empty(&options.collation) {
+                check_name("排序规则", collation)?;
+                sql.push_str(&format!(" COLLATE {}", collation));
+            }
+        }
+        _ => {
+            if let Some(owner) = non_empty(&options.owner) {
+                sql.push_str(&format!(" OWNER {}", quote_ident(db_type, owner)));
+            }
+            if let Some(template) = non_empty(&options.template) {
+                sql.push_str(&format!(" TEMPLATE {}", quote_ident(db_type, template)));
+            }
+            if let Some(encoding) = non_empty(&options.encoding) {
+                check_name("编码", encoding)?;
+                sql.push_str(&format!(" ENCODING {}", quote_str(db_type, encoding)));
+            }
+            if let Some(collation) = non_empty(&options.collation) {
+                sql.push_str(&format!(" LC_COLLATE {}", quote_str(db_type, collation)));
+            }
+        }
+    }
+    Ok(vec![sql])
+}
+
+pub fn drop_database_sql(db_type: &str, name: &str) -> Vec<String> {
+    vec![format!("DROP DATABASE {}", quote_ident(db_type, name))]
+}
+
+pub fn drop_table_sql(db_type: &str, table: &str, cascade: bool) -> Vec<String> {
+    let cascade = if cascade && db_type == "postgresql" {
+        " CASCADE"
+    } else {
+        ""
+    };
+    vec![format!(
+        "DROP TABLE {}{}",
+        quote_ident(db_type, table),
+        cascade
+    )]
+}
+
+/// `restart_identity` resets PostgreSQL sequences; MySQL always resets AUTO_INCREMENT
+pub fn truncate_table_sql(
+    db_type: &str,
+    table: &str,
+    restart_identity: bool,
+    cascade: bool,
+) -> Vec<String> {
+    let mut sql = format!("TRUNCATE TABLE {}", quote_ident(db_type, table));
+    if db_type == "postgresql" {
+        if restart_identity {
+            sql.push_str(" RESTART IDENTITY");
+        }
+        if cascade {
+            sql.push_str(" CASCADE");
+        }
+    }
+    vec![sql]
+}
+
+pub fn rename_table_sql(db_type: &str, table: &str, new_name: &str) -> Result<Vec<String>, String> {
+    if new_name.trim().is_empty() {
+        return Err("新表名不能为空".to_string());
+    }
+    let (from, to) = (quote_ident(db_type, table), quote_ident(db_type, new_name));
+    Ok(vec![match db_type {
+        "mysql" => format!("RENAME TABLE {} TO {}", from, to),
+        _ => format!("ALTER TABLE {} RENAME TO {}", from, to),
+    }])
+}
+
+/// Copy a table's structure (columns, indexes, defaults) and optionally its rows.
+/// On PostgreSQL serial columns of the copy keep using the source table's sequence.
+pub fn duplicate_table_sql(
+    db_type: &str,
+    table: &str,
+    new_name: &str,
+    with_data: bool,
+) -> Result<Vec<String>, String> {
+    if new_name.trim().is_empty() {
+        return Err("新表名不能为空".to_string());
+    }
+    let (from, to) = (quote_ident(db_type, table), quote_ident(db_type, new_name));
+    let mut statements = vec![match db_type {
+        "mysql" => format!("CREATE TABLE {} LIKE {}", to, from),
+        _ => format!("CREATE TABLE {} (LIKE {} INCLUDING ALL)", to, from),
+    }];
+    if with_data {
+        statements.push(format!("INSERT INTO {} SELECT * FROM {}", to, from));
+    }
+    Ok(statements)
+}
+
+fn quote_list(db_type: &str, names: &[String]) -> String {
+    names
+        .iter()
+        .map(|n| quote_ident(db_type, n))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn is_serial_type(type_name: &str) -> bool {
+    let lower = type_name.trim().to_lowercase();
+    [
+        "serial",
+        "serial2",
+        "serial4",
+        "serial8",
+        "smallserial",
+        "bigserial",
+    ]
+    .contains(&lower.as_str())
+}
+
+/// Whether an auto-increment column becomes an identity column on PostgreSQL.
+/// serial types already bring their own sequence.
+fn pg_identity(db_type: &str, col: &ColumnDef) -> bool {
+    db_type == "postgresql" && col.auto_increment == Some(true) && !is_serial_type(&col.type_name)
+}
+
+/// `"col" type NOT NULL DEFAULT x`, plus the inline COMMENT on MySQL
+pub fn column_definition(db_type: &str, col: &ColumnDef) -> Result<String, String> {
+    if col.name.trim().is_empty() || col.type_name.trim().is_empty() {
+        return Err("列名和类型不能为空".to_string());
+    }
+    let mut def = format!("{} {}", quote_ident(db_type, &col.name), col.type_name);
+    let identity = pg_identity(db_type, col);
+    if identity {
+        def.push_str(" GENERATED BY DEFAULT AS IDENTITY");
+    }
+    if col.is_nullable == Some(false) || col.is_pk {
+        def.push_str(" NOT NULL");
+    }
+    // Identity columns can't have a default
+    if let Some(default) = non_empty(&col.default_value).filter(|_| !identity) {
+        def.push_str(&format!(" DEFAULT {}", default));
+    }
+    if db_type == "mysql" && col.auto_increment == Some(true) {
+        def.push_str(" AUTO_INCREMENT");
+    }
+    if db_type == "mysql" {
+        if let Some(comment) = non_empty(&col.comment) {
+            def.push_str(&format!(" COMMENT {}", quote_str(db_type, comment)));
+        }
+    }
+    Ok(def)
+}
+
+fn referential_action(action: &Option<String>) -> Result<Option<String>, String> {
+    match non_empty(action) {
+        None => Ok(None),
+        Some(a) => {
+            let a = a
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_uppercase();
+            if !REFERENTIAL_ACTIONS.contains(&a.as_str()) {
+                return Err(format!("不支持的外键动作: {}", a));
+            }
+            Ok(Some(a))
+        }
+    }
+}
+
+/// `CONSTRAINT "name" FOREIGN KEY (...) REFERENCES "t" (...) ON DELETE ...`
+pub fn foreign_key_clause(db_type: &str, fk: &ForeignKeyDef) -> Result<String, String> {
+    if fk.columns.is_empty() || fk.columns.len() != fk.ref_columns.len() {
+        return Err("外键的列与引用列数量必须一致".to_string());
+    }
+    let mut clause = String::new();
+    if let Some(name) = non_empty(&fk.name) {
+        clause.push_str(&format!("CONSTRAINT {} ", quote_ident(db_type, name)));
+    }
+    clause.push_str(&format!(
+        "FOREIGN KEY ({}) REFERENCES {} ({})",
+        quote_list(db_type, &fk.columns),
+        quote_ident(db_type, &fk.ref_table),
+        quote_list(db_type, &fk.ref_columns)
+    ));
+    if let Some(action) = referential_action(&fk.on_delete)? {
+        clause.push_str(&format!(" ON DELETE {}", action));
+    }
+    if let Some(action) = referential_action(&fk.on_update)? {
+        clause.push_str(&format!(" ON UPDATE {}", action));
+    }
+    Ok(clause)
+}
+
+pub fn check_clause(db_type: &str, check: &CheckDef) -> Result<String, String> {
+    if check.expression.trim().is_empty() {
+        return Err("CHECK 约束表达式不能为空".to_string());
+    }
+    Ok(match non_empty(&check.name) {
+        Some(name) => format!(
+            "CONSTRAINT {} CHECK ({})",
+            quote_ident(db_type, name),
+            check.expression.trim()
+        ),
+        None => format!("CHECK ({})", check.expression.trim()),
+    })
+}
+
+fn index_method(db_type: &str, idx: &IndexDef) -> Result<Option<String>, String> {
+    let Some(method) = non_empty(&idx.method) else {
+        return Ok(None);
+    };
+    let method = method.to_uppercase();
+    let allowed = if db_type == "mysql" {
+        MYSQL_INDEX_METHODS
+    } else {
+        PG_INDEX_METHODS
+    };
+    if !allowed.contains(&method.as_str()) {
+        return Err(format!("不支持的索引类型: {}", method));
+    }
+    Ok(Some(method))
+}
+
+/// `(col(10) DESC, ...)` from `column_options` when given, else the plain column names
+fn index_columns(db_type: &str, idx: &IndexDef) -> Result<String, String> {
+    let Some(options) = idx.column_options.as_ref().filter(|o| !o.is_empty()) else {
+        if idx.columns.is_empty() {
+            return Err(format!("索引 {} 没有列", idx.name));
+        }
+        return Ok(format!("({})", quote_list(db_type, &idx.columns)));
+    };
+    let parts = options
+        .iter()
+        .map(|col| {
+            let mut part = quote_ident(db_type, &col.name);
+            if let Some(length) = col.length {
+                if db_type != "mysql" {
+                    return Err("前缀索引长度仅支持 MySQL".to_string());
+                }
+                part.push_str(&format!("({})", length));
+            }
+            match non_empty(&col.order).map(str::to_uppercase).as_deref() {
+                None => {}
+                Some(order @ ("ASC" | "DESC")) => part.push_str(&format!(" {}", order)),
+                Some(other) => return Err(format!("无效的排序方向: {}", other)),
+            }
+            Ok(part)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(format!("({})", parts.join(", ")))
+}
+
+/// `UNIQUE INDEX `name` (cols) USING BTREE`, as used inside MySQL CREATE / ALTER TABLE
+fn mysql_index_clause(idx: &IndexDef) -> Result<String, String> {
+    let db_type = "mysql";
+    if non_empty(&idx.predicate).is_some() {
+        return Err("MySQL 不支持部分索引 (WHERE)".to_string());
+    }
+    let method = index_method(db_type, idx)?;
+    let kind = match method.as_deref() {
+        Some("FULLTEXT") => "FULLTEXT ",
+        Some("SPATIAL") => "SPATIAL ",
+        _ if idx.is_unique => "UNIQUE ",
+        _ => "",
+    };
+    let using = match method.as_deref() {
+        Some(m @ ("BTREE" | "HASH")) => format!(" USING {}", m),
+        _ => String::new(),
+    };
+    Ok(format!(
+        "{}INDEX {} {}{}",
+        kind,
+        quote_ident(db_type, &idx.name),
+        index_columns(db_type, idx)?,
+        using
+    ))
+}
+
+/// `CREATE INDEX` on PostgreSQL with method, partial predicate and CONCURRENTLY
+fn pg_create_index(table: &str, idx: &IndexDef, concurrently: bool) -> Result<String, String> {
+    let db_type = "postgresql";
+    let mut sql = format!(
+        "CREATE {}INDEX {}{} ON {}",
+        if idx.is_unique { "UNIQUE " } else { "" },
+        if concurrently { "CONCURRENTLY " } else { "" },
+        quote_ident(db_type, &idx.name),
+        quote_ident(db_type, table)
+    );
+    if let Some(method) = index_method(db_type, idx)? {
+        sql.push_str(&format!(" USING {}", method.to_lowercase()));
+    }
+    sql.push_str(&format!(" {}", index_columns(db_type, idx)?));
+    if let Some(predicate) = non_empty(&idx.predicate) {
+        sql.push_str(&format!(" WHERE {}", predicate));
+    }
+    Ok(sql)
+}
+
+/// CREATE TABLE and, on PostgreSQL, the COMMENT ON / CREATE INDEX statements that
+/// can't be written inline
+pub fn create_table_sql(db_type: &str, spec: &TableSpec) -> Result<Vec<String>, String> {
+    if spec.name.trim().is_empty() {
+        return Err("表名不能为空".to_string());
+    }
+    if spec.columns.is_empty() {
+        return Err("至少需要一列".to_string());
+    }
+    let table = quote_ident(db_type, &spec.name);
+
+    let mut defs = spec
+        .columns
+        .iter()
+        .map(|col| column_definition(db_type, col))
+        .collect::<Result<Vec<_>, _>>()?;
+    let pk: Vec<String> = spec
+        .columns
+        .iter()
+        .filter(|c| c.is_pk)
+        .map(|c| c.name.clone())
+        .collect();
+    if !pk.is_empty() {
+        defs.push(format!("PRIMARY KEY ({})", quote_list(db_type, &pk)));
+    }
+    let indexes: Vec<&IndexDef> = spec.indexes.iter().filter(|i| !i.is_pk).collect();
+    if db_type == "mysql" {
+        for idx in &indexes {
+            defs.push(mysql_index_clause(idx)?);
+        }
+    }
+    for fk in &spec.foreign_keys {
+        defs.push(foreign_key_clause(db_type, fk)?);
+    }
+    for check in &spec.checks {
+        defs.push(check_clause(db_type, check)?);
+    }
+
+    let mut sql = format!("CREATE TABLE {} (\n  {}\n)", table, defs.join(",\n  "));
+    let mut statements = Vec::new();
+    match db_type {
+        "mysql" => {
+            if let Some(engine) = non_empty(&spec.engine) {
+                check_name("存储引擎", engine)?;
+                sql.push_str(&format!(" ENGINE={}", engine));
+            }
+            if let Some(charset) = non_empty(&spec.charset) {
+                check_name("字符集", charset)?;
+                sql.push_str(&format!(" DEFAULT CHARSET={}", charset));
+            }
+            if let Some(collation) = non_empty(&spec.collation) {
+                check_name("排序规则", collation)?;
+                sql.push_str(&format!(" COLLATE={}", collation));
+            }
+            if let Some(comment) = non_empty(&spec.comment) {
+                sql.push_str(&format!(" COMMENT={}", quote_str(db_type, comment)));
+            }
+            if let Some(partition) = non_empty(&spec.partition_by) {
+                sql.push_str(&format!("\nPARTITION BY {}", partition));
+            }
+            statements.push(sql);
+        }
+        _ => {
+            if let Some(partition) = non_empty(&spec.partition_by) {
+                sql.push_str(&format!(" PARTITION BY {}", partition));
+            }
+            if let Some(tablespace) = non_empty(&spec.tablespace) {
+                sql.push_str(&format!(" TABLESPACE {}", quote_ident(db_type, tablespace)));
+            }
+            statements.push(sql);
+            if let Some(comment) = non_empty(&spec.comment) {
+                statements.push(format!(
+                    "COMMENT ON TABLE {} IS {}",
+                    table,
+                    quote_str(db_type, comment)
+                ));
+            }
+            for col in &spec.columns {
+                if let Some(comment) = non_empty(&col.comment) {
+                    statements.push(format!(
+                        "COMMENT ON COLUMN {}.{} IS {}",
+                        table,
+                        quote_ident(db_type, &col.name),
+                        quote_str(db_type, comment)
+                    ));
+                }
+            }
+            for idx in &indexes {
+                statements.push(pg_create_index(&spec.name, idx, false)?);
+            }
+        }
+    }
+    Ok(statements)
+}
+
+fn op_column_def(op: &AlterOperation) -> Result<&ColumnDef, String> {
+    op.column_def
+        .as_ref()
+        .ok_or_else(|| "Missing column definition".to_string())
+}
+
+fn op_column_name(op: &AlterOperation) -> Result<&str, String> {
+    non_empty(&op.column_name).ok_or_else(|| "Missing column name".to_string())
+}
+
+/// One clause of a MySQL ALTER TABLE
+fn mysql_alter_clause(op: &AlterOperation) -> Result<String, String> {
+    let db_type = "mysql";
+    match op.op_type.as_str() {
+        "add" => {
+            let col = op_column_def(op)?;
+            let pk = if col.is_pk { " PRIMARY KEY" } else { "" };
+            Ok(format!(
+                "ADD COLUMN {}{}",
+                column_definition(db_type, col)?,
+                pk
+            ))
+        }
+        "modify" => {
+            let col = op_column_def(op)?;
+            // A modify whose column_name differs from the definition's name also renames
+            match non_empty(&op.column_name).filter(|old| *old != col.name) {
+                Some(old) => Ok(format!(
+                    "CHANGE COLUMN {} {}",
+                    quote_ident(db_type, old),
+                    column_definition(db_type, col)?
+                )),
+                None => Ok(format!(
+                    "MODIFY COLUMN {}",
+                    column_definition(db_type, col)?
+                )),
+            }
+        }
+        "drop" => Ok(format!(
+            "DROP COLUMN {}",
+            quote_ident(db_type, op_column_name(op)?)
+        )),
+        "rename" => {
+            let new_name = non_empty(&op.new_name).ok_or("Missing new name")?;
+            Ok(format!(
+                "RENAME COLUMN {} TO {}",
+                quote_ident(db_type, op_column_name(op)?),
+                quote_ident(db_type, new_name)
+            ))
+        }
+        "add_index" => {
+            let idx = op.index_def.as_ref().ok_or("Missing index definition")?;
+            Ok(format!("ADD {}", mysql_index_clause(idx)?))
+        }
+        "drop_index" => {
+            let name = non_empty(&op.index_name).ok_or("Missing index name")?;
+            Ok(format!("DROP INDEX {}", quote_ident(db_type, name)))
+        }
+        "add_foreign_key" => {
+            let fk = op
+                .foreign_key
+                .as_ref()
+                .ok_or("Missing foreign key definition")?;
+            Ok(format!("ADD {}", foreign_key_clause(db_type, fk)?))
+        }
+        "add_check" => {
+            let check = op.check.as_ref().ok_or("Missing check definition")?;
+            Ok(format!("ADD {}", check_clause(db_type, check)?))
+        }
+        "add_unique" => {
+            let idx = op.index_def.as_ref().ok_or("Missing unique definition")?;
+            Ok(format!(
+                "ADD CONSTRAINT {} UNIQUE {}",
+                quote_ident(db_type, &idx.name),
+                index_columns(db_type, idx)?
+            ))
+        }
+        "drop_foreign_key" | "drop_check" | "drop_unique" => {
+            let name = non_empty(&op.constraint_name).ok_or("Missing constraint name")?;
+            let kind = match op.op_type.as_str() {
+                "drop_foreign_key" => "FOREIGN KEY",
+                "drop_check" => "CHECK",
+                _ => "INDEX",
+            };
+            Ok(format!("DROP {} {}", kind, quote_ident(db_type, name)))
+        }
+        other => Err(format!("Unknown operation: {}", other)),
+    }
+}
+
+/// ALTER COLUMN statements turning `existing` (None if unknown) into `col`. Only the
+/// parts that differ are changed, so editing a comment doesn't rewrite the table.
+fn pg_modify_statements(
+    table: &str,
+    col: &ColumnDef,
+    old_name: &str,
+    existing: Option<&ColumnDef>,
+    using: Option<&str>,
+) -> Vec<String> {
+    let db_type = "postgresql";
+    let alter = format!("ALTER TABLE {}", quote_ident(db_type, table));
+    let column = quote_ident(db_type, &col.name);
+    let alter_column = format!("{} ALTER COLUMN {}", alter, column);
+    let mut statements = Vec::new();
+
+    if old_name != col.name {
+        statements.push(format!(
+            "{} RENAME COLUMN {} TO {}",
+            alter,
+            quote_ident(db_type, old_name),
+            column
+        ));
+    }
+
+    let was_auto = existing.and_then(|c| c.auto_increment).unwrap_or(false);
+    let old_default = existing.and_then(|c| non_empty(&c.default_value));
+    let was_serial = old_default.is_some_and(|d| d.starts_with("nextval("));
+    let wants_auto = col.auto_increment.unwrap_or(was_auto);
+    let mut default_handled = false;
+    if was_auto && !wants_auto {
+        statements.push(if was_serial {
+            format!("{} DROP DEFAULT", alter_column)
+        } else {
+            format!("{} DROP IDENTITY IF EXISTS", alter_column)
+        });
+        default_handled = was_serial;
+    }
+
+    let type_changed =
+        existing.is_none_or(|c| !c.type_name.eq_ignore_ascii_case(col.type_name.trim()));
+    let using = using.map(str::trim).filter(|u| !u.is_empty());
+    if type_changed || using.is_some() {
+        let mut sql = format!("{} TYPE {}", alter_column, col.type_name);
+        if let Some(expr) = using {
+            sql.push_str(&format!(" USING {}", expr));
+        }
+        statements.push(sql);
+    }
+
+    let not_null = col.is_nullable == Some(false) || (wants_auto && !was_auto);
+    let was_not_null = existing.is_some_and(|c| c.is_nullable == Some(false));
+    if col.is_nullable.is_some() || not_null {
+        if not_null && !was_not_null {
+            statements.push(format!("{} SET NOT NULL", alter_column));
+        } else if !not_null && (was_not_null || existing.is_none()) {
+            statements.push(format!("{} DROP NOT NULL", alter_column));
+        }
+    }
+
+    if wants_auto && !was_auto {
+        if old_default.is_some() {
+            statements.push(format!("{} DROP DEFAULT", alter_column));
+        }
+        statements.push(format!(
+            "{} ADD GENERATED BY DEFAULT AS IDENTITY",
+            alter_column
+        ));
+        default_handled = true;
+    }
+    // An identity column has no default; a serial keeps its nextval() default
+    let is_identity = wants_auto && !was_serial;
+    if !default_handled && !is_identity {
+        let new_default = non_empty(&col.default_value);
+        if new_default != old_default || existing.is_none() {
+            statements.push(match new_default {
+                Some(d) => format!("{} SET DEFAULT {}", alter_column, d),
+                None => format!("{} DROP DEFAULT", alter_column),
+            });
+        }
+    }
+
+    let new_comment = non_empty(&col.comment);
+    if existing.is_none_or(|c| non_empty(&c.comment) != new_comment) {
+        statements.push(format!(
+            "COMMENT ON COLUMN {}.{} IS {}",
+            quote_ident(db_type, table),
+            column,
+            new_comment
+                .map(|c| quote_str(db_type, c))
+                .unwrap_or_else(|| "NULL".to_string())
+        ));
+    }
+    statements
+}
+
+/// Statements for one operation on PostgreSQL
+fn pg_alter_statements(
+    table: &str,
+    op: &AlterOperation,
+    current: &[ColumnDef],
+) -> Result<Vec<String>, String> {
+    let db_type = "postgresql";
+    let alter = format!("ALTER TABLE {}", quote_ident(db_type, table));
+    let concurrently = op.concurrently.unwrap_or(false);
+    let mut statements = Vec::new();
+    match op.op_type.as_str() {
+        "add" => {
+            let col = op_column_def(op)?;
+            let pk = if col.is_pk { " PRIMARY KEY" } else { "" };
+            statements.push(format!(
+                "{} ADD COLUMN {}{}",
+                alter,
+                column_definition(db_type, col)?,
+                pk
+            ));
+            if let Some(comment) = non_empty(&col.comment) {
+                statements.push(format!(
+                    "COMMENT ON COLUMN {}.{} IS {}",
+                    quote_ident(db_type, table),
+                    quote_ident(db_type, &col.name),
+                    quote_str(db_type, comment)
+                ));
+            }
+        }
+        "modify" => {
+            let col = op_column_def(op)?;
+            let old_name = non_empty(&op.column_name).unwrap_or(&col.name);
+            let existing = current.iter().find(|c| c.name == old_name);
+            statements.extend(pg_modify_statements(
+                table,
+                col,
+                old_name,
+                existing,
+                op.using.as_deref(),
+            ));
+        }
+        "drop" => statements.push(format!(
+            "{} DROP COLUMN {}",
+            alter,
+            quote_ident(db_type, op_column_name(op)?)
+        )),
+        "rename" => {
+            let new_name = non_empty(&op.new_name).ok_or("Missing new name")?;
+            statements.push(format!(
+                "{} RENAME COLUMN {} TO {}",
+                alter,
+                quote_ident(db_type, op_column_name(op)?),
+                quote_ident(db_type, new_name)
+            ));
+        }
+        "add_index" => {
+            let idx = op.index_def.as_ref().ok_or("Missing index definition")?;
+            statements.push(pg_create_index(table, idx, concurrently)?);
+        }
+        "drop_index" => {
+            let name = non_empty(&op.index_name).ok_or("Missing index name")?;
+            statements.push(format!(
+                "DROP INDEX {}{}",
+                if concurrently { "CONCURRENTLY " } else { "" },
+                quote_ident(db_type, name)
+            ));
+        }
+        "add_foreign_key" => {
+            let fk = op
+                .foreign_key
+                .as_ref()
+                .ok_or("Missing foreign key definition")?;
+            statements.push(format!(
+                "{} ADD {}",
+                alter,
+                foreign_key_clause(db_type, fk)?
+            ));
+        }
+        "add_check" => {
+            let check = op.check.as_ref().ok_or("Missing check definition")?;
+            statements.push(format!("{} ADD {}", alter, check_clause(db_type, check)?));
+        }
+        "add_unique" => {
+            let idx = op.index_def.as_ref().ok_or("Missing unique definition")?;
+            statements.push(format!(
+                "{} ADD CONSTRAINT {} UNIQUE {}",
+                alter,
+                quote_ident(db_type, &idx.name),
+                index_columns(db_type, idx)?
+            ));
+        }
+        "drop_foreign_key" | "drop_check" | "drop_unique" => {
+            let name = non_empty(&op.constraint_name).ok_or("Missing constraint name")?;
+            statements.push(format!(
+                "{} DROP CONSTRAINT {}",
+                alter,
+                quote_ident(db_type, name)
+            ));
+        }
+        other => return Err(format!("Unknown operation: {}", other)),
+    }
+    Ok(statements)
+}
+
+/// SQL for a batch of table changes: a single ALTER TABLE on MySQL, or the
+/// statements to run in one transaction on PostgreSQL. `current` is the table's
+/// columns as they are now, used to work out what a PostgreSQL modify changes.
+pub fn alter_table_sql(
+    db_type: &str,
+    table: &str,
+    operations: &[AlterOperation],
+    current: &[ColumnDef],
+) -> Result<Vec<String>, String> {
+    if operations.is_empty() {
+        return Err("没有要执行的更改".to_string());
+    }
+    match db_type {
+        "mysql" => {
+            let clauses = operations
+                .iter()
+                .enumerate()
+                .map(|(i, op)| {
+                    mysql_alter_clause(op).map_err(|e| format!("第 {} 项更改无效: {}", i + 1, e))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(vec![format!(
+                "ALTER TABLE {}\n  {}",
+                quote_ident(db_type, table),
+                clauses.join(",\n  ")
+            )])
+        }
+        "postgresql" => {
+            // CONCURRENTLY can't run inside the transaction the batch uses
+            if operations.len() > 1 && operations.iter().any(|op| op.concurrently == Some(true)) {
+                return Err("CONCURRENTLY 索引操作不能在事务中执行，请单独提交".to_string());
+            }
+            let mut statements = Vec::new();
+            for (i, op) in operations.iter().enumerate() {
+                statements.extend(
+                    pg_alter_statements(table, op, current)
+                        .map_err(|e| format!("第 {} 项更改无效: {}", i + 1, e))?,
+                );
+            }
+            Ok(statements)
+        }
+        other => Err(format!("Unsupported database type: {}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IndexColumn;
+
+    fn col(name: &str, type_name: &str) -> ColumnDef {
+        ColumnDef {
+            name: name.to_string(),
+            type_name: type_name.to_string(),
+            is_pk: false,
+            is_nullable: None,
+            default_value: None,
+            comment: None,
+            auto_increment: None,
+        }
+    }
+
+    fn index(name: &str, columns: &[&str]) -> IndexDef {
+        IndexDef {
+            name: name.to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            is_unique: false,
+            is_pk: false,
+            comment: None,
+            method: None,
+            predicate: None,
+            column_options: None,
+            definition: None,
+            size_bytes: None,
+            scans: None,
+            is_valid: None,
+        }
+    }
+
+    fn index_column(name: &str, order: Option<&str>, length: Option<u32>) -> IndexColumn {
+        IndexColumn {
+            name: name.to_string(),
+            order: order.map(String::from),
+            length,
+        }
+    }
+
+    fn op(op_type: &str) -> AlterOperation {
+        AlterOperation {
+            op_type: op_type.to_string(),
+            column_name: None,
+            new_name: None,
+            column_def: None,
+            index_def: None,
+            index_name: None,
+            using: None,
+            foreign_key: None,
+            check: None,
+            constraint_name: None,
+            concurrently: None,
+        }
+    }
+
+    fn foreign_key(on_delete: Option<&str>, on_update: Option<&str>) -> ForeignKeyDef {
+        ForeignKeyDef {
+            name: Some("fk_user".to_string()),
+            columns: vec!["user_id".to_string()],
+            ref_table: "users".to_string(),
+            ref_columns: vec!["id".to_string()],
+            on_delete: on_delete.map(String::from),
+            on_update: on_update.map(String::from),
+        }
+    }
+
+    fn some(s: &str) -> Option<String> {
+        Some(s.to_string())
+    }
+
+    fn users_spec() -> TableSpec {
+        TableSpec {
+            name: "users".to_string(),
+            columns: vec![
+                ColumnDef {
+                    is_pk: true,
+                    auto_increment: Some(true),
+                    ..col("id", "bigint")
+                },
+                ColumnDef {
+                    is_nullable: Some(false),
+                    comment: some("display name"),
+                    ..col("name", "varchar(50)")
+                },
+            ],
+            indexes: vec![index("idx_name", &["name"])],
+            foreign_keys: vec![],
+            checks: vec![CheckDef {
+                name: None,
+                expression: "id > 0".to_string(),
+            }],
+            comment: some("accounts"),
+            engine: some("InnoDB"),
+            charset: some("utf8mb4"),
+            collation: None,
+            tablespace: None,
+            partition_by: None,
+        }
+    }
+
+    #[test]
+    fn create_table_mysql() {
+        assert_eq!(
+            create_table_sql("mysql", &users_spec()).unwrap(),
+            vec![[
+                "CREATE TABLE `users` (",
+                "  `id` bigint NOT NULL AUTO_INCREMENT,",
+                "  `name` varchar(50) NOT NULL COMMENT 'display name',",
+                "  PRIMARY KEY (`id`),",
+                "  INDEX `idx_name` (`name`),",
+                "  CHECK (id > 0)",
+                ") ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='accounts'",
+            ]
+            .join("\n")]
+        );
+    }
+
+    #[test]
+    fn create_table_postgresql() {
+        assert_eq!(
+            create_table_sql("postgresql", &users_spec()).unwrap(),
+            vec![
+                [
+                    r#"CREATE TABLE "users" ("#,
+                    r#"  "id" bigint GENERATED BY DEFAULT AS IDENTITY NOT NULL,"#,
+                    r#"  "name" varchar(50) NOT NULL,"#,
+                    r#"  PRIMARY KEY ("id"),"#,
+                    r#"  CHECK (id > 0)"#,
+                    r#")"#,
+                ]
+                .join("\n"),
+                r#"COMMENT ON TABLE "users" IS 'accounts'"#.to_string(),
+                r#"COMMENT ON COLUMN "users"."name" IS 'display name'"#.to_string(),
+                r#"CREATE INDEX "idx_name" ON "users" ("name")"#.to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn create_table_rejects_bad_specs() {
+        let cases = [
+            (
+                TableSpec {
+                    name: " ".to_string(),
+                    ..users_spec()
+                },
+                "表名不能为空",
+            ),
+            (
+                TableSpec {
+                    columns: vec![],
+                    ..users_spec()
+                },
+                "至少需要一列",
+            ),
+            (
+                TableSpec {
+                    engine: some("InnoDB; DROP TABLE x"),
+                    ..users_spec()
+                },
+                "无效的存储引擎: InnoDB; DROP TABLE x",
+            ),
+        ];
+        for (spec, expected) in cases {
+            assert_eq!(create_table_sql("mysql", &spec).unwrap_err(), expected);
+        }
+    }
+
+    #[test]
+    fn mysql_alter_is_one_statement() {
+        let operations = vec![
+            AlterOperation {
+                column_def: Some(ColumnDef {
+                    is_nullable: Some(false),
+                    default_value: some("0"),
+                    comment: some("age"),
+                    ..col("age", "int")
+                }),
+                ..op("add")
+            },
+            AlterOperation {
+                column_name: some("old"),
+                ..op("drop")
+            },
+            AlterOperation {
+                column_name: some("nick"),
+                column_def: Some(col("nickname", "varchar(20)")),
+                ..op("modify")
+            },
+            AlterOperation {
+                column_def: Some(col("name", "varchar(50)")),
+                ..op("modify")
+            },
+            AlterOperation {
+                index_def: Some(index("idx_name", &["name"])),
+                ..op("add_index")
+            },
+            AlterOperation {
+                index_name: some("idx_old"),
+                ..op("drop_index")
+            },
+        ];
+        assert_eq!(
+            alter_table_sql("mysql", "t", &operations, &[]).unwrap(),
+            vec![[
+                "ALTER TABLE `t`",
+                "  ADD COLUMN `age` int NOT NULL DEFAULT 0 COMMENT 'age',",
+                "  DROP COLUMN `old`,",
+                "  CHANGE COLUMN `nick` `nickname` varchar(20),",
+                "  MODIFY COLUMN `name` varchar(50),",
+                "  ADD INDEX `idx_name` (`name`),",
+                "  DROP INDEX `idx_old`",
+            ]
+            .join("\n")]
+        );
+    }
+
+    #[test]
+    fn pg_alter_statements_per_operation() {
+        let operations = vec![
+            AlterOperation {
+                column_def: Some(col("note", "text")),
+                ..op("add")
+            },
+            AlterOperation {
+                column_name: some("a"),
+                new_name: some("b"),
+                ..op("rename")
+            },
+            AlterOperation {
+                index_def: Some(index("idx_note", &["note"])),
+                ..op("add_index")
+            },
+            AlterOperation {
+                column_name: some("x"),
+                ..op("drop")
+            },
+        ];
+        assert_eq!(
+            alter_table_sql("postgresql", "t", &operations, &[]).unwrap(),
+            vec![
+                r#"ALTER TABLE "t" ADD COLUMN "note" text"#,
+                r#"ALTER TABLE "t" RENAME COLUMN "a" TO "b""#,
+                r#"CREATE INDEX "idx_note" ON "t" ("note")"#,
+                r#"ALTER TABLE "t" DROP COLUMN "x""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn alter_table_rejects_bad_batches() {
+        assert!(alter_table_sql("mysql", "t", &[], &[]).is_err());
+        for db_type in ["mysql", "postgresql"] {
+            let err = alter_table_sql(db_type, "t", &[op("drop"), op("explode")], &[]);
+            assert_eq!(
+                err.unwrap_err(),
+                "第 1 项更改无效: Missing column name",
+                "{}",
+                db_type
+            );
+        }
+        assert!(alter_table_sql("sqlite", "t", &[op("drop")], &[]).is_err());
+    }
+
+    #[test]
+    fn pg_modify_changes_only_what_differs() {
+        let int = col("c", "integer");
+        let not_null_int = ColumnDef {
+            is_nullable: Some(false),
+            ..col("c", "integer")
+        };
+        let identity = ColumnDef {
+            auto_increment: Some(true),
+            is_nullable: Some(false),
+            ..col("c", "integer")
+        };
+        let serial = ColumnDef {
+            auto_increment: Some(true),
+            is_nullable: Some(false),
+            default_value: some("nextval('t_c_seq'::regclass)"),
+            ..col("c", "integer")
+        };
+        let with_default = ColumnDef {
+            default_value: some("0"),
+            is_nullable: Some(true),
+            ..col("c", "integer")
+        };
+        let cases = vec![
+            (
+                "type change",
+                Some(&int),
+                col("c", "bigint"),
+                None,
+                vec![r#"ALTER TABLE "t" ALTER COLUMN "c" TYPE bigint"#],
+            ),
+            (
+                "same type in another case is unchanged",
+                Some(&int),
+                col("c", "INTEGER"),
+                None,
+                vec![],
+            ),
+            (
+                "type change with USING",
+                Some(&int),
+                col("c", "integer"),
+                Some("c::integer"),
+                vec![r#"ALTER TABLE "t" ALTER COLUMN "c" TYPE integer USING c::integer"#],
+            ),
+            (
+                "comment only",
+                Some(&int),
+                ColumnDef {
+                    comment: some("it's"),
+                    ..col("c", "integer")
+                },
+                None,
+                vec![r#"COMMENT ON COLUMN "t"."c" IS 'it''s'"#],
+            ),
+            (
+                "set not null and default",
+                Some(&int),
+                ColumnDef {
+                    is_nullable: Some(false),
+                    default_value: some("0"),
+                    ..col("c", "integer")
+                },
+                None,
+                vec![
+                    r#"ALTER TABLE "t" ALTER COLUMN "c" SET NOT NULL"#,
+                    r#"ALTER TABLE "t" ALTER COLUMN "c" SET DEFAULT 0"#,
+                ],
+            ),
+            (
+                "drop not null",
+                Some(&not_null_int),
+                ColumnDef {
+                    is_nullable: Some(true),
+                    ..col("c", "integer")
+                },
+                None,
+                vec![r#"ALTER TABLE "t" ALTER COLUMN "c" DROP NOT NULL"#],
+            ),
+            (
+                "drop default",
+                Some(&with_default),
+                ColumnDef {
+                    is_nullable: Some(true),
+                    ..col("c", "integer")
+                },
+                None,
+                vec![r#"ALTER TABLE "t" ALTER COLUMN "c" DROP DEFAULT"#],
+            ),
+            (
+                "become identity replaces the default",
+                Some(&with_default),
+                ColumnDef {
+                    auto_increment: Some(true),
+                    ..col("c", "integer")
+                },
+                None,
+                vec![
+                    r#"ALTER TABLE "t" ALTER COLUMN "c" SET NOT NULL"#,
+                    r#"ALTER TABLE "t" ALTER COLUMN "c" DROP DEFAULT"#,
+                    r#"ALTER TABLE "t" ALTER COLUMN "c" ADD GENERATED BY DEFAULT AS IDENTITY"#,
+                ],
+            ),
+            (
+                "identity kept when unchanged",
+                Some(&identity),
+                ColumnDef {
+                    is_nullable: Some(false),
+                    ..col("c", "integer")
+                },
+                None,
+                vec![],
+            ),
+            (
+                "stop being identity",
+                Some(&identity),
+                ColumnDef {
+                    auto_increment: Some(false),
+                    ..col("c", "integer")
+                },
+                None,
+                vec![r#"ALTER TABLE "t" ALTER COLUMN "c" DROP IDENTITY IF EXISTS"#],
+            ),
+            (
+                "stop being serial drops the nextval default",
+                Some(&serial),
+                ColumnDef {
+                    auto_increment: Some(false),
+                    ..col("c", "integer")
+                },
+                None,
+                vec![r#"ALTER TABLE "t" ALTER COLUMN "c" DROP DEFAULT"#],
+            ),
+            (
+                "serial keeps its default",
+                Some(&serial),
+                ColumnDef {
+                    is_nullable: Some(false),
+                    default_value: some("nextval('t_c_seq'::regclass)"),
+                    ..col("c", "integer")
+                },
+                None,
+                vec![],
+            ),
+            (
+                "unknown column sets everything",
+                None,
+                col("c", "integer"),
+                None,
+                vec![
+                    r#"ALTER TABLE "t" ALTER COLUMN "c" TYPE integer"#,
+                    r#"ALTER TABLE "t" ALTER COLUMN "c" DROP DEFAULT"#,
+                    r#"COMMENT ON COLUMN "t"."c" IS NULL"#,
+                ],
+            ),
+        ];
+        for (name, existing, column, using, expected) in cases {
+            assert_eq!(
+                pg_modify_statements("t", &column, "c", existing, using),
+                expected,
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn pg_modify_renames_first() {
+        let existing = col("old", "integer");
+        assert_eq!(
+            pg_modify_statements("t", &col("new", "text"), "old", Some(&existing), None),
+            vec![
+                r#"ALTER TABLE "t" RENAME COLUMN "old" TO "new""#,
+                r#"ALTER TABLE "t" ALTER COLUMN "new" TYPE text"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn pg_alter_adds_identity_and_diffs_modify() {
+        let operations = vec![
+            AlterOperation {
+                column_def: Some(ColumnDef {
+                    is_pk: true,
+                    auto_increment: Some(true),
+                    comment: some("key"),
+                    ..col("id", "bigint")
+                }),
+                ..op("add")
+            },
+            AlterOperation {
+                column_def: Some(col("n", "bigint")),
+                ..op("modify")
+            },
+        ];
+        let current = vec![col("n", "integer")];
+        assert_eq!(
+            alter_table_sql("postgresql", "t", &operations, &current).unwrap(),
+            vec![
+                r#"ALTER TABLE "t" ADD COLUMN "id" bigint GENERATED BY DEFAULT AS IDENTITY NOT NULL PRIMARY KEY"#,
+                r#"COMMENT ON COLUMN "t"."id" IS 'key'"#,
+                r#"ALTER TABLE "t" ALTER COLUMN "n" TYPE bigint"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn constraint_operations() {
+        let mysql = vec![
+            AlterOperation {
+                index_def: Some(IndexDef {
+                    is_unique: true,
+                    method: some("btree"),
+                    ..index("idx_name", &["name"])
+                }),
+                ..op("add_index")
+            },
+            AlterOperation {
+                constraint_name: some("fk_user"),
+                ..op("drop_foreign_key")
+            },
+        ];
+        assert_eq!(
+            alter_table_sql("mysql", "t", &mysql, &[]).unwrap(),
+            vec![[
+                "ALTER TABLE `t`",
+                "  ADD UNIQUE INDEX `idx_name` (`name`) USING BTREE,",
+                "  DROP FOREIGN KEY `fk_user`",
+            ]
+            .join("\n")]
+        );
+
+        let postgresql = vec![
+            AlterOperation {
+                check: Some(CheckDef {
+                    name: some("positive"),
+                    expression: " n > 0 ".to_string(),
+                }),
+                ..op("add_check")
+            },
+            AlterOperation {
+                constraint_name: some("uq"),
+                ..op("drop_unique")
+            },
+        ];
+        assert_eq!(
+            alter_table_sql("postgresql", "t", &postgresql, &[]).unwrap(),
+            vec![
+                r#"ALTER TABLE "t" ADD CONSTRAINT "positive" CHECK (n > 0)"#,
+                r#"ALTER TABLE "t" DROP CONSTRAINT "uq""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn pg_concurrent_index_must_be_alone() {
+        let add = AlterOperation {
+            concurrently: Some(true),
+            index_def: Some(IndexDef {
+                method: some("GIN"),
+                ..index("idx_doc", &["doc"])
+            }),
+            ..op("add_index")
+        };
+        assert_eq!(
+            alter_table_sql("postgresql", "t", std::slice::from_ref(&add), &[]).unwrap(),
+            vec![r#"CREATE INDEX CONCURRENTLY "idx_doc" ON "t" USING gin ("doc")"#]
+        );
+        let drop = AlterOperation {
+            column_name: some("x"),
+            ..op("drop")
+        };
+        assert!(alter_table_sql("postgresql", "t", &[add, drop], &[]).is_err());
+    }
+
+    #[test]
+    fn foreign_key_clauses() {
+        let cases = [
+            (
+                "mysql",
+                foreign_key(Some("set  null"), Some("cascade")),
+                Ok("CONSTRAINT `fk_user` FOREIGN KEY (`user_id`) REFERENCES `users` (`id`) ON DELETE SET NULL ON UPDATE CASCADE"),
+            ),
+            (
+                "postgresql",
+                foreign_key(None, Some(" ")),
+                Ok(r#"CONSTRAINT "fk_user" FOREIGN KEY ("user_id") REFERENCES "users" ("id")"#),
+            ),
+            (
+                "postgresql",
+                ForeignKeyDef {
+                    name: None,
+                    ..foreign_key(Some("RESTRICT"), None)
+                },
+                Ok(r#"FOREIGN KEY ("user_id") REFERENCES "users" ("id") ON DELETE RESTRICT"#),
+            ),
+            (
+                "mysql",
+                foreign_key(Some("DROP TABLE"), None),
+                Err("不支持的外键动作: DROP TABLE"),
+            ),
+            (
+                "mysql",
+                ForeignKeyDef {
+                    ref_columns: vec![],
+                    ..foreign_key(None, None)
+                },
+                Err("外键的列与引用列数量必须一致"),
+            ),
+        ];
+        for (db_type, fk, expected) in cases {
+            assert_eq!(
+                foreign_key_clause(db_type, &fk),
+                expected.map(String::from).map_err(String::from)
+            );
+        }
+    }
+
+    #[test]
+    fn index_column_lists() {
+        let prefixed = IndexDef {
+            column_options: Some(vec![
+                index_column("name", None, Some(10)),
+                index_column("created", Some("desc"), None),
+            ]),
+            ..index("idx", &["ignored"])
+        };
+        let cases = [
+            ("mysql", index("idx", &["a", "b"]), Ok("(`a`, `b`)")),
+            ("postgresql", index("idx", &["a"]), Ok(r#"("a")"#)),
+            (
+                "mysql",
+                prefixed.clone(),
+                Ok("(`name`(10), `created` DESC)"),
+            ),
+            ("postgresql", prefixed, Err("前缀索引长度仅支持 MySQL")),
+            (
+                "postgresql",
+                IndexDef {
+                    column_options: Some(vec![index_column("a", Some("UP"), None)]),
+                    ..index("idx", &[])
+                },
+                Err("无效的排序方向: UP"),
+            ),
+            ("mysql", index("idx", &[]), Err("索引 idx 没有列")),
+        ];
+        for (db_type, idx, expected) in cases {
+            assert_eq!(
+                index_columns(db_type, &idx),
+                expected.map(String::from).map_err(String::from)
+            );
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use serde_json::Value;
+
+/// Quote an identifier for the dialect: `name` for MySQL, "name" for PostgreSQL
+pub fn quote_ident(db_type: &str, name: &str) -> String {
+    match db_type {
+        "mysql" => format!("`{}`", name.replace('`', "``")),
+        _ => format!("\"{}\"", name.replace('"', "\"\"")),
+    }
+}
+
+/// Quote a string literal. MySQL also treats backslashes as escapes by default.
+pub fn quote_str(db_type: &str, s: &str) -> String {
+    let escaped = s.replace('\'', "''");
+    match db_type {
+        "mysql" => format!("'{}'", escaped.replace('\\', "\\\\")),
+        _ => format!("'{}'", escaped),
+    }
+}
+
+/// Render a JSON value as an SQL literal
+pub fn literal(db_type: &str, value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Bool(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => quote_str(db_type, s),
+        other => quote_str(db_type, &other.to_string()),
+    }
+}
+
+/// Literal for a binary value read as "0x<hex>": X'..' on MySQL, '\x..'::bytea on PostgreSQL
+pub fn binary_literal(db_type: &str, value: &Value) -> String {
+    match value.as_str().and_then(|s| s.strip_prefix("0x")) {
+        Some(hex) if hex.chars().all(|c| c.is_ascii_hexdigit()) => match db_type {
+            "mysql" => format!("X'{}'", hex),
+            _ => format!("'\\x{}'::bytea", hex),
+        },
+        _ => literal(db_type, value),
+    }
+}
+
+/// Multi-row `INSERT INTO table (cols) VALUES (...), (...)` without trailing semicolon.
+/// Values of columns named in `binary` are written as binary literals.
+pub fn insert_statement(
+    db_type: &str,
+    table: &str,
+    columns: &[String],
+    binary: &[String],
+    rows: &[Vec<Value>],
+) -> String {
+    let is_binary: Vec<bool> = columns.iter().map(|c| binary.contains(c)).collect();
+    let cols = columns
+        .iter()
+        .map(|c| quote_ident(db_type, c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let values = rows
+        .iter()
+        .map(|row| {
+            let vals = row
+                .iter()
+                .zip(&is_binary)
+                .map(|(v, binary)| {
+                    if *binary {
+                        binary_literal(db_type, v)
+                    } else {
+                        literal(db_type, v)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("({})", vals)
+        })
+        .collect::<Vec<_>>()
+        .join(",\n  ");
+    format!(
+        "INSERT INTO {} ({}) VALUES\n  {}",
+        quote_ident(db_type, table),
+        cols,
+        values
+    )
+}
+
+/// Bind placeholder for the n-th (1-based) parameter: `?` for MySQL, `$n` for PostgreSQL
+pub fn placeholder(db_type: &str, n: usize) -> String {
+    match db_type {
+        "mysql" => "?".to_string(),
+        _ => format!("${}", n),
+    }
+}
+
+/// Split a script into statements on `;`, ignoring semicolons inside quotes,
+/// comments and PostgreSQL dollar-quoted bodies. Line comments are dropped,
+/// block comments are kept since MySQL runs `/*!... */` ones.
+pub fn split_statements(db_type: &str, script: &str) -> Vec<String> {
+    let mysql = db_type == "mysql";
+    let chars: Vec<char> = script.chars().collect();
+    let mut statements = Vec::new();
+    let mut cur = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            '\'' | '"' | '`' => {
+                cur.push(c);
+                i += 1;
+                while i < chars.len() {
+                    let d = chars[i];
+                    cur.push(d);
+                    i += 1;
+                    if mysql && d == '\\' && c != '`' {
+                        if let Some(&escaped) = chars.get(i) {
+                            cur.push(escaped);
+                            i += 1;
+                        }
+                    } else if d == c {
+                        // A doubled quote is an escaped quote, not the end
+                        if chars.get(i) == Some(&c) {
+                            cur.push(c);
+                            i += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                }
+            }
+            '-' if next == Some('-') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '#' if mysql => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if next == Some('*') => {
+                let start = i;
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i = (i + 2).min(chars.len());
+                cur.extend(&chars[start..i]);
+            }
+            '$' if !mysql => {
+                // Dollar quote tag: $$ or $name$
+                let mut j = i + 1;
+                while j < chars.len() && (chars[j].is_alphanumeric() || chars[j] == '_') {
+                    j += 1;
+                }
+                let is_tag = chars.get(j) == Some(&'$')
+                    && !chars.get(i + 1).is_some_and(|ch| ch.is_ascii_digit());
+                if !is_tag {
+                    cur.push(c);
+                    i += 1;
+                    continue;
+                }
+                let tag: Vec<char> = chars[i..=j].to_vec();
+                cur.extend(&tag);
+                i = j + 1;
+                while i < chars.len() && !chars[i..].starts_with(&tag) {
+                    cur.push(chars[i]);
+                    i += 1;
+                }
+                if i < chars.len() {
+                    cur.extend(&tag);
+                    i += tag.len();
+                }
+            }
+            ';' => {
+                if !cur.trim().is_empty() {
+                    statements.push(cur.trim().to_string());
+                }
+                cur.clear();
+                i += 1;
+            }
+            _ => {
+                cur.push(c);
+                i += 1;
+            }
+        }
+    }
+    if !cur.trim().is_empty() {
+        statements.push(cur.trim().to_string());
+    }
+    statements
+}
+
+/// Multi-row parameterized INSERT for `rows` rows. Each column has a template where
+/// `{}` stands for its placeholder, e.g. `CAST({} AS integer)` or `UNHEX({})`.
+pub fn insert_params_statement(
+    db_type: &str,
+    table: &str,
+    columns: &[String],
+    templates: &[String],
+    rows: usize,
+) -> String {
+    let mut n = 0;
+    let values: Vec<String> = (0..rows)
+        .map(|_| {
+            let row: Vec<String> = templates
+                .iter()
+                .map(|template| {
+                    n += 1;
+                    template.replace("{}", &placeholder(db_type, n))
+                })
+                .collect();
+            format!("({})", row.join(", "))
+        })
+        .collect();
+    let cols: Vec<String> = columns.iter().map(|c| quote_ident(db_type, c)).collect();
+    format!(
+        "INSERT INTO {} ({}) VALUES {}",
+        quote_ident(db_type, table),
+        cols.join(", "),
+        values.join(", ")
+    )
+}
+
+/// Split "varchar(255) unsigned" into ("varchar", "(255)")
+fn split_type(type_name: &str) -> (String, String) {
+    let t = type_name.trim().to_lowercase();
+    match (t.find('('), t.find(')')) {
+        (Some(open), Some(close)) if close > open => {
+            (t[..open].trim().to_string(), t[open..=close].to_string())
+        }
+        _ => {
+            let base = t
+                .trim_end_matches(" unsigned")
+                .trim_end_matches(" with time zone")
+                .trim_end_matches(" without time zone");
+            (base.to_string(), String::new())
+        }
+    }
+}
+
+/// Whether a column type holds raw bytes
+pub fn is_binary_type(type_name: &str) -> bool {
+    let (base, _) = split_type(type_name);
+    base.contains("blob") || base.contains("binary") || base == "bytea"
+}
+
+/// Translate a column type from one dialect to the other.
+/// Types without a close counterpart fall back to text.
+pub fn map_type(from: &str, to: &str, type_name: &str) -> String {
+    if from == to {
+        return type_name.to_string();
+    }
+    let lower = type_name.to_lowercase();
+    let (base, args) = split_type(type_name);
+    let unsigned = lower.contains("unsigned");
+
+    if to == "postgresql" {
+        match base.as_str() {
+            "tinyint" if args == "(1)" => "boolean".to_string(),
+            "tinyint" => "smallint".to_string(),
+            "smallint" if unsigned => "integer".to_string(),
+            "smallint" | "year" => "smallint".to_string(),
+            "mediumint" => "integer".to_string(),
+            "int" | "integer" if unsigned => "bigint".to_string(),
+            "int" | "integer" => "integer".to_string(),
+            "bigint" if unsigned => "numeric(20)".to_string(),
+            "bigint" | "bit" => "bigint".to_string(),
+            "float" => "real".to_string(),
+            "double" | "real" => "double precision".to_string(),
+            "decimal" | "numeric" => format!("numeric{}", args),
+            "char" => format!("char{}", args),
+            "varchar" => format!("varchar{}", args),
+            "date" => "date".to_string(),
+            "datetime" | "timestamp" => format!("timestamp{}", args),
+            "time" => format!("time{}", args),
+            "json" => "jsonb".to_string(),
+            b if b.contains("blob") || b.contains("binary") => "bytea".to_string(),
+            _ => "text".to_string(),
+        }
+    } else {
+        if lower.ends_with("[]") {
+            return "json".to_string();
+        }
+        match base.as_str() {
+            "smallint" | "int2" => "smallint".to_string(),
+            "integer" | "int" | "int4" | "serial" => "int".to_string(),
+            "bigint" | "int8" | "bigserial" => "bigint".to_string(),
+            "real" | "float4" => "float".to_string(),
+            "double precision" | "float8" => "double".to_string(),
+            "numeric" | "decimal" if args.is_empty() => "decimal(65,30)".to_string(),
+            "numeric" | "decimal" => format!("decimal{}", args),
+            "boolean" | "bool" => "tinyint(1)".to_string(),
+            "character varying" | "varchar" if args.is_empty() => "longtext".to_string(),
+            "character varying" | "varchar" => format!("varchar{}", args),
+            "character" | "char" | "bpchar" => format!("char{}", args),
+            "bytea" => "longblob".to_string(),
+            "date" => "date".to_string(),
+            "timestamp" => format!("datetime{}", if args.is_empty() { "(6)" } else { &args }),
+            "time" => format!("time{}", if args.is_empty() { "(6)" } else { &args }),
+            "json" | "jsonb" => "json".to_string(),
+            "uuid" => "char(36)".to_string(),
+            _ => "longtext".to_string(),
+        }
+    }
+}
@@ -0,0 +1,384 @@
+use serde::{Deserialize, Serialize};
+
+use crate::sql_admin::{field, text_field};
+use crate::sql_conn::SqlConnection;
+use crate::sql_dialect::{quote_ident, quote_str};
+
+/// Shown in previews in place of the password
+const PASSWORD_MASK: &str = "********";
+
+const MYSQL_PRIVILEGES: &[&str] = &[
+    "ALL",
+    "ALL PRIVILEGES",
+    "ALTER",
+    "ALTER ROUTINE",
+    "CREATE",
+    "CREATE ROUTINE",
+    "CREATE TEMPORARY TABLES",
+    "CREATE USER",
+    "CREATE VIEW",
+    "DELETE",
+    "DROP",
+    "EVENT",
+    "EXECUTE",
+    "FILE",
+    "GRANT OPTION",
+    "INDEX",
+    "INSERT",
+    "LOCK TABLES",
+    "PROCESS",
+    "REFERENCES",
+    "RELOAD",
+    "REPLICATION CLIENT",
+    "REPLICATION SLAVE",
+    "SELECT",
+    "SHOW DATABASES",
+    "SHOW VIEW",
+    "SHUTDOWN",
+    "SUPER",
+    "TRIGGER",
+    "UPDATE",
+];
+
+const PG_PRIVILEGES: &[&str] = &[
+    "ALL",
+    "ALL PRIVILEGES",
+    "CONNECT",
+    "CREATE",
+    "DELETE",
+    "EXECUTE",
+    "INSERT",
+    "REFERENCES",
+    "SELECT",
+    "TEMP",
+    "TEMPORARY",
+    "TRIGGER",
+    "TRUNCATE",
+    "UPDATE",
+    "USAGE",
+];
+
+/// Privileges that may be limited to columns, on both engines
+const COLUMN_PRIVILEGES: &[&str] = &["SELECT", "INSERT", "UPDATE", "REFERENCES"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DbUser {
+    pub name: String,
+    pub host: Option<String>, // MySQL account host
+    pub can_login: bool,
+    pub superuser: bool,
+    pub attributes: Vec<String>, // PostgreSQL role flags (CREATEDB, REPLICATION ...)
+    pub member_of: Vec<String>,
+    pub grants: Vec<String>, // SHOW GRANTS lines / "SELECT, INSERT ON public.t"
+    pub grants_error: Option<String>, // Why the grants couldn't be read
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserOperation {
+    pub op_type: String, // "create_user", "drop_user", "set_password", "grant", "revoke"
+    pub name: String,
+    pub host: Option<String>,            // MySQL only, default "%"
+    pub password: Option<String>,        // create_user / set_password
+    pub can_login: Option<bool>,         // PostgreSQL create_user, false creates a plain role
+    pub privileges: Option<Vec<String>>, // grant / revoke, e.g. ["SELECT", "UPDATE"]
+    pub database: Option<String>,        // None = global (MySQL)
+    pub schema: Option<String>,          // PostgreSQL table grants, default "public"
+    pub table: Option<String>,
+    pub columns: Option<Vec<String>>,
+    pub with_grant_option: Option<bool>,
+}
+
+fn account(db_type: &str, op: &UserOperation) -> String {
+    match db_type {
+        "mysql" => format!(
+            "{}@{}",
+            quote_str(db_type, &op.name),
+            quote_str(
+                db_type,
+                op.host.as_deref().filter(|h| !h.is_empty()).unwrap_or("%")
+            )
+        ),
+        _ => quote_ident(db_type, &op.name),
+    }
+}
+
+fn password(db_type: &str, op: &UserOperation, masked: bool) -> Result<String, String> {
+    let pw = op.password.as_deref().ok_or("Missing password")?;
+    Ok(quote_str(db_type, if masked { PASSWORD_MASK } else { pw }))
+}
+
+/// "SELECT, UPDATE (`a`, `b`)" after checking every keyword against the dialect's list
+fn privilege_list(db_type: &str, op: &UserOperation) -> Result<String, String> {
+    let allowed = if db_type == "mysql" {
+        MYSQL_PRIVILEGES
+    } else {
+        PG_PRIVILEGES
+    };
+    let privileges = op
+        .privileges
+        .as_ref()
+        .filter(|p| !p.is_empty())
+        .ok_or("Missing privileges")?;
+    let columns = op.columns.as_ref().filter(|c| !c.is_empty());
+    if columns.is_some() && op.table.is_none() {
+        return Err("列级权限需要指定表".to_string());
+    }
+    let column_list = columns.map(|cols| {
+        cols.iter()
+            .map(|c| quote_ident(db_type, c))
+            .collect::<Vec<_>>()
+            .join(", ")
+    });
+
+    privileges
+        .iter()
+        .map(|p| {
+            let p = p
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .to_uppercase();
+            if !allowed.contains(&p.as_str()) {
+                return Err(format!("不支持的权限: {}", p));
+            }
+            match &column_list {
+                Some(_) if !COLUMN_PRIVILEGES.contains(&p.as_str()) => {
+                    Err(format!("{} 不能授予到列", p))
+                }
+                Some(cols) => Ok(format!("{} ({})", p, cols)),
+                None => Ok(p),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|list| list.join(", "))
+}
+
+/// The object a grant applies to: `*.*`, `db`.*, `db`.`t` / DATABASE "db", TABLE "s"."t"
+fn privilege_target(db_type: &str, op: &UserOperation) -> Result<String, String> {
+    let database = op.database.as_deref().filter(|d| !d.is_empty());
+    let table = op.table.as_deref().filter(|t| !t.is_empty());
+    match db_type {
+        "mysql" => Ok(match (database, table) {
+            (None, None) => "*.*".to_string(),
+            (Some(db), None) => format!("{}.*", quote_ident(db_type, db)),
+            (Some(db), Some(t)) => {
+                format!("{}.{}", quote_ident(db_type, db), quote_ident(db_type, t))
+            }
+            (None, Some(t)) => quote_ident(db_type, t),
+        }),
+        _ => match (database, table) {
+            (_, Some(t)) => Ok(format!(
+                "TABLE {}.{}",
+                quote_ident(db_type, op.schema.as_deref().unwrap_or("public")),
+                quote_ident(db_type, t)
+            )),
+            (Some(db), None) => Ok(format!("DATABASE {}", quote_ident(db_type, db))),
+            (None, None) => Err("PostgreSQL 授权需要指定数据库或表".to_string()),
+        },
+    }
+}
+
+/// SQL for one operation. `masked` hides passwords, for previews.
+fn statement(db_type: &str, op: &UserOperation, masked: bool) -> Result<String, String> {
+    if op.name.trim().is_empty() {
+        return Err("用户名不能为空".to_string());
+    }
+    let who = account(db_type, op);
+    match (db_type, op.op_type.as_str()) {
+        ("mysql", "create_user") => Ok(format!(
+            "CREATE USER {} IDENTIFIED BY {}",
+            who,
+            password(db_type, op, masked)?
+        )),
+        (_, "create_user") => {
+            let login = if op.can_login.unwrap_or(true) {
+                "LOGIN"
+            } else {
+                "NOLOGIN"
+            };
+            Ok(match op.password.as_deref() {
+                Some(_) => format!(
+                    "CREATE ROLE {} WITH {} PASSWORD {}",
+                    who,
+                    login,
+                    password(db_type, op, masked)?
+                ),
+                None => format!("CREATE ROLE {} WITH {}", who, login),
+            })
+        }
+        ("mysql", "drop_user") => Ok(format!("DROP USER {}", who)),
+        (_, "drop_user") => Ok(format!("DROP ROLE {}", who)),
+        ("mysql", "set_password") => Ok(format!(
+            "ALTER USER {} IDENTIFIED BY {}",
+            who,
+            password(db_type, op, masked)?
+        )),
+        (_, "set_password") => Ok(format!(
+            "ALTER ROLE {} WITH PASSWORD {}",
+            who,
+            password(db_type, op, masked)?
+        )),
+        (_, "grant") => {
+            let grant_option = if op.with_grant_option.unwrap_or(false) {
+                " WITH GRANT OPTION"
+            } else {
+                ""
+            };
+            Ok(format!(
+                "GRANT {} ON {} TO {}{}",
+                privilege_list(db_type, op)?,
+                privilege_target(db_type, op)?,
+                who,
+                grant_option
+            ))
+        }
+        (_, "revoke") => Ok(format!(
+            "REVOKE {} ON {} FROM {}",
+            privilege_list(db_type, op)?,
+            privilege_target(db_type, op)?,
+            who
+        )),
+        (_, other) => Err(format!("Unknown operation: {}", other)),
+    }
+}
+
+/// Generate the statements for `operations` and run them in order unless `dry_run`.
+/// Returns the statements, with passwords masked. Stops at the first failure.
+pub async fn apply(
+    conn: &mut SqlConnection,
+    operations: &[UserOperation],
+    dry_run: bool,
+) -> Result<Vec<String>, String> {
+    let db_type = conn.db_type();
+    let preview = operations
+        .iter()
+        .map(|op| statement(db_type, op, true))
+        .collect::<Result<Vec<_>, _>>()?;
+    if dry_run {
+        return Ok(preview);
+    }
+    for (i, op) in operations.iter().enumerate() {
+        let sql = statement(db_type, op, false)?;
+        // GRANT / CREATE USER can't be prepared on MySQL, so these run unprepared
+        conn.execute(&sql)
+            .await
+            .map_err(|e| format!("第 {} 条语句执行失败 ({}): {}", i + 1, preview[i], e))?;
+    }
+    Ok(preview)
+}
+
+async fn mysql_users(conn: &mut SqlConnection) -> Result<Vec<DbUser>, String> {
+    let accounts = conn
+        .fetch_all(
+            "SELECT CONVERT(User USING utf8mb4) AS name, CONVERT(Host USING utf8mb4) AS host,
+                Super_priv AS super
+             FROM mysql.user ORDER BY User, Host",
+        )
+        .await?;
+    let mut users = Vec::new();
+    for row in &accounts {
+        let name = text_field(row, "name").unwrap_or_default();
+        let host = text_field(row, "host").unwrap_or_else(|| "%".to_string());
+        let sql = format!(
+            "SHOW GRANTS FOR {}@{}",
+            quote_str("mysql", &name),
+            quote_str("mysql", &host)
+        );
+        // Accounts with plugins this server can't describe still get listed, with the error
+        let (grants, grants_error) = match conn.fetch_all(&sql).await {
+            Ok(rows) => (
+                rows.into_iter()
+                    .filter_map(|g| {
+                        g.into_iter()
+                            .next()
+                            .and_then(|(_, v)| v.as_str().map(String::from))
+                    })
+                    .collect(),
+                None,
+            ),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+        users.push(DbUser {
+            name,
+            host: Some(host),
+            can_login: true,
+            superuser: text_field(row, "super").as_deref() == Some("Y"),
+            attributes: Vec::new(),
+            member_of: Vec::new(),
+            grants,
+            grants_error,
+        });
+    }
+    Ok(users)
+}
+
+async fn pg_users(conn: &mut SqlConnection) -> Result<Vec<DbUser>, String> {
+    let roles = conn
+        .fetch_all(
+            "SELECT r.rolname AS name, r.rolcanlogin AS can_login, r.rolsuper AS superuser,
+                concat_ws(',',
+                    CASE WHEN r.rolcreatedb THEN 'CREATEDB' END,
+                    CASE WHEN r.rolcreaterole THEN 'CREATEROLE' END,
+                    CASE WHEN r.rolreplication THEN 'REPLICATION' END,
+                    CASE WHEN r.rolbypassrls THEN 'BYPASSRLS' END,
+                    CASE WHEN r.rolvaliduntil IS NOT NULL THEN 'VALID UNTIL ' || r.rolvaliduntil END
+                ) AS attributes,
+                (SELECT string_agg(g.rolname, ',' ORDER BY g.rolname)
+                    FROM pg_auth_members m JOIN pg_roles g ON g.oid = m.roleid
+                    WHERE m.member = r.oid) AS member_of
+             FROM pg_roles r
+             WHERE r.rolname !~ '^pg_'
+             ORDER BY r.rolname",
+        )
+        .await?;
+    let grants = conn
+        .fetch_all(
+            "SELECT grantee, privileges || ' ON ' || target AS grant_text FROM (
+                SELECT grantee, table_schema || '.' || table_name AS target,
+                    string_agg(privilege_type, ', ' ORDER BY privilege_type) AS privileges
+                FROM information_schema.role_table_grants
+                WHERE table_schema NOT IN ('pg_catalog', 'information_schema')
+                GROUP BY grantee, table_schema, table_name
+                UNION ALL
+                SELECT grantee, table_schema || '.' || table_name || ' (' || column_name || ')',
+                    string_agg(privilege_type, ', ' ORDER BY privilege_type)
+                FROM information_schema.role_column_grants
+                WHERE table_schema NOT IN ('pg_catalog', 'information_schema')
+                GROUP BY grantee, table_schema, table_name, column_name
+            ) g ORDER BY grantee, target",
+        )
+        .await?;
+
+    let split = |v: Option<String>| -> Vec<String> {
+        v.map(|s| s.split(',').map(String::from).collect())
+            .unwrap_or_default()
+    };
+    Ok(roles
+        .iter()
+        .map(|row| {
+            let name = text_field(row, "name").unwrap_or_default();
+            DbUser {
+                grants: grants
+                    .iter()
+                    .filter(|g| text_field(g, "grantee").as_deref() == Some(name.as_str()))
+                    .filter_map(|g| text_field(g, "grant_text"))
+                    .collect(),
+                grants_error: None,
+                name,
+                host: None,
+                can_login: field(row, "can_login").as_bool().unwrap_or(false),
+                superuser: field(row, "superuser").as_bool().unwrap_or(false),
+                attributes: split(text_field(row, "attributes")),
+                member_of: split(text_field(row, "member_of")),
+            }
+        })
+        .collect())
+}
+
+/// Accounts / roles with their grants
+pub async fn list(conn: &mut SqlConnection) -> Result<Vec<DbUser>, String> {
+    match conn {
+        SqlConnection::MySql(_) => mysql_users(conn).await,
+        SqlConnection::Postgres(_) => pg_users(conn).await,
+    }
+}
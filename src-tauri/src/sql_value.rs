@@ -0,0 +1,398 @@
+use serde_json::{json, Value};
+use sqlx::mysql::MySqlRow;
+use sqlx::postgres::{PgRow, PgValueFormat};
+use sqlx::{Column, Row, TypeInfo, ValueRef};
+
+/// Whether values of a column type are read as "0x<hex>" binary strings
+pub fn is_binary_type(type_name: &str) -> bool {
+    let upper = type_name.to_uppercase();
+    upper.contains("BINARY") || upper.contains("BLOB") || upper.contains("BYTEA")
+}
+
+/// Preview of raw bytes no typed decode accepted, so the value is never silently lost
+fn raw_preview(bytes: &[u8]) -> Value {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => json!(text),
+        Err(_) => {
+            let hex: String = bytes
+                .iter()
+                .take(16)
+                .map(|b| format!("{:02X}", b))
+                .collect();
+            let suffix = if bytes.len() > 16 { "..." } else { "" };
+            json!(format!("[BLOB: 0x{}{}]", hex, suffix))
+        }
+    }
+}
+
+fn mysql_raw(row: &MySqlRow, idx: usize) -> Value {
+    // MySQL sends DECIMAL and every text-protocol value as text
+    match row.try_get_unchecked::<Option<Vec<u8>>, _>(idx) {
+        Ok(Some(bytes)) => raw_preview(&bytes),
+        _ => Value::Null,
+    }
+}
+
+fn pg_raw(row: &PgRow, idx: usize) -> Value {
+    match row.try_get_raw(idx) {
+        Ok(raw) if !raw.is_null() => match (raw.format(), raw.as_bytes()) {
+            (PgValueFormat::Text, Ok(bytes)) => raw_preview(bytes),
+            (PgValueFormat::Binary, Ok(bytes)) => {
+                let hex: String = bytes
+                    .iter()
+                    .take(16)
+                    .map(|b| format!("{:02X}", b))
+                    .collect();
+                let suffix = if bytes.len() > 16 { "..." } else { "" };
+                json!(format!("[BLOB: 0x{}{}]", hex, suffix))
+            }
+            _ => Value::Null,
+        },
+        _ => Value::Null,
+    }
+}
+
+/// Decimal text of a binary NUMERIC: base-10000 digit groups with weight, sign and scale
+fn pg_numeric_text(bytes: &[u8]) -> Option<String> {
+    let read = |i: usize| {
+        bytes
+            .get(i * 2..i * 2 + 2)
+            .map(|b| i16::from_be_bytes([b[0], b[1]]))
+    };
+    let ndigits = read(0)? as usize;
+    let weight = read(1)? as i32;
+    let sign = read(2)? as u16;
+    let scale = read(3)? as u16 as usize;
+    match sign {
+        0xC000 => return Some("NaN".to_string()),
+        0xD000 => return Some("Infinity".to_string()),
+        0xF000 => return Some("-Infinity".to_string()),
+        _ => {}
+    }
+    let digits: Vec<i16> = (0..ndigits).map(|i| read(4 + i)).collect::<Option<_>>()?;
+    let group = |i: i32| {
+        if i < 0 {
+            0
+        } else {
+            digits.get(i as usize).copied().unwrap_or(0)
+        }
+    };
+
+    let mut text = String::new();
+    if sign == 0x4000 {
+        text.push('-');
+    }
+    if weight < 0 {
+        text.push('0');
+    } else {
+        text.push_str(&group(0).to_string());
+        for i in 1..=weight {
+            text.push_str(&format!("{:04}", group(i)));
+        }
+    }
+    if scale > 0 {
+        let mut fraction = String::new();
+        let mut i = weight + 1;
+        while fraction.len() < scale {
+            fraction.push_str(&format!("{:04}", group(i)));
+            i += 1;
+        }
+        fraction.truncate(scale);
+        text.push('.');
+        text.push_str(&fraction);
+    }
+    Some(text)
+}
+
+fn pg_numeric(row: &PgRow, idx: usize) -> Value {
+    match row.try_get_raw(idx) {
+        Ok(raw) if raw.is_null() => Value::Null,
+        // Kept as text so no precision is lost
+        Ok(raw) => match (raw.format(), raw.as_bytes()) {
+            (PgValueFormat::Binary, Ok(bytes)) => match pg_numeric_text(bytes) {
+                Some(text) => json!(text),
+                None => pg_raw(row, idx),
+            },
+            _ => pg_raw(row, idx),
+        },
+        Err(_) => Value::Null,
+    }
+}
+
+/// Convert a MySQL row into (column, value) pairs in column order.
+/// Binary values are shown as a truncated hex preview unless `full_binary` is set.
+pub fn mysql_row_values(row: &MySqlRow, full_binary: bool) -> Vec<(String, Value)> {
+    let limit = if full_binary { usize::MAX } else { 32 };
+    let mut values = Vec::new();
+    for col in row.columns() {
+        let name = col.name();
+        let type_name = col.type_info().name();
+
+        let value: Value = match type_name {
+            "BOOLEAN" | "BOOL" => match row.try_get::<Option<bool>, _>(col.ordinal()) {
+                Ok(v) => json!(v),
+                Err(_) => mysql_raw(row, col.ordinal()),
+            },
+            _ if type_name.starts_with("TINYINT")
+                || type_name.starts_with("SMALLINT")
+                || type_name.starts_with("INT")
+                || type_name.starts_with("INTEGER")
+                || type_name.starts_with("BIGINT")
+                || type_name.starts_with("MEDIUMINT")
+                || type_name == "INT4"
+                || type_name == "INT8" =>
+            {
+                // Try i64 first (handles TINYINT(1), INT(11), etc.)
+                if let Ok(v) = row.try_get::<Option<i64>, _>(col.ordinal()) {
+                    json!(v)
+                } else if let Ok(v) = row.try_get::<Option<u64>, _>(col.ordinal()) {
+                    json!(v)
+                } else if let Ok(v) = row.try_get::<Option<i32>, _>(col.ordinal()) {
+                    json!(v)
+                } else if let Ok(v) = row.try_get::<Option<i8>, _>(col.ordinal()) {
+                    json!(v)
+                } else {
+                    mysql_raw(row, col.ordinal())
+                }
+            }
+            "FLOAT" | "DOUBLE" | "REAL" => {
+                if let Ok(v) = row.try_get::<Option<f64>, _>(col.ordinal()) {
+                    json!(v)
+                } else if let Ok(v) = row.try_get::<Option<f32>, _>(col.ordinal()) {
+                    // Through the shortest f32 text, so 0.1 doesn't become 0.10000000149
+                    json!(v.and_then(|f| f.to_string().parse::<f64>().ok()))
+                } else {
+                    mysql_raw(row, col.ordinal())
+                }
+            }
+            // Exact decimal text, without a decimal crate
+            "DECIMAL" | "NUMERIC" => mysql_raw(row, col.ordinal()),
+            "BIT" => {
+                // BIT often comes as bytes or int depending on driver/length
+                // Try u64 first
+                if let Ok(v) = row.try_get::<Option<u64>, _>(col.ordinal()) {
+                    json!(v)
+                } else {
+                    // Try bytes
+                    match row.try_get::<Option<Vec<u8>>, _>(col.ordinal()) {
+                        Ok(Some(v)) => {
+                            // Simple binary string like "0x..."
+                            let hex: String = v.iter().map(|b| format!("{:02X}", b)).collect();
+                            json!(format!("0x{}", hex))
+                        }
+                        Ok(None) => Value::Null,
+                        Err(_) => mysql_raw(row, col.ordinal()),
+                    }
+                }
+            }
+            "JSON" => {
+                // Requires sqlx json feature
+                match row.try_get::<Option<serde_json::Value>, _>(col.ordinal()) {
+                    Ok(v) => json!(v),
+                    Err(_) => mysql_raw(row, col.ordinal()),
+                }
+            }
+            "TIMESTAMP" | "DATETIME" => {
+                match row.try_get::<Option<chrono::NaiveDateTime>, _>(col.ordinal()) {
+                    Ok(Some(v)) => json!(v.to_string()),
+                    Ok(None) => Value::Null,
+                    Err(_) => {
+                        // Fallback if it's maybe a string already?
+                        match row.try_get::<Option<String>, _>(col.ordinal()) {
+                            Ok(v) => json!(v),
+                            Err(_) => mysql_raw(row, col.ordinal()),
+                        }
+                    }
+                }
+            }
+            "DATE" => match row.try_get::<Option<chrono::NaiveDate>, _>(col.ordinal()) {
+                Ok(Some(v)) => json!(v.to_string()),
+                Ok(None) => Value::Null,
+                Err(_) => mysql_raw(row, col.ordinal()),
+            },
+            // TIME can be negative or beyond 24h, which NaiveTime can't hold
+            "TIME" => match row.try_get::<Option<chrono::NaiveTime>, _>(col.ordinal()) {
+                Ok(Some(v)) => json!(v.to_string()),
+                Ok(None) => Value::Null,
+                Err(_) => mysql_raw(row, col.ordinal()),
+            },
+            "YEAR" => {
+                match row.try_get::<Option<i32>, _>(col.ordinal()) {
+                    Ok(Some(v)) => json!(v),
+                    Ok(None) => Value::Null, // Or string
+                    Err(_) => mysql_raw(row, col.ordinal()),
+                }
+            }
+            _ if is_binary_type(type_name) => {
+                // Handle binary types: VARBINARY, BINARY, BLOB, TINYBLOB, MEDIUMBLOB, LONGBLOB, BYTEA (PG)
+                match row.try_get::<Option<Vec<u8>>, _>(col.ordinal()) {
+                    Ok(Some(v)) => {
+                        // Display as hex, truncated for readability
+                        let hex: String =
+                            v.iter().take(limit).map(|b| format!("{:02X}", b)).collect();
+                        let suffix = if v.len() > limit {
+                            format!("... ({} bytes)", v.len())
+                        } else {
+                            String::new()
+                        };
+                        json!(format!("0x{}{}", hex, suffix))
+                    }
+                    Ok(None) => Value::Null,
+                    Err(_) => mysql_raw(row, col.ordinal()),
+                }
+            }
+            _ => {
+                // Fallback to string for TEXT, VARCHAR, etc.
+                match row.try_get::<Option<String>, _>(col.ordinal()) {
+                    Ok(v) => json!(v),
+                    Err(_) => mysql_raw(row, col.ordinal()),
+                }
+            }
+        };
+        values.push((name.to_string(), value));
+    }
+    values
+}
+
+/// Convert a PostgreSQL row into (column, value) pairs in column order.
+/// Binary values are shown as a truncated hex preview unless `full_binary` is set.
+pub fn pg_row_values(row: &PgRow, full_binary: bool) -> Vec<(String, Value)> {
+    let limit = if full_binary { usize::MAX } else { 32 };
+    let mut values = Vec::new();
+    for col in row.columns() {
+        let name = col.name();
+        let type_name = col.type_info().name();
+
+        let value: Value = match type_name {
+            // sqlx only decodes the exact width, e.g. int4 as i32
+            "BOOL" => match row.try_get::<Option<bool>, _>(col.ordinal()) {
+                Ok(v) => json!(v),
+                Err(_) => pg_raw(row, col.ordinal()),
+            },
+            "INT2" => match row.try_get::<Option<i16>, _>(col.ordinal()) {
+                Ok(v) => json!(v),
+                Err(_) => pg_raw(row, col.ordinal()),
+            },
+            "INT4" => match row.try_get::<Option<i32>, _>(col.ordinal()) {
+                Ok(v) => json!(v),
+                Err(_) => pg_raw(row, col.ordinal()),
+            },
+            "INT8" => match row.try_get::<Option<i64>, _>(col.ordinal()) {
+                Ok(v) => json!(v),
+                Err(_) => pg_raw(row, col.ordinal()),
+            },
+            "OID" => match row.try_get::<Option<sqlx::postgres::types::Oid>, _>(col.ordinal()) {
+                Ok(v) => json!(v.map(|oid| oid.0)),
+                Err(_) => pg_raw(row, col.ordinal()),
+            },
+            "FLOAT4" => match row.try_get::<Option<f32>, _>(col.ordinal()) {
+                // Through the shortest f32 text, so 0.1 doesn't become 0.10000000149
+                Ok(v) => json!(v.and_then(|f| f.to_string().parse::<f64>().ok())),
+                Err(_) => pg_raw(row, col.ordinal()),
+            },
+            "FLOAT8" => match row.try_get::<Option<f64>, _>(col.ordinal()) {
+                Ok(v) => json!(v),
+                Err(_) => pg_raw(row, col.ordinal()),
+            },
+            "NUMERIC" => pg_numeric(row, col.ordinal()),
+            "MONEY" => {
+                match row.try_get::<Option<sqlx::postgres::types::PgMoney>, _>(col.ordinal()) {
+                    // Binary money is in cents; assumes the usual two fraction digits
+                    Ok(Some(money)) => {
+                        let cents = money.0;
+                        let sign = if cents < 0 { "-" } else { "" };
+                        json!(format!(
+                            "{}{}.{:02}",
+                            sign,
+                            (cents / 100).unsigned_abs(),
+                            (cents % 100).unsigned_abs()
+                        ))
+                    }
+                    Ok(None) => Value::Null,
+                    Err(_) => pg_raw(row, col.ordinal()),
+                }
+            }
+            "TIMESTAMP" | "TIMESTAMPTZ" => {
+                // Use chrono::NaiveDateTime or DateTime<Utc>
+                // sqlx maps TIMESTAMP -> NaiveDateTime, TIMESTAMPTZ -> DateTime<Utc> or DateTime<Local>
+                // We try generic string first, if that fails, we try specific types
+                if let Ok(v) = row.try_get::<Option<String>, _>(col.ordinal()) {
+                    json!(v)
+                } else if let Ok(v) = row.try_get::<Option<chrono::NaiveDateTime>, _>(col.ordinal())
+                {
+                    json!(v.map(|d| d.to_string()))
+                } else if let Ok(v) =
+                    row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>(col.ordinal())
+                {
+                    json!(v.map(|d| d.to_string()))
+                } else {
+                    pg_raw(row, col.ordinal())
+                }
+            }
+            "DATE" => {
+                if let Ok(v) = row.try_get::<Option<String>, _>(col.ordinal()) {
+                    json!(v)
+                } else if let Ok(v) = row.try_get::<Option<chrono::NaiveDate>, _>(col.ordinal()) {
+                    json!(v.map(|d| d.to_string()))
+                } else {
+                    pg_raw(row, col.ordinal())
+                }
+            }
+            "TIME" | "TIMETZ" => {
+                if let Ok(v) = row.try_get::<Option<String>, _>(col.ordinal()) {
+                    json!(v)
+                } else if let Ok(v) = row.try_get::<Option<chrono::NaiveTime>, _>(col.ordinal()) {
+                    json!(v.map(|d| d.to_string()))
+                } else {
+                    pg_raw(row, col.ordinal())
+                }
+            }
+            "JSON" | "JSONB" => {
+                if let Ok(v) = row.try_get::<Option<serde_json::Value>, _>(col.ordinal()) {
+                    json!(v)
+                } else if let Ok(v) = row.try_get::<Option<String>, _>(col.ordinal()) {
+                    json!(v)
+                } else {
+                    pg_raw(row, col.ordinal())
+                }
+            }
+            "BYTEA" | "VARBINARY" | "BINARY" | "BLOB" => {
+                // Handle binary types explicitly for Postgres/Generic
+                match row.try_get::<Option<Vec<u8>>, _>(col.ordinal()) {
+                    Ok(Some(v)) => {
+                        // Display as hex, truncated for readability
+                        let hex: String =
+                            v.iter().take(limit).map(|b| format!("{:02X}", b)).collect();
+                        let suffix = if v.len() > limit {
+                            format!("... ({} bytes)", v.len())
+                        } else {
+                            String::new()
+                        };
+                        json!(format!("0x{}{}", hex, suffix))
+                    }
+                    Ok(None) => Value::Null,
+                    Err(_) => pg_raw(row, col.ordinal()),
+                }
+            }
+            // sqlx's uuid feature is off, so read the 16 raw bytes
+            "UUID" => match row.try_get_raw(col.ordinal()) {
+                Ok(raw) if raw.format() == PgValueFormat::Binary && !raw.is_null() => {
+                    match raw.as_bytes().map(uuid::Uuid::from_slice) {
+                        Ok(Ok(u)) => json!(u.to_string()),
+                        _ => pg_raw(row, col.ordinal()),
+                    }
+                }
+                _ => pg_raw(row, col.ordinal()),
+            },
+            _ => {
+                // PG also calls text TEXT, varchar VARCHAR
+                match row.try_get::<Option<String>, _>(col.ordinal()) {
+                    Ok(v) => json!(v),
+                    Err(_) => pg_raw(row, col.ordinal()),
+                }
+            }
+        };
+        values.push((name.to_string(), value));
+    }
+    values
+}
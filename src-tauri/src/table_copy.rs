@@ -0,0 +1,286 @@
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::jobs::JobHandle;
+use crate::masking::Masker;
+use crate::sql_conn::SqlConnection;
+use crate::sql_dialect;
+use crate::{ColumnDef, ConnectionConfig};
+
+/// Most bind parameters a single statement may carry (MySQL and PostgreSQL both cap at 65535)
+const MAX_PARAMS: usize = 65535;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CopyOptions {
+    pub source_database: Option<String>,
+    pub target_database: Option<String>,
+    pub where_clause: Option<String>, // Filter on the source rows, without the WHERE keyword
+    pub truncate_first: Option<bool>, // Empty the target table before copying
+    pub create_table: Option<bool>,   // Create the target table with mapped types if missing
+    pub batch_size: Option<usize>,    // Rows per INSERT / transaction, default 500
+}
+
+/// Columns with their exact type: `format_type` on PostgreSQL, COLUMN_TYPE on MySQL
+async fn columns_with_types(
+    conn: &mut SqlConnection,
+    config: &ConnectionConfig,
+    table: &str,
+    database: Option<String>,
+) -> Result<Vec<ColumnDef>, String> {
+    let mut columns = crate::get_columns(config.clone(), table.to_string(), database).await?;
+    let types = conn.pg_column_types(table).await?;
+    for col in &mut columns {
+        if let Some((_, t)) = types.iter().find(|(name, _)| *name == col.name) {
+            col.type_name = t.clone();
+        }
+    }
+    Ok(columns)
+}
+
+/// CREATE TABLE on the target with the source columns translated to its dialect.
+/// Defaults and auto-increment are dialect specific and not carried over.
+fn create_table_sql(
+    source_db: &str,
+    target_db: &str,
+    table: &str,
+    columns: &[ColumnDef],
+) -> String {
+    let q = |name: &str| sql_dialect::quote_ident(target_db, name);
+    let mut defs: Vec<String> = columns
+        .iter()
+        .map(|col| {
+            let mut type_name = sql_dialect::map_type(source_db, target_db, &col.type_name);
+            // MySQL can't index TEXT without a prefix length
+            if col.is_pk && target_db == "mysql" && type_name.ends_with("text") {
+                type_name = "varchar(255)".to_string();
+            }
+            let not_null = if col.is_nullable == Some(false) {
+                " NOT NULL"
+            } else {
+                ""
+            };
+            format!("  {} {}{}", q(&col.name), type_name, not_null)
+        })
+        .collect();
+    let pk: Vec<String> = columns
+        .iter()
+        .filter(|c| c.is_pk)
+        .map(|c| q(&c.name))
+        .collect();
+    if !pk.is_empty() {
+        defs.push(format!("  PRIMARY KEY ({})", pk.join(", ")));
+    }
+    format!("CREATE TABLE {} (\n{}\n)", q(table), defs.join(",\n"))
+}
+
+/// Source select expression returning the value as text, binary as hex
+fn select_expr(db_type: &str, col: &ColumnDef) -> String {
+    let name = sql_dialect::quote_ident(db_type, &col.name);
+    let binary = sql_dialect::is_binary_type(&col.type_name);
+    let lower = col.type_name.to_lowercase();
+    let expr = match db_type {
+        "mysql" if binary => format!("HEX({})", name),
+        "mysql" if lower.starts_with("bit") => format!("CAST({} + 0 AS CHAR)", name),
+        "mysql" => format!("CAST({} AS CHAR)", name),
+        _ if binary => format!("encode({}, 'hex')", name),
+        // 1 / 0 is accepted by both PostgreSQL boolean and MySQL tinyint(1)
+        _ if lower == "boolean" => format!("{}::int::text", name),
+        _ => format!("{}::text", name),
+    };
+    format!("{} AS {}", expr, name)
+}
+
+/// Fixed-length BIT(n), read as a number from MySQL and as binary digits from PostgreSQL
+fn is_bit(col: &ColumnDef) -> bool {
+    let lower = col.type_name.to_lowercase();
+    lower == "bit" || lower.starts_with("bit(")
+}
+
+/// Target value expression for a text parameter
+fn insert_template(
+    source_db: &str,
+    target_db: &str,
+    source: &ColumnDef,
+    target: &ColumnDef,
+) -> String {
+    let binary = sql_dialect::is_binary_type(&source.type_name)
+        && sql_dialect::is_binary_type(&target.type_name);
+    match target_db {
+        "mysql" if binary => "UNHEX({})".to_string(),
+        // A string bound to a MySQL BIT column is stored as its bytes
+        "mysql" if is_bit(target) && source_db != "mysql" && is_bit(source) => {
+            "CAST(CONV({}, 2, 10) AS UNSIGNED)".to_string()
+        }
+        "mysql" if is_bit(target) => "CAST({} AS UNSIGNED)".to_string(),
+        "mysql" => "{}".to_string(),
+        _ if binary => "decode({}, 'hex')".to_string(),
+        _ if is_bit(target) && source_db == "mysql" => {
+            format!("CAST(CAST({{}} AS bigint) AS {})", target.type_name)
+        }
+        _ => format!("CAST({{}} AS {})", target.type_name),
+    }
+}
+
+/// Copy rows of `source_table` into `target_table`, one transaction per batch.
+/// Rows are masked by the source connection's saved masking rules.
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    job: JobHandle,
+    mut source: SqlConnection,
+    source_config: ConnectionConfig,
+    source_table: String,
+    mut target: SqlConnection,
+    target_config: ConnectionConfig,
+    target_table: String,
+    options: CopyOptions,
+    masker: Masker,
+) -> Result<u64, String> {
+    let source_db = source.db_type();
+    let target_db = target.db_type();
+
+    let source_columns = columns_with_types(
+        &mut source,
+        &source_config,
+        &source_table,
+        options.source_database.clone(),
+    )
+    .await?;
+    if source_columns.is_empty() {
+        return Err(format!("源表 {} 不存在或没有列", source_table));
+    }
+
+    let mut target_columns = columns_with_types(
+        &mut target,
+        &target_config,
+        &target_table,
+        options.target_database.clone(),
+    )
+    .await?;
+    if target_columns.is_empty() {
+        if !options.create_table.unwrap_or(false) {
+            return Err(format!("目标表 {} 不存在", target_table));
+        }
+        let ddl = create_table_sql(source_db, target_db, &target_table, &source_columns);
+        target.execute(&ddl).await?;
+        target_columns = columns_with_types(
+            &mut target,
+            &target_config,
+            &target_table,
+            options.target_database.clone(),
+        )
+        .await?;
+    }
+
+    // Only columns present on both sides are copied
+    let pairs: Vec<(&ColumnDef, &ColumnDef)> = source_columns
+        .iter()
+        .filter_map(|s| {
+            let t = target_columns
+                .iter()
+                .find(|t| t.name.eq_ignore_ascii_case(&s.name))?;
+            Some((s, t))
+        })
+        .collect();
+    if pairs.is_empty() {
+        return Err("源表与目标表没有同名的列".to_string());
+    }
+
+    if options.truncate_first.unwrap_or(false) {
+        target
+            .execute(&format!(
+                "TRUNCATE TABLE {}",
+                sql_dialect::quote_ident(target_db, &target_table)
+            ))
+            .await?;
+    }
+
+    let from = sql_dialect::quote_ident(source_db, &source_table);
+    let filter = match options.where_clause.as_deref().map(str::trim) {
+        Some(w) if !w.is_empty() => format!(" WHERE {}", w),
+        _ => String::new(),
+    };
+
+    let total = {
+        let count_sql = format!("SELECT COUNT(*) AS cnt FROM {}{}", from, filter);
+        let mut rows = source.fetch_values(&count_sql);
+        match rows.next().await {
+            Some(row) => row?.first().and_then(|(_, v)| v.as_u64()),
+            None => None,
+        }
+    };
+
+    let select = pairs
+        .iter()
+        .map(|(s, _)| select_expr(source_db, s))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!("SELECT {} FROM {}{}", select, from, filter);
+
+    let columns: Vec<String> = pairs.iter().map(|(_, t)| t.name.clone()).collect();
+    let templates: Vec<String> = pairs
+        .iter()
+        .map(|(s, t)| insert_template(source_db, target_db, s, t))
+        .collect();
+    let batch_size = options
+        .batch_size
+        .unwrap_or(500)
+        .clamp(1, MAX_PARAMS / columns.len());
+    let full_batch = sql_dialect::insert_params_statement(
+        target_db,
+        &target_table,
+        &columns,
+        &templates,
+        batch_size,
+    );
+
+    let types = source_columns
+        .iter()
+        .map(|c| (c.name.clone(), c.type_name.clone()))
+        .collect();
+    let masker = masker.for_table(&source_table, types);
+    let source_names: Vec<String> = pairs.iter().map(|(s, _)| s.name.clone()).collect();
+    let mut copied = 0;
+    let mut params: Vec<Option<String>> = Vec::with_capacity(batch_size * columns.len());
+    let mut pending = 0;
+    let mut rows = source.fetch_values(&sql);
+    while let Some(row) = rows.next().await {
+        let mut values: Vec<Value> = row?.into_iter().map(|(_, v)| v).collect();
+        masker.mask_row(&source_names, &mut values);
+        for value in values {
+            params.push(match value {
+                Value::Null => None,
+                Value::String(s) => Some(s),
+                other => Some(other.to_string()),
+            });
+        }
+        pending += 1;
+        if pending == batch_size {
+            job.check_cancelled()?;
+            target
+                .execute_in_transaction(&full_batch, &params)
+                .await
+                .map_err(|e| format!("写入目标表失败 (已复制 {} 行): {}", copied, e))?;
+            copied += pending as u64;
+            params.clear();
+            pending = 0;
+            job.progress(copied, total, None);
+        }
+    }
+    if pending > 0 {
+        job.check_cancelled()?;
+        let stmt = sql_dialect::insert_params_statement(
+            target_db,
+            &target_table,
+            &columns,
+            &templates,
+            pending,
+        );
+        target
+            .execute_in_transaction(&stmt, &params)
+            .await
+            .map_err(|e| format!("写入目标表失败 (已复制 {} 行): {}", copied, e))?;
+        copied += pending as u64;
+    }
+    Ok(copied)
+}
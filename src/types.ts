@@ -7,4 +7,8 @@ export interface ConnectionConfig {
     username?: string
     password?: string
     database?: string
+    redis_mode?: 'standalone' | 'cluster' | 'sentinel'
+    redis_nodes?: string[]
+    sentinel_master?: string
+    redis_resp3?: boolean
 }